@@ -0,0 +1,155 @@
+//! Concurrent reachability checks for external links in `mdi links --check`
+
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+use ureq::Agent;
+
+/// How to probe external URLs
+pub struct CheckOptions {
+    /// Timeout for each request, including redirects
+    pub timeout: Duration,
+    /// Extra attempts after a network error or a 429/5xx response
+    pub retries: u32,
+    /// Number of requests in flight at once
+    pub jobs: usize,
+}
+
+/// Probe a URL once, returning an error message if it is unreachable
+fn probe(agent: &Agent, url: &str) -> Result<(), String> {
+    let status = agent
+        .head(url)
+        .call()
+        .map_err(|err| err.to_string())?
+        .status();
+
+    // Some servers don't implement HEAD, so ask again with GET
+    let status = if status == 405 || status == 501 {
+        agent
+            .get(url)
+            .call()
+            .map_err(|err| err.to_string())?
+            .status()
+    } else {
+        status
+    };
+
+    if status.is_client_error() || status.is_server_error() {
+        Err(format!("HTTP {}", status.as_u16()))
+    } else {
+        Ok(())
+    }
+}
+
+/// Whether a failed probe is worth another attempt
+fn is_transient(error: &str) -> bool {
+    !error.starts_with("HTTP 4") || error == "HTTP 429"
+}
+
+/// Check every URL, returning the unreachable ones with the reason
+///
+/// Results are in the order of `urls`.
+pub fn check_urls(urls: &[String], options: &CheckOptions) -> Vec<(String, String)> {
+    let agent: Agent = Agent::config_builder()
+        .timeout_global(Some(options.timeout))
+        .http_status_as_error(false)
+        .build()
+        .into();
+    let next = AtomicUsize::new(0);
+    let failures = Mutex::new(Vec::new());
+
+    thread::scope(|scope| {
+        for _ in 0..options.jobs.clamp(1, urls.len().max(1)) {
+            scope.spawn(|| {
+                while let Some(url) = urls.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    let mut result = probe(&agent, url);
+                    for _ in 0..options.retries {
+                        match &result {
+                            Err(error) if is_transient(error) => result = probe(&agent, url),
+                            _ => break,
+                        }
+                    }
+                    if let Err(error) = result {
+                        failures.lock().unwrap().push((url.clone(), error));
+                    }
+                }
+            });
+        }
+    });
+
+    let mut failures = failures.into_inner().unwrap();
+    failures.sort_by_key(|(url, _)| urls.iter().position(|u| u == url));
+    failures
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    /// Serve HTTP on a local port, answering `/NNN...` paths with status NNN
+    /// and everything else with 200
+    fn status_server() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut request_line = String::new();
+                let mut reader = BufReader::new(&stream);
+                reader.read_line(&mut request_line).unwrap();
+                // Drain the headers before answering
+                let mut header = String::new();
+                while reader.read_line(&mut header).unwrap() > 2 {
+                    header.clear();
+                }
+
+                let path = request_line.split(' ').nth(1).unwrap_or("/");
+                let status = path.get(1..4).filter(|code| code.parse::<u16>().is_ok());
+                let status = status.unwrap_or("200");
+                write!(
+                    stream,
+                    "HTTP/1.1 {} Status\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                    status
+                )
+                .unwrap();
+            }
+        });
+        format!("http://{}", address)
+    }
+
+    #[test]
+    fn test_is_transient() {
+        assert!(is_transient("HTTP 500"));
+        assert!(is_transient("HTTP 503"));
+        assert!(is_transient("HTTP 429"));
+        assert!(is_transient("io: Connection refused"));
+        assert!(!is_transient("HTTP 404"));
+        assert!(!is_transient("HTTP 410"));
+    }
+
+    #[test]
+    fn test_failures_in_input_order() {
+        let base = status_server();
+        let urls: Vec<String> = ["/404/a", "/ok", "/500", "/410", "/fine", "/404/b"]
+            .iter()
+            .map(|path| format!("{}{}", base, path))
+            .collect();
+        let options = CheckOptions {
+            timeout: Duration::from_secs(5),
+            retries: 0,
+            jobs: 4,
+        };
+
+        let failures = check_urls(&urls, &options);
+        let expected = [
+            (urls[0].clone(), "HTTP 404".to_string()),
+            (urls[2].clone(), "HTTP 500".to_string()),
+            (urls[3].clone(), "HTTP 410".to_string()),
+            (urls[5].clone(), "HTTP 404".to_string()),
+        ];
+        assert_eq!(failures, expected);
+    }
+}
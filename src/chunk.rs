@@ -0,0 +1,215 @@
+//! Splitting documents into size-limited chunks along section boundaries
+
+use crate::parse_headings;
+use crate::search::ancestry_at;
+
+/// Options for [`chunk_document`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkOptions {
+    /// Soft size limit for each chunk, in characters
+    pub max_chars: usize,
+    /// Headings at this level or shallower always start a new chunk
+    pub split_on_level: u8,
+}
+
+impl Default for ChunkOptions {
+    fn default() -> Self {
+        Self {
+            max_chars: 2000,
+            split_on_level: 2,
+        }
+    }
+}
+
+/// A piece of a document produced by [`chunk_document`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Chunk {
+    /// Texts of the headings containing the chunk, outermost first
+    pub heading_path: Vec<String>,
+    /// First line of the chunk (1-indexed)
+    pub start_line: usize,
+    /// Last line of the chunk (inclusive)
+    pub end_line: usize,
+    /// Chunk text, without trailing blank lines
+    pub text: String,
+}
+
+/// Character count of a line range joined with newlines
+fn range_len(lines: &[&str], range: (usize, usize)) -> usize {
+    let (start, end) = range;
+    lines[start..end]
+        .iter()
+        .map(|l| l.chars().count() + 1)
+        .sum()
+}
+
+/// Greedily pack consecutive ranges into groups no larger than `max_chars`
+fn pack(lines: &[&str], ranges: &[(usize, usize)], max_chars: usize) -> Vec<(usize, usize)> {
+    let mut packed: Vec<(usize, usize)> = Vec::new();
+
+    for &range in ranges {
+        match packed.last_mut() {
+            Some(last) if range_len(lines, (last.0, range.1)) <= max_chars => last.1 = range.1,
+            _ => packed.push(range),
+        }
+    }
+
+    packed
+}
+
+/// Split a range at blank lines into paragraph ranges (blank lines stay with the
+/// paragraph before them)
+fn paragraphs(lines: &[&str], (start, end): (usize, usize)) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+    let mut para_start = start;
+
+    for idx in start + 1..end {
+        if lines[idx - 1].trim().is_empty() && !lines[idx].trim().is_empty() {
+            ranges.push((para_start, idx));
+            para_start = idx;
+        }
+    }
+    ranges.push((para_start, end));
+    ranges
+}
+
+/// Split a document into chunks along heading boundaries
+///
+/// Headings at `split_on_level` or shallower always start a new chunk. A section
+/// larger than `max_chars` is split further at its deeper headings, packing
+/// consecutive subsections together while they fit, and a single heading with
+/// an oversized body is split between paragraphs. A heading always stays in
+/// the same chunk as the start of its body, so chunks can exceed `max_chars`
+/// when a paragraph alone is larger. Each chunk carries the breadcrumb of
+/// headings that contain it.
+pub fn chunk_document(content: &str, opts: &ChunkOptions) -> Vec<Chunk> {
+    let headings = parse_headings(content);
+    let lines: Vec<&str> = content.lines().collect();
+
+    // Start lines (0-indexed) of every heading block, including the preamble
+    let mut starts: Vec<usize> = headings.iter().map(|h| h.line_number - 1).collect();
+    if starts.first() != Some(&0) {
+        starts.insert(0, 0);
+    }
+    let block_ranges: Vec<(usize, usize)> = starts
+        .iter()
+        .zip(starts.iter().skip(1).chain(std::iter::once(&lines.len())))
+        .map(|(&s, &e)| (s, e))
+        .collect();
+    let splits_here = |start: usize| {
+        headings
+            .iter()
+            .find(|h| h.line_number - 1 == start)
+            .is_some_and(|h| h.level <= opts.split_on_level)
+    };
+
+    // Group blocks into top-level segments, then shrink oversized ones
+    let mut segments: Vec<Vec<(usize, usize)>> = Vec::new();
+    for &block in &block_ranges {
+        match segments.last_mut() {
+            Some(segment) if !splits_here(block.0) => segment.push(block),
+            _ => segments.push(vec![block]),
+        }
+    }
+
+    let mut pieces = Vec::new();
+    for segment in segments {
+        let whole = (segment[0].0, segment[segment.len() - 1].1);
+        if range_len(&lines, whole) <= opts.max_chars {
+            pieces.push(whole);
+            continue;
+        }
+
+        for group in pack(&lines, &segment, opts.max_chars) {
+            if range_len(&lines, group) <= opts.max_chars {
+                pieces.push(group);
+            } else {
+                // Keep a heading together with its first paragraph
+                let mut paras = paragraphs(&lines, group).into_iter();
+                let mut merged = Vec::new();
+                while let Some(mut para) = paras.next() {
+                    let heading_only = starts.contains(&para.0)
+                        && lines[para.0 + 1..para.1]
+                            .iter()
+                            .all(|l| l.trim().is_empty());
+                    if heading_only && let Some(next) = paras.next() {
+                        para.1 = next.1;
+                    }
+                    merged.push(para);
+                }
+                pieces.extend(pack(&lines, &merged, opts.max_chars));
+            }
+        }
+    }
+
+    pieces
+        .into_iter()
+        .filter_map(|(start, end)| {
+            let text = lines[start..end].join("\n");
+            let text = text.trim_end();
+            if text.trim().is_empty() {
+                return None;
+            }
+            let end_line = start + text.lines().count();
+            Some(Chunk {
+                heading_path: ancestry_at(&headings, start + 1)
+                    .iter()
+                    .map(|h| h.text.clone())
+                    .collect(),
+                start_line: start + 1,
+                end_line,
+                text: text.to_string(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_on_level() {
+        let content = "Preamble\n\n# Guide\n\nintro\n\n## Install\n\nsteps\n\n### Linux\n\napt\n\n## Usage\n\nrun\n";
+        let chunks = chunk_document(content, &ChunkOptions::default());
+
+        let summary: Vec<_> = chunks
+            .iter()
+            .map(|c| (c.heading_path.join(" > "), c.start_line, c.end_line))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (String::new(), 1, 1),
+                ("Guide".to_string(), 3, 5),
+                ("Guide > Install".to_string(), 7, 13),
+                ("Guide > Usage".to_string(), 15, 17),
+            ]
+        );
+        assert_eq!(chunks[3].text, "## Usage\n\nrun");
+    }
+
+    #[test]
+    fn test_chunk_oversized_section() {
+        let content =
+            "# Big\n\nfirst paragraph here\n\nsecond paragraph here\n\n## Sub\n\nsub text\n";
+        let opts = ChunkOptions {
+            max_chars: 25,
+            split_on_level: 1,
+        };
+        let chunks = chunk_document(content, &opts);
+
+        let texts: Vec<_> = chunks.iter().map(|c| c.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            [
+                "# Big\n\nfirst paragraph here",
+                "second paragraph here",
+                "## Sub\n\nsub text"
+            ]
+        );
+        assert_eq!(chunks[1].heading_path, ["Big"]);
+        assert_eq!(chunks[2].heading_path, ["Big", "Sub"]);
+    }
+}
@@ -0,0 +1,97 @@
+//! Fenced code block extraction
+
+use crate::scan::{CodeBlockTracker, indent_width, parse_fence};
+
+/// A fenced code block found in a document
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CodeBlock {
+    /// Line number of the opening fence (1-indexed)
+    pub start_line: usize,
+    /// Line number of the closing fence, or the last line if the block is never closed
+    pub end_line: usize,
+    /// Full info string after the opening fence (e.g. `rust ignore`)
+    pub info: String,
+    /// Block contents without the fences
+    pub body: String,
+}
+
+impl CodeBlock {
+    /// The language, i.e. the first word of the info string
+    pub fn language(&self) -> Option<&str> {
+        self.info.split_whitespace().next()
+    }
+}
+
+/// Remove up to `indent` columns of leading spaces, as CommonMark does for fence content
+fn strip_fence_indent(line: &str, indent: usize) -> &str {
+    let spaces = line.len() - line.trim_start_matches(' ').len();
+    &line[spaces.min(indent)..]
+}
+
+/// Extract all fenced code blocks from markdown content
+///
+/// Indented code blocks are skipped since they carry no info string. A fence
+/// that is never closed runs to the end of the document.
+pub fn parse_code_blocks(content: &str) -> Vec<CodeBlock> {
+    let mut code = CodeBlockTracker::default();
+    let mut blocks = Vec::new();
+    let mut open: Option<(CodeBlock, usize, Vec<&str>)> = None;
+
+    for (idx, line) in content.lines().enumerate() {
+        let line_number = idx + 1;
+        let was_fenced = code.in_fence();
+        code.is_code(line);
+
+        if !was_fenced && code.in_fence() {
+            let info = parse_fence(line).map(|(_, info)| info).unwrap_or_default();
+            let block = CodeBlock {
+                start_line: line_number,
+                end_line: line_number,
+                info: info.to_string(),
+                body: String::new(),
+            };
+            open = Some((block, indent_width(line), Vec::new()));
+        } else if let Some((block, indent, body)) = &mut open {
+            block.end_line = line_number;
+            if code.in_fence() {
+                body.push(strip_fence_indent(line, *indent));
+            } else {
+                let (mut block, _, body) = open.take().unwrap();
+                block.body = body.join("\n");
+                blocks.push(block);
+            }
+        }
+    }
+
+    if let Some((mut block, _, body)) = open {
+        block.body = body.join("\n");
+        blocks.push(block);
+    }
+
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_code_blocks() {
+        let content = "# Setup\n\n```bash\ncargo build\ncargo test\n```\n\n    indented\n\n  ~~~~rust ignore\n  fn main() {}\n  ~~~~\n\n```\nunclosed\n";
+        let blocks = parse_code_blocks(content);
+
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks[0].language(), Some("bash"));
+        assert_eq!(blocks[0].body, "cargo build\ncargo test");
+        assert_eq!((blocks[0].start_line, blocks[0].end_line), (3, 6));
+
+        assert_eq!(blocks[1].info, "rust ignore");
+        assert_eq!(blocks[1].language(), Some("rust"));
+        assert_eq!(blocks[1].body, "fn main() {}");
+
+        assert_eq!(blocks[2].language(), None);
+        assert_eq!(blocks[2].body, "unclosed");
+        assert_eq!((blocks[2].start_line, blocks[2].end_line), (14, 15));
+    }
+}
@@ -0,0 +1,170 @@
+//! Heading extraction backed by pulldown-cmark
+
+use crate::scan::{BOM, HeadingAttributes, parse_attribute_block, strip_line_ending};
+use crate::{Heading, line_starts, parse_front_matter};
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+use std::ops::Range;
+
+/// Parse the attribute block after a heading's text, if any
+///
+/// pulldown-cmark strips the block but drops parts of it (such as Pandoc's `-`),
+/// so it is read again from the source with the line scanner's rules.
+fn trailing_attributes(content: &str, text_end: usize) -> HeadingAttributes {
+    let rest = &content[text_end..];
+    let line = strip_line_ending(&rest[..rest.find('\n').map_or(rest.len(), |idx| idx + 1)]);
+    // Skip an ATX closing sequence before the block
+    let trailing = line.trim().trim_start_matches('#').trim();
+    trailing
+        .strip_prefix('{')
+        .and_then(|block| block.strip_suffix('}'))
+        .and_then(parse_attribute_block)
+        .unwrap_or_default()
+}
+
+/// A heading whose end event hasn't been seen yet
+struct OpenHeading {
+    level: u8,
+    /// Source range reported for the whole heading
+    source: Range<usize>,
+    /// Span of the inline events seen so far
+    text: Option<Range<usize>>,
+}
+
+/// Parse headings with a full CommonMark parser
+///
+/// Produces the same [`Heading`] fields as the line scanner. Differences follow
+/// from CommonMark itself, e.g. headings inside list items are found too.
+/// Headings inside block quotes are only reported with `blockquotes` set.
+pub(crate) fn parse_headings(content: &str, blockquotes: bool) -> Vec<Heading> {
+    let mut body = content.strip_prefix(BOM).unwrap_or(content);
+    let mut line_starts = line_starts(content);
+    // The first line starts after the BOM
+    if let Some(first) = line_starts.first_mut() {
+        *first = content.len() - body.len();
+    }
+    let line_index = |offset: usize| line_starts.partition_point(|&s| s <= offset) - 1;
+
+    // Skip front matter by the same rules as the line scanner, which are stricter
+    // than pulldown-cmark's metadata blocks
+    if let Some(front_matter) = parse_front_matter(content) {
+        let skip: usize = body
+            .split_inclusive('\n')
+            .take(front_matter.end_line)
+            .map(str::len)
+            .sum();
+        body = &body[skip..];
+    }
+    let base = content.len() - body.len();
+
+    let options =
+        Options::ENABLE_HEADING_ATTRIBUTES | Options::ENABLE_TABLES | Options::ENABLE_FOOTNOTES;
+
+    let mut headings = Vec::new();
+    let mut current: Option<OpenHeading> = None;
+    let mut quote_depth = 0;
+
+    for (event, range) in Parser::new_ext(body, options).into_offset_iter() {
+        let range = range.start + base..range.end + base;
+        match event {
+            Event::Start(Tag::BlockQuote(_)) => quote_depth += 1,
+            Event::End(TagEnd::BlockQuote(_)) => quote_depth -= 1,
+            Event::Start(Tag::Heading { .. }) if quote_depth > 0 && !blockquotes => {}
+            Event::Start(Tag::Heading { level, .. }) => {
+                current = Some(OpenHeading {
+                    level: level as u8,
+                    source: range,
+                    text: None,
+                });
+            }
+            Event::End(TagEnd::Heading(_)) => {
+                let Some(OpenHeading {
+                    level,
+                    source,
+                    text,
+                }) = current.take()
+                else {
+                    continue;
+                };
+                let line = line_index(source.start);
+                let byte_offset = line_starts[line];
+                let source_end = content[..source.end].trim_end_matches(['\n', '\r']).len();
+                let text_range = text.unwrap_or(source_end..source_end);
+                let raw = &content[text_range.clone()];
+                let attrs = trailing_attributes(content, text_range.end);
+                headings.push(Heading {
+                    line_number: line + 1,
+                    level,
+                    // Continuation lines of a quoted setext heading repeat the markers
+                    text: raw
+                        .lines()
+                        .map(|line| line.trim().trim_start_matches(['>', ' ', '\t']))
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                    id: attrs.id,
+                    classes: attrs.classes,
+                    attributes: attrs.attributes,
+                    byte_offset,
+                    byte_len: source_end - byte_offset,
+                    column: content[line_starts[line_index(text_range.start)]..text_range.start]
+                        .chars()
+                        .count()
+                        + 1,
+                    text_range,
+                    in_blockquote: quote_depth > 0,
+                });
+            }
+            _ => {
+                if let Some(open) = &mut current {
+                    let span = open.text.get_or_insert(range.clone());
+                    span.start = span.start.min(range.start);
+                    span.end = span.end.max(range.end);
+                }
+            }
+        }
+    }
+
+    headings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scan::scan_headings;
+
+    #[test]
+    fn test_matches_line_scanner() {
+        let content = "\u{feff}---\ntitle: x\n---\n# Title\r\n\nText *here*\nand there\n===\n\n```\n# no\n```\n\n    # code\n\n## Install ## {#setup}\n\n  ### Indented `code`\n\n## Notes {- key=\"a b\"}\n";
+        assert_eq!(
+            parse_headings(content, false),
+            scan_headings(content, false)
+        );
+
+        let quoted = "Text\n> ## Quoted\n>\n> > Nested\n> > text\n> > ---\n";
+        assert_eq!(parse_headings(quoted, true), scan_headings(quoted, true));
+    }
+
+    #[test]
+    fn test_commonmark_containers() {
+        let headings = parse_headings("> # Quoted\n\n- ## Listed\n\n#\n", true);
+        let texts: Vec<_> = headings.iter().map(|h| h.text.as_str()).collect();
+        assert_eq!(texts, ["Quoted", "Listed", ""]);
+        assert!(headings[0].in_blockquote);
+        assert_eq!(headings[1].line_number, 3);
+        assert_eq!(headings[1].column, 6);
+    }
+
+    #[test]
+    fn test_edit_headings_in_containers() {
+        let content = "# Spec\n\n## Scope\n\n- ## Listed\n\n> ## Quoted\n";
+        let headings = parse_headings(content, true);
+
+        assert_eq!(
+            crate::apply_section_numbers(content, &headings, 2),
+            "# Spec\n\n## 1. Scope\n\n- ## 2. Listed\n\n> ## 3. Quoted\n"
+        );
+        assert_eq!(
+            crate::shift_section_levels(content, &headings, &headings[0], 1).unwrap(),
+            "## Spec\n\n### Scope\n\n- ### Listed\n\n> ### Quoted\n"
+        );
+    }
+}
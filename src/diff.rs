@@ -0,0 +1,234 @@
+//! Structural comparison of two outlines
+
+use crate::Heading;
+use crate::fuzzy::{FUZZY_THRESHOLD, fuzzy_score};
+use std::collections::HashMap;
+
+/// A heading present in both outlines
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HeadingChange {
+    /// The heading in the old outline
+    pub old: Heading,
+    /// The corresponding heading in the new outline
+    pub new: Heading,
+}
+
+/// Section-level differences between two outlines
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OutlineDiff {
+    /// Headings only in the new outline
+    pub added: Vec<Heading>,
+    /// Headings only in the old outline
+    pub removed: Vec<Heading>,
+    /// Headings whose text changed in place
+    pub renamed: Vec<HeadingChange>,
+    /// Headings whose level changed
+    pub releveled: Vec<HeadingChange>,
+    /// Headings that changed position relative to the others
+    pub moved: Vec<HeadingChange>,
+}
+
+impl OutlineDiff {
+    /// Whether the outlines are structurally identical
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.renamed.is_empty()
+            && self.releveled.is_empty()
+            && self.moved.is_empty()
+    }
+}
+
+/// Indices (into `seq`) of a longest strictly increasing subsequence
+fn longest_increasing(seq: &[usize]) -> Vec<usize> {
+    // tails[k] is the index of the smallest tail of an increasing run of length k + 1
+    let mut tails: Vec<usize> = Vec::new();
+    let mut prev: Vec<Option<usize>> = vec![None; seq.len()];
+
+    for (idx, &value) in seq.iter().enumerate() {
+        let pos = tails.partition_point(|&t| seq[t] < value);
+        prev[idx] = pos.checked_sub(1).map(|p| tails[p]);
+        if pos == tails.len() {
+            tails.push(idx);
+        } else {
+            tails[pos] = idx;
+        }
+    }
+
+    let mut result = Vec::with_capacity(tails.len());
+    let mut cursor = tails.last().copied();
+    while let Some(idx) = cursor {
+        result.push(idx);
+        cursor = prev[idx];
+    }
+    result.reverse();
+    result
+}
+
+/// Similarity of two heading texts, whichever is taken as the query
+fn rename_score(old: &str, new: &str) -> f64 {
+    fuzzy_score(old, new).max(fuzzy_score(new, old))
+}
+
+/// Compare two outlines section by section
+///
+/// Headings are paired by identical text, in order of occurrence. Pairs that
+/// keep their relative order form the stable backbone; the remaining pairs are
+/// reported as moved. Unpaired headings that sit between the same two backbone
+/// headings on both sides are paired up as renames, in order, when their texts
+/// are similar enough for fuzzy section lookup to match them; whatever is left
+/// over is added or removed. Paired headings (including renames) whose level differs
+/// are also reported as releveled.
+pub fn diff_outlines(old: &[Heading], new: &[Heading]) -> OutlineDiff {
+    // Pair headings with identical text in order of occurrence
+    let mut by_text: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (idx, h) in new.iter().enumerate().rev() {
+        by_text.entry(h.text.as_str()).or_default().push(idx);
+    }
+    let mut pairs: Vec<(usize, usize)> = old
+        .iter()
+        .enumerate()
+        .filter_map(|(i, h)| {
+            let j = by_text.get_mut(h.text.as_str())?.pop()?;
+            Some((i, j))
+        })
+        .collect();
+
+    let new_order: Vec<usize> = pairs.iter().map(|&(_, j)| j).collect();
+    let stable = longest_increasing(&new_order);
+
+    let mut diff = OutlineDiff::default();
+    let change = |i: usize, j: usize| HeadingChange {
+        old: old[i].clone(),
+        new: new[j].clone(),
+    };
+
+    let mut stable_iter = stable.iter().peekable();
+    for (k, &(i, j)) in pairs.iter().enumerate() {
+        if stable_iter.peek() == Some(&&k) {
+            stable_iter.next();
+        } else {
+            diff.moved.push(change(i, j));
+        }
+    }
+
+    // Pair the leftovers between consecutive backbone headings as renames
+    let mut old_paired = vec![false; old.len()];
+    let mut new_paired = vec![false; new.len()];
+    for &(i, j) in &pairs {
+        old_paired[i] = true;
+        new_paired[j] = true;
+    }
+
+    let mut bounds: Vec<(usize, usize)> = vec![(0, 0)];
+    bounds.extend(stable.iter().map(|&k| (pairs[k].0 + 1, pairs[k].1 + 1)));
+    let ends = stable
+        .iter()
+        .map(|&k| pairs[k])
+        .chain(std::iter::once((old.len(), new.len())));
+
+    let mut renames = Vec::new();
+    for (&(old_start, new_start), (old_end, new_end)) in bounds.iter().zip(ends) {
+        let old_gap = (old_start..old_end).filter(|&i| !old_paired[i]);
+        let new_gap: Vec<usize> = (new_start..new_end).filter(|&j| !new_paired[j]).collect();
+        let mut next = 0;
+        for i in old_gap {
+            // The most similar heading that keeps the renames in order
+            let best = new_gap[next..]
+                .iter()
+                .enumerate()
+                .map(|(k, &j)| (k, rename_score(&old[i].text, &new[j].text)))
+                .filter(|&(_, score)| score >= FUZZY_THRESHOLD)
+                .min_by(|a, b| b.1.total_cmp(&a.1));
+            if let Some((k, _)) = best {
+                renames.push((i, new_gap[next + k]));
+                next += k + 1;
+            }
+        }
+    }
+    for &(i, j) in &renames {
+        old_paired[i] = true;
+        new_paired[j] = true;
+        diff.renamed.push(change(i, j));
+    }
+
+    pairs.extend(renames);
+    pairs.sort_unstable();
+    diff.releveled = pairs
+        .iter()
+        .filter(|&&(i, j)| old[i].level != new[j].level)
+        .map(|&(i, j)| change(i, j))
+        .collect();
+
+    diff.removed = old
+        .iter()
+        .zip(&old_paired)
+        .filter(|(_, paired)| !**paired)
+        .map(|(h, _)| h.clone())
+        .collect();
+    diff.added = new
+        .iter()
+        .zip(&new_paired)
+        .filter(|(_, paired)| !**paired)
+        .map(|(h, _)| h.clone())
+        .collect();
+
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_headings;
+
+    #[test]
+    fn test_diff_outlines() {
+        let old = parse_headings(
+            "# Guide\n## Install\n## Usage\n## Config\n## FAQ\n## Legacy\n# Appendix\n",
+        );
+        let new = parse_headings(
+            "# Guide\n## FAQ\n## Installation\n### Usage\n## Config\n## Contributing\n# Appendix\n",
+        );
+        let diff = diff_outlines(&old, &new);
+
+        let texts = |hs: &[Heading]| hs.iter().map(|h| h.text.clone()).collect::<Vec<_>>();
+        let pairs = |cs: &[HeadingChange]| {
+            cs.iter()
+                .map(|c| (c.old.text.clone(), c.new.text.clone()))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(pairs(&diff.moved), [("FAQ".into(), "FAQ".into())]);
+        assert_eq!(
+            pairs(&diff.renamed),
+            [("Install".into(), "Installation".into())]
+        );
+        assert_eq!(pairs(&diff.releveled), [("Usage".into(), "Usage".into())]);
+        // Unrelated headings in the same place are not a rename
+        assert_eq!(texts(&diff.added), ["Contributing"]);
+        assert_eq!(texts(&diff.removed), ["Legacy"]);
+        assert!(!diff.is_empty());
+
+        let diff = diff_outlines(&old, &old[..6]);
+        assert_eq!(texts(&diff.removed), ["Appendix"]);
+        assert!(diff_outlines(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn test_renames_need_similar_text() {
+        let old = parse_headings("# Guide\n## Instalation\n## Legacy\n## Tips\n");
+        let new = parse_headings("# Guide\n## Contributing\n## Installation\n## Tip\n");
+        let diff = diff_outlines(&old, &new);
+
+        let renamed: Vec<_> = diff
+            .renamed
+            .iter()
+            .map(|c| (c.old.text.as_str(), c.new.text.as_str()))
+            .collect();
+        assert_eq!(renamed, [("Instalation", "Installation"), ("Tips", "Tip")]);
+        assert_eq!(diff.added[0].text, "Contributing");
+        assert_eq!(diff.removed[0].text, "Legacy");
+    }
+}
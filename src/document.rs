@@ -0,0 +1,307 @@
+//! A parsed document handle with cached line and heading indexes
+
+use crate::scan::{BOM, HeadingScanner, strip_line_ending};
+use crate::{
+    FrontMatterFormat, Heading, find_section, find_section_by_path, get_section_range, line_starts,
+    parse_headings,
+};
+use std::ops::Range;
+
+/// A markdown document parsed once for repeated queries
+///
+/// The line-start index and the heading list are computed up front, so looking
+/// up lines and extracting sections don't re-split the content.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Document {
+    content: String,
+    /// Byte offset of the start of every line
+    line_starts: Vec<usize>,
+    headings: Vec<Heading>,
+}
+
+/// Whether a heading was written on a single unindented `#` line
+///
+/// Scanning can restart at such a line, since it ends any open paragraph or list
+/// and can't be inside a code block.
+fn is_atx(content: &str, heading: &Heading) -> bool {
+    let source = &content[heading.byte_range()];
+    source.starts_with('#') && !source.contains('\n')
+}
+
+impl Document {
+    /// Parse a document
+    pub fn new(content: impl Into<String>) -> Self {
+        let content = content.into();
+        Self {
+            line_starts: line_starts(&content),
+            headings: parse_headings(&content),
+            content,
+        }
+    }
+
+    /// The full document text
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+
+    /// All headings in document order
+    pub fn headings(&self) -> &[Heading] {
+        &self.headings
+    }
+
+    /// Headings up to `max_depth`, as shown by `mdi outline`
+    pub fn outline(&self, max_depth: u8) -> Vec<&Heading> {
+        self.headings
+            .iter()
+            .filter(|h| h.level <= max_depth)
+            .collect()
+    }
+
+    /// Number of lines in the document
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// Byte offset of the start of a line (1-indexed)
+    pub fn line_offset(&self, line_number: usize) -> Option<usize> {
+        self.line_starts.get(line_number.checked_sub(1)?).copied()
+    }
+
+    /// A single line (1-indexed) without its line ending
+    pub fn line(&self, line_number: usize) -> Option<&str> {
+        let start = self.line_offset(line_number)?;
+        let end = self
+            .line_offset(line_number + 1)
+            .unwrap_or(self.content.len());
+        let line = &self.content[start..end];
+        let line = line.strip_suffix('\n').unwrap_or(line);
+        Some(line.strip_suffix('\r').unwrap_or(line))
+    }
+
+    /// Replace the bytes in `range` with `replacement`, updating the line index
+    /// and headings
+    ///
+    /// Only the lines between the ATX headings surrounding the edit are
+    /// re-scanned; headings after it are shifted. Edits that can change how the
+    /// rest of the document parses (touching the first line, a code fence or a
+    /// front matter delimiter) fall back to a full re-parse, as does every edit
+    /// with the `commonmark` feature.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is out of bounds or doesn't lie on `char` boundaries,
+    /// like [`String::replace_range`].
+    pub fn apply_edit(&mut self, range: Range<usize>, replacement: &str) {
+        // Lines touched by the edit, as indexes into `line_starts`
+        let first = self
+            .line_starts
+            .partition_point(|&s| s <= range.start)
+            .max(1)
+            - 1;
+        let last = self.line_starts.partition_point(|&s| s <= range.end).max(1) - 1;
+        let old_region = self.line_starts.get(first).copied().unwrap_or(0)
+            ..self
+                .line_starts
+                .get(last + 1)
+                .copied()
+                .unwrap_or(self.content.len());
+        let old_text = self.content[old_region.clone()].to_string();
+
+        // Headings around the edit where scanning can restart and stop
+        let restart = self
+            .headings
+            .iter()
+            .rposition(|h| h.line_number <= first && is_atx(&self.content, h));
+        let resync = self
+            .headings
+            .iter()
+            .position(|h| h.line_number > last + 1 && is_atx(&self.content, h))
+            .unwrap_or(self.headings.len());
+
+        self.content.replace_range(range.clone(), replacement);
+        let delta = replacement.len() as isize - range.len() as isize;
+        let shift = |offset: usize| offset.checked_add_signed(delta).unwrap();
+        let new_region = old_region.start..shift(old_region.end);
+
+        // With nothing to resume from, the whole prefix needs scanning anyway
+        let Some(restart) = restart.filter(|_| {
+            first > 0
+                && !cfg!(feature = "commonmark")
+                && !self.reparse_needed(&old_text, &self.content[new_region.clone()])
+        }) else {
+            *self = Self::new(std::mem::take(&mut self.content));
+            return;
+        };
+
+        // Line index: unchanged before the edit, re-split inside, shifted after
+        let old_line_count = self.line_starts.len();
+        let tail: Vec<usize> = self.line_starts[last + 1..]
+            .iter()
+            .map(|&s| shift(s))
+            .collect();
+        self.line_starts.truncate(first + 1);
+        self.line_starts.extend(
+            self.content[new_region.clone()]
+                .match_indices('\n')
+                .map(|(idx, _)| new_region.start + idx + 1)
+                .filter(|&s| s < new_region.end),
+        );
+        self.line_starts.extend(tail);
+        if self.line_starts.last() == Some(&self.content.len()) {
+            self.line_starts.pop();
+        }
+        let line_delta = self.line_starts.len() as isize - old_line_count as isize;
+
+        // Headings: re-scan from the last ATX heading before the edit up to the
+        // first one after it, which is where the old and new parses agree again
+        let mut headings = std::mem::take(&mut self.headings);
+        for h in &mut headings[resync..] {
+            h.line_number = h.line_number.checked_add_signed(line_delta).unwrap();
+            h.byte_offset = shift(h.byte_offset);
+            h.text_range = shift(h.text_range.start)..shift(h.text_range.end);
+        }
+        let scan_start = headings[restart].byte_offset;
+        let scan_end = headings
+            .get(resync)
+            .map_or(self.content.len(), |h| h.byte_offset);
+        let mut scanner = HeadingScanner::resume_at(headings[restart].line_number - 1, scan_start);
+        let rescanned: Vec<Heading> = self.content[scan_start..scan_end]
+            .split_inclusive('\n')
+            .filter_map(|raw| scanner.push_line(strip_line_ending(raw), raw.len()))
+            .collect();
+
+        headings.splice(restart..resync, rescanned);
+        self.headings = headings;
+    }
+
+    /// Whether an edit replacing `old` lines with `new` ones may affect parsing
+    /// outside the lines between its surrounding headings
+    fn reparse_needed(&self, old: &str, new: &str) -> bool {
+        let front_matter = self
+            .content
+            .lines()
+            .next()
+            .and_then(|line| FrontMatterFormat::from_opening(line.trim_start_matches(BOM)));
+        old.lines().chain(new.lines()).any(|line| {
+            line.contains("```")
+                || line.contains("~~~")
+                || front_matter.is_some_and(|format| format.is_closing(line))
+        })
+    }
+
+    /// Find a section by line number, heading text or `/`-separated heading path
+    pub fn section(&self, query: &str) -> Option<&Heading> {
+        query
+            .contains('/')
+            .then(|| find_section_by_path(&self.headings, query))
+            .flatten()
+            .or_else(|| find_section(&self.headings, query))
+    }
+
+    /// Line range of a section, as returned by [`get_section_range`]
+    pub fn section_range(&self, heading: &Heading) -> (usize, Option<usize>) {
+        get_section_range(&self.headings, heading)
+    }
+
+    /// Borrow lines `start` to `end` (exclusive, 1-indexed) including line endings,
+    /// like [`extract_section_str`](crate::extract_section_str)
+    pub fn extract(&self, start: usize, end: Option<usize>) -> &str {
+        &self.content[self.span(start, end)]
+    }
+
+    /// Byte range of lines `start` to `end` (exclusive, 1-indexed)
+    fn span(&self, start: usize, end: Option<usize>) -> Range<usize> {
+        let len = self.content.len();
+        let to = end.and_then(|e| self.line_offset(e)).unwrap_or(len);
+        let from = self.line_offset(start.max(1)).unwrap_or(len).min(to);
+        from..to
+    }
+
+    /// Byte range of a section, as returned by
+    /// [`get_section_byte_range`](crate::get_section_byte_range)
+    pub fn section_byte_range(&self, heading: &Heading) -> Range<usize> {
+        let (start, end) = self.section_range(heading);
+        self.span(start, end)
+    }
+
+    /// Borrow the full text of a section, including its subsections
+    pub fn extract_section(&self, heading: &Heading) -> &str {
+        &self.content[self.section_byte_range(heading)]
+    }
+
+    /// Borrow a section without its heading, like
+    /// [`extract_section_body`](crate::extract_section_body)
+    pub fn extract_section_body(&self, heading: &Heading) -> &str {
+        crate::strip_heading(&self.content, self.section_byte_range(heading), heading)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extract_section_str;
+
+    #[test]
+    fn test_document_queries() {
+        let content = "# Guide\r\n\r\n## Install\r\n\r\nsteps\r\n\r\n## Usage\r\n\r\nrun";
+        let doc = Document::new(content);
+
+        assert_eq!(doc.line_count(), 9);
+        assert_eq!(doc.line(3), Some("## Install"));
+        assert_eq!(doc.line(10), None);
+        assert_eq!(doc.outline(1).len(), 1);
+
+        let install = doc.section("Guide/install").unwrap();
+        assert_eq!(
+            doc.extract_section(install),
+            "## Install\r\n\r\nsteps\r\n\r\n"
+        );
+        assert_eq!(doc.extract_section_body(install), "steps\r\n\r\n");
+        assert_eq!(
+            doc.section_byte_range(install),
+            crate::get_section_byte_range(content, doc.headings(), install)
+        );
+
+        // Same slices as the free functions
+        for (start, end) in [(1, Some(3)), (7, None), (4, Some(4)), (12, None)] {
+            assert_eq!(
+                doc.extract(start, end),
+                extract_section_str(content, start, end)
+            );
+        }
+    }
+
+    #[test]
+    fn test_apply_edit_matches_full_parse() {
+        let content = "# Guide\n\nintro\n\n## Install\n\nsteps\ntext\n\n## Usage\n\nrun\n\n## End";
+        let edits: &[(&str, &str)] = &[
+            // Plain text change inside a section
+            ("steps", "more steps"),
+            // New ATX heading
+            ("run\n", "run\n\n### Flags\n"),
+            // Underline turns a paragraph into a setext heading
+            ("text\n", "text\n---\n"),
+            // Joining a paragraph onto that heading's text
+            ("more steps\n", "more steps\r\nand"),
+            // Heading removed across several lines
+            ("## Usage\n\nrun", "run"),
+            // An opened fence hides the rest of the document
+            ("intro\n", "```\nintro\n"),
+            ("```\n", ""),
+            // Edit in the last line without a trailing newline
+            ("## End", "## The End\n"),
+        ];
+
+        let mut doc = Document::new(content);
+        for (from, to) in edits {
+            let start = doc.content().find(from).unwrap();
+            doc.apply_edit(start..start + from.len(), to);
+            assert_eq!(
+                doc,
+                Document::new(doc.content()),
+                "after {from:?} -> {to:?}"
+            );
+        }
+        assert_eq!(doc.headings().last().unwrap().text, "The End");
+    }
+}
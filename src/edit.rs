@@ -0,0 +1,1012 @@
+//! Structural edits that rewrite a document section by section
+
+use crate::html_headings::relevel_html_heading;
+use crate::{Heading, parse_headings};
+use anyhow::{Result, bail};
+use std::ops::Range;
+
+/// A heading together with the text it owns (up to the next heading of any level)
+struct Block {
+    /// Position of the block in the original document
+    index: usize,
+    level: u8,
+    range: Range<usize>,
+}
+
+/// Split a document into the end of the text before the first heading and one
+/// block per heading
+fn split_blocks(content: &str, headings: &[Heading]) -> (usize, Vec<Block>) {
+    let preamble_end = headings.first().map_or(content.len(), |h| h.byte_offset);
+
+    let blocks = headings
+        .iter()
+        .enumerate()
+        .map(|(index, h)| {
+            let end = headings
+                .get(index + 1)
+                .map_or(content.len(), |next| next.byte_offset);
+            Block {
+                index,
+                level: h.level,
+                range: h.byte_offset..end,
+            }
+        })
+        .collect();
+
+    (preamble_end, blocks)
+}
+
+/// Number of blocks making up the section starting at `idx` (heading + descendants)
+fn subtree_len(blocks: &[Block], idx: usize) -> usize {
+    let level = blocks[idx].level;
+    1 + blocks[idx + 1..]
+        .iter()
+        .take_while(|b| b.level > level)
+        .count()
+}
+
+/// Apply a sequence of section moves and return the rewritten document
+///
+/// Each move is `(from_heading_index, to_position)`: the section whose heading is
+/// `headings[from]` is cut out together with its body and all descendants, and
+/// re-inserted so that its heading ends up at index `to` in the resulting heading
+/// list. Moves are applied in order, so indices in later moves refer to the
+/// heading order produced by the earlier ones.
+///
+/// Sections keep their text byte for byte. Only where a section meets a new
+/// neighbour is a blank line added if it had none, and the section that ends up
+/// last ends the way the document did.
+///
+/// A move is rejected if the insertion point would sit between a heading and its
+/// children, since the moved section would then adopt them.
+pub fn apply_section_moves(
+    content: &str,
+    headings: &[Heading],
+    moves: &[(usize, usize)],
+) -> Result<String> {
+    let (preamble_end, mut blocks) = split_blocks(content, headings);
+
+    for &(from, to) in moves {
+        if from >= blocks.len() {
+            bail!(
+                "Heading index {} out of range ({} headings)",
+                from,
+                blocks.len()
+            );
+        }
+
+        let level = blocks[from].level;
+        let len = subtree_len(&blocks, from);
+        let moved: Vec<Block> = blocks.drain(from..from + len).collect();
+
+        if to > blocks.len() {
+            bail!(
+                "Target position {} out of range ({} headings remain)",
+                to,
+                blocks.len()
+            );
+        }
+
+        if let Some(next) = blocks.get(to)
+            && next.level > level
+        {
+            bail!(
+                "Moving heading {} to position {} would split a section from its children",
+                from,
+                to
+            );
+        }
+
+        blocks.splice(to..to, moved);
+    }
+
+    let mut output = String::with_capacity(content.len() + 8);
+    output.push_str(&content[..preamble_end]);
+    let mut eol = line_ending_at(content, 0);
+    let mut successor = 0;
+
+    for (pos, block) in blocks.iter().enumerate() {
+        // Separate the block from a new predecessor with a blank line
+        if block.index != successor && !output.trim().is_empty() {
+            if !output.ends_with('\n') {
+                output.push_str(eol);
+            }
+            if !ends_with_blank_line(&output) {
+                output.push_str(eol);
+            }
+        }
+
+        let text = &content[block.range.clone()];
+        let last = blocks.len() - 1;
+        if pos == last && block.index != last {
+            // A new last block ends like the document
+            let (_, text, _) = split_blank_edges(text);
+            output.push_str(text.trim_end_matches(['\r', '\n']));
+            if content.ends_with('\n') {
+                output.push_str(line_ending_at(content, block.range.start));
+            }
+        } else {
+            output.push_str(text);
+        }
+
+        eol = line_ending_at(content, block.range.start);
+        successor = block.index + 1;
+    }
+
+    Ok(output)
+}
+
+/// Line ending used by the line containing `offset` (`\r\n` or `\n`)
+pub(crate) fn line_ending_at(content: &str, offset: usize) -> &'static str {
+    match content[offset..].find('\n') {
+        Some(pos) if content[..offset + pos].ends_with('\r') => "\r\n",
+        _ => "\n",
+    }
+}
+
+/// Byte offset just past the line containing `offset`, including its line ending
+pub(crate) fn next_line_start(content: &str, offset: usize) -> usize {
+    content[offset..]
+        .find('\n')
+        .map(|pos| offset + pos + 1)
+        .unwrap_or(content.len())
+}
+
+/// Byte range of a section's body: after the heading up to the next heading at
+/// the same or higher level (so subsections are included)
+pub(crate) fn section_body_range(
+    content: &str,
+    headings: &[Heading],
+    heading: &Heading,
+) -> (usize, usize) {
+    let start = next_line_start(content, heading.byte_range().end);
+    let end = headings
+        .iter()
+        .find(|h| h.line_number > heading.line_number && h.level <= heading.level)
+        .map(|h| h.byte_offset)
+        .unwrap_or(content.len());
+    (start, end.max(start))
+}
+
+/// Split a text into its leading blank lines, the rest, and its trailing blank lines
+fn split_blank_edges(text: &str) -> (&str, &str, &str) {
+    let lead = text
+        .split_inclusive('\n')
+        .take_while(|line| line.trim().is_empty())
+        .map(str::len)
+        .sum::<usize>();
+    if lead == text.len() {
+        return (text, "", "");
+    }
+
+    let trail = text
+        .split_inclusive('\n')
+        .rev()
+        .take_while(|line| line.trim().is_empty())
+        .map(str::len)
+        .sum::<usize>();
+    (
+        &text[..lead],
+        &text[lead..text.len() - trail],
+        &text[text.len() - trail..],
+    )
+}
+
+/// Convert text to use the given line ending, dropping leading and trailing blank lines
+fn normalize_body(body: &str, eol: &str) -> String {
+    let lines: Vec<&str> = body.lines().collect();
+    let first = lines.iter().position(|l| !l.trim().is_empty());
+    let last = lines.iter().rposition(|l| !l.trim().is_empty());
+    match (first, last) {
+        (Some(first), Some(last)) => lines[first..=last].join(eol),
+        _ => String::new(),
+    }
+}
+
+/// Replace the body of a section, keeping its heading
+///
+/// Everything after the heading up to the next heading at the same or higher
+/// level is replaced with `new_body`, so subsections are replaced too. The blank
+/// lines that separated the old body from the heading and from the next section
+/// are kept, and `new_body` is converted to the line ending used by the heading.
+pub fn replace_section(
+    content: &str,
+    headings: &[Heading],
+    heading: &Heading,
+    new_body: &str,
+) -> String {
+    let eol = line_ending_at(content, heading.byte_offset);
+    let (start, end) = section_body_range(content, headings, heading);
+    let (lead, old_body, trail) = split_blank_edges(&content[start..end]);
+    let body = normalize_body(new_body, eol);
+
+    let mut output = String::with_capacity(content.len() + body.len());
+    output.push_str(&content[..start]);
+
+    if body.is_empty() {
+        // Keep a single run of blank lines between the heading and what follows
+        output.push_str(if old_body.is_empty() { lead } else { trail });
+    } else {
+        if !output.is_empty() && !output.ends_with('\n') {
+            output.push_str(eol);
+        }
+        output.push_str(lead);
+        output.push_str(&body);
+        if end < content.len() || content.ends_with('\n') {
+            output.push_str(eol);
+        }
+        // A section without body text reuses its separator on both sides
+        output.push_str(if old_body.is_empty() { lead } else { trail });
+    }
+
+    output.push_str(&content[end..]);
+    output
+}
+
+/// Where [`insert_section`] places a new section
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InsertPosition<'a> {
+    /// Directly before the given heading, as a sibling
+    Before(&'a Heading),
+    /// After the given heading's section (including its subsections), as a sibling
+    After(&'a Heading),
+    /// Before the given heading's existing subsections, as its first child
+    FirstChildOf(&'a Heading),
+    /// After the given heading's existing subsections, as its last child
+    LastChildOf(&'a Heading),
+    /// At the end of the document
+    EndOfDocument,
+}
+
+/// Rewrite a heading's source so that it has a different level
+///
+/// ATX headings get a new `#` run and HTML headings new `<hN>` tags. Setext
+/// headings keep their form for levels 1 and 2 and are converted to ATX otherwise.
+fn relevel_heading(source: &str, heading: &Heading, level: u8) -> String {
+    let marks = "#".repeat(level as usize);
+    // Where the text is found in `source`; anything before it on the line is
+    // indent or list and block quote markers
+    let relative = |offset: usize| {
+        offset
+            .checked_sub(heading.byte_offset)
+            .map_or(source.len(), |offset| offset.min(source.len()))
+    };
+    let text_start = relative(heading.text_range.start);
+
+    if let Some(html) = relevel_html_heading(source, level) {
+        return html;
+    }
+    if !source.contains('\n') {
+        // The `#` run is the last one before the text
+        let prefix = source[..text_start].trim_end();
+        let before = prefix.trim_end_matches('#');
+        return format!("{}{}{}", before, marks, &source[prefix.len()..]);
+    }
+
+    match (level, source.rsplit_once('\n')) {
+        (1 | 2, Some((text, underline))) => {
+            let marker = if level == 1 { "=" } else { "-" };
+            let indent = underline.len() - underline.trim_start().len();
+            let width = underline.trim().len();
+            format!("{}\n{}{}", text, &underline[..indent], marker.repeat(width))
+        }
+        _ => {
+            // Keep the attribute block written after the text as it is
+            let text_end = relative(heading.text_range.end);
+            let line_end = source[text_end..]
+                .find('\n')
+                .map_or(source.len(), |pos| text_end + pos);
+            let prefix = &source[..text_start.min(source.find('\n').unwrap_or(0))];
+            format!(
+                "{}{} {}{}",
+                prefix,
+                marks,
+                heading.text,
+                source[text_end..line_end].trim_end()
+            )
+        }
+    }
+}
+
+/// Rewrite the levels of the given headings, which must be in document order
+pub(crate) fn relevel_headings<'h>(
+    content: &str,
+    changes: impl IntoIterator<Item = (&'h Heading, u8)>,
+) -> String {
+    let mut output = String::with_capacity(content.len());
+    let mut pos = 0;
+
+    for (heading, level) in changes {
+        let range = heading.byte_range();
+        output.push_str(&content[pos..range.start]);
+        if heading.level == level {
+            output.push_str(&content[range.clone()]);
+        } else {
+            output.push_str(&relevel_heading(&content[range.clone()], heading, level));
+        }
+        pos = range.end;
+    }
+
+    output.push_str(&content[pos..]);
+    output
+}
+
+/// Insert a new section with the given title and body
+///
+/// With `level` set to `None` the level is chosen to fit the position: the level
+/// of the heading for [`InsertPosition::Before`] and [`InsertPosition::After`],
+/// one deeper for [`InsertPosition::FirstChildOf`] and
+/// [`InsertPosition::LastChildOf`], and the document's top level for
+/// [`InsertPosition::EndOfDocument`]. Headings inside `body` are shifted so that
+/// they nest below the new heading. Blank lines are added around the new
+/// section as needed.
+pub fn insert_section(
+    content: &str,
+    headings: &[Heading],
+    position: InsertPosition,
+    level: Option<u8>,
+    title: &str,
+    body: &str,
+) -> Result<String> {
+    let (offset, auto_level, min_level) = match position {
+        InsertPosition::Before(h) => (h.byte_offset, h.level, 1),
+        InsertPosition::After(h) => (section_body_range(content, headings, h).1, h.level, 1),
+        InsertPosition::FirstChildOf(h) => {
+            let end = section_body_range(content, headings, h).1;
+            let first_child = headings
+                .iter()
+                .find(|c| c.byte_offset > h.byte_offset && c.byte_offset < end);
+            (
+                first_child.map_or(end, |c| c.byte_offset),
+                h.level + 1,
+                h.level + 1,
+            )
+        }
+        InsertPosition::LastChildOf(h) => (
+            section_body_range(content, headings, h).1,
+            h.level + 1,
+            h.level + 1,
+        ),
+        InsertPosition::EndOfDocument => (
+            content.len(),
+            headings.iter().map(|h| h.level).min().unwrap_or(1),
+            1,
+        ),
+    };
+
+    let level = level.unwrap_or(auto_level);
+    if !(min_level..=6).contains(&level) {
+        bail!(
+            "Heading level {} doesn't fit the insertion point (expected {}-6)",
+            level,
+            min_level
+        );
+    }
+
+    let eol = line_ending_at(content, headings.first().map_or(0, |h| h.byte_offset));
+
+    // Nest the body's own headings below the new one
+    let body_headings = parse_headings(body);
+    let shallowest = body_headings.iter().map(|h| h.level).min().unwrap_or(6);
+    let delta = i16::from(level) + 1 - i16::from(shallowest);
+    let body = relevel_headings(
+        body,
+        body_headings
+            .iter()
+            .map(|h| (h, (i16::from(h.level) + delta).clamp(1, 6) as u8)),
+    );
+    let body = normalize_body(&body, eol);
+
+    let mut section = format!("{} {}{}", "#".repeat(level as usize), title.trim(), eol);
+    if !body.is_empty() {
+        section.push_str(eol);
+        section.push_str(&body);
+        section.push_str(eol);
+    }
+
+    Ok(splice_section(content, offset, &section, eol))
+}
+
+/// Insert section text (ending with a line ending) at a byte offset, separating
+/// it from the surrounding text with blank lines
+fn splice_section(content: &str, offset: usize, section: &str, eol: &str) -> String {
+    let before = &content[..offset];
+    let after = &content[offset..];
+
+    let mut output = String::with_capacity(content.len() + section.len() + 8);
+    output.push_str(before);
+    if !before.is_empty() {
+        if !before.ends_with('\n') {
+            output.push_str(eol);
+        }
+        if !before.trim().is_empty() && !ends_with_blank_line(&output) {
+            output.push_str(eol);
+        }
+    }
+
+    output.push_str(section);
+    if !after.is_empty() {
+        output.push_str(eol);
+    }
+
+    output.push_str(after);
+    output
+}
+
+/// Remove a section: its heading, body and all subsections
+///
+/// This removes the same range as [`get_section_range`](crate::get_section_range).
+/// The blank lines left around the cut are collapsed so that the surrounding
+/// text is separated by a single blank line.
+pub fn delete_section(content: &str, headings: &[Heading], heading: &Heading) -> String {
+    let (_, end) = section_body_range(content, headings, heading);
+    remove_range(content, heading.byte_offset, end)
+}
+
+/// Remove a section's heading and its own text, promoting its subsections to
+/// take its place
+///
+/// The shallowest subsections take the level of the removed heading and deeper
+/// ones keep their nesting below them. A section without subsections is removed
+/// as by [`delete_section`].
+pub fn unwrap_section(content: &str, headings: &[Heading], heading: &Heading) -> String {
+    let Some(idx) = headings.iter().position(|h| h == heading) else {
+        return content.to_string();
+    };
+    let children: Vec<&Heading> = headings[idx + 1..]
+        .iter()
+        .take_while(|h| h.level > heading.level)
+        .collect();
+    let Some(shallowest) = children.iter().map(|h| h.level).min() else {
+        return delete_section(content, headings, heading);
+    };
+
+    let delta = i16::from(heading.level) - i16::from(shallowest);
+    let promoted = relevel_headings(
+        content,
+        children
+            .iter()
+            .map(|h| (*h, (i16::from(h.level) + delta) as u8)),
+    );
+    // Rewriting starts at the first subsection, so the text before it is unchanged
+    remove_range(&promoted, heading.byte_offset, children[0].byte_offset)
+}
+
+/// Cut a byte range out of a document, collapsing the blank lines around it
+fn remove_range(content: &str, start: usize, end: usize) -> String {
+    let eol = line_ending_at(content, start);
+    let before = &content[..start];
+    let after = &content[end..];
+
+    let (_, _, trailing_blanks) = split_blank_edges(before);
+    let kept = &before[..before.len() - trailing_blanks.len()];
+
+    if kept.trim().is_empty() {
+        return after.to_string();
+    }
+
+    let mut output = String::with_capacity(content.len());
+    output.push_str(kept);
+    if !after.is_empty() {
+        output.push_str(eol);
+    }
+    output.push_str(after);
+    output
+}
+
+/// Promote (negative `delta`) or demote (positive `delta`) a section and all its
+/// descendants
+///
+/// Fails if `heading` is not in `headings` or if any heading in the section
+/// would end up outside levels 1-6. Setext headings that end up deeper than
+/// level 2 are rewritten as ATX headings.
+pub fn shift_section_levels(
+    content: &str,
+    headings: &[Heading],
+    heading: &Heading,
+    delta: i8,
+) -> Result<String> {
+    let Some(idx) = headings.iter().position(|h| h == heading) else {
+        bail!(
+            "Heading at line {} is not in the document",
+            heading.line_number
+        );
+    };
+
+    let section: Vec<&Heading> = std::iter::once(heading)
+        .chain(
+            headings[idx + 1..]
+                .iter()
+                .take_while(|h| h.level > heading.level),
+        )
+        .collect();
+    let mut changes = Vec::with_capacity(section.len());
+    for h in section {
+        let level = i16::from(h.level) + i16::from(delta);
+        if !(1..=6).contains(&level) {
+            bail!(
+                "Shifting by {} would move the heading at line {} to level {}",
+                delta,
+                h.line_number,
+                level
+            );
+        }
+        changes.push((h, level as u8));
+    }
+    Ok(relevel_headings(content, changes))
+}
+
+/// Where [`move_section`] places a section
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveTarget<'a> {
+    /// Directly before the given heading, as its sibling
+    Before(&'a Heading),
+    /// After the given heading's section (including its subsections), as its sibling
+    After(&'a Heading),
+    /// After the given heading's existing subsections, as its last child
+    Into(&'a Heading),
+}
+
+/// Move a section with all its subsections to a new place in the document
+///
+/// Heading levels of the moved section are shifted so that it fits the target:
+/// siblings take the level of the target heading, children are one level
+/// deeper. Fails if a subsection would end up deeper than level 6. The section is
+/// separated from its new neighbours by blank lines and the gap it leaves behind
+/// is collapsed as in [`delete_section`].
+pub fn move_section(
+    content: &str,
+    headings: &[Heading],
+    heading: &Heading,
+    target: MoveTarget,
+) -> Result<String> {
+    let Some(from) = headings.iter().position(|h| h == heading) else {
+        bail!(
+            "Heading at line {} is not in the document",
+            heading.line_number
+        );
+    };
+    let target_heading = match target {
+        MoveTarget::Before(h) | MoveTarget::After(h) | MoveTarget::Into(h) => h,
+    };
+    let Some(to) = headings.iter().position(|h| h == target_heading) else {
+        bail!(
+            "Target heading at line {} is not in the document",
+            target_heading.line_number
+        );
+    };
+
+    let len = 1 + headings[from + 1..]
+        .iter()
+        .take_while(|h| h.level > heading.level)
+        .count();
+    if (from..from + len).contains(&to) {
+        bail!("Cannot move a section relative to itself or one of its subsections");
+    }
+
+    let level = match target {
+        MoveTarget::Before(h) | MoveTarget::After(h) => h.level,
+        MoveTarget::Into(h) => h.level + 1,
+    };
+    if level > 6 {
+        bail!("Moving under a level 6 heading would need level 7");
+    }
+    let delta = i16::from(level) - i16::from(heading.level);
+    let mut levels = Vec::with_capacity(len);
+    for h in &headings[from..from + len] {
+        let level = i16::from(h.level) + delta;
+        if !(1..=6).contains(&level) {
+            bail!(
+                "Moving the section would put the heading at line {} at level {}",
+                h.line_number,
+                level
+            );
+        }
+        levels.push(level as u8);
+    }
+
+    // Cut out the section and shift its headings to the new level, using the
+    // caller's headings (which may come from non-default parse options)
+    let eol = line_ending_at(content, heading.byte_offset);
+    let (_, end) = section_body_range(content, headings, heading);
+    let start = heading.byte_offset;
+    let text = &content[start..end];
+    let moved_headings: Vec<Heading> = headings[from..from + len]
+        .iter()
+        .map(|h| Heading {
+            byte_offset: h.byte_offset - start,
+            text_range: h.text_range.start - start..h.text_range.end - start,
+            ..h.clone()
+        })
+        .collect();
+    let moved = relevel_headings(text, moved_headings.iter().zip(levels));
+    let moved = normalize_body(&moved, eol) + eol;
+
+    // Where the section goes in the original document, mapped into the document
+    // without it: text after the cut is unchanged, only shifted
+    let target_offset = match target {
+        MoveTarget::Before(h) => h.byte_offset,
+        MoveTarget::After(h) | MoveTarget::Into(h) => section_body_range(content, headings, h).1,
+    };
+    let remaining = remove_range(content, start, end);
+    let after_cut = remaining.len() - (content.len() - end);
+    let offset = if target_offset >= end {
+        target_offset - end + after_cut
+    } else {
+        let kept = content[..start].trim_end().len();
+        if target_offset <= kept {
+            target_offset
+        } else {
+            after_cut
+        }
+    };
+
+    let mut output = splice_section(&remaining, offset, &moved, eol);
+    if !content.ends_with('\n') && output.ends_with(&moved) {
+        output.truncate(output.len() - eol.len());
+    }
+    Ok(output)
+}
+
+/// Whether text ends with an empty line (two consecutive line endings)
+fn ends_with_blank_line(text: &str) -> bool {
+    text.trim_end_matches('\r')
+        .strip_suffix('\n')
+        .is_some_and(|rest| rest.trim_end_matches([' ', '\t', '\r']).ends_with('\n'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_swap_sibling_sections() {
+        let content = "# Title\n\n## A\n\na text\n\n### A.1\n\nnested\n\n## B\n\nb text\n";
+        let headings = parse_headings(content);
+
+        // Move B (index 3) so that it becomes index 1, directly after the title
+        let moved = apply_section_moves(content, &headings, &[(3, 1)]).unwrap();
+        assert_eq!(
+            moved,
+            "# Title\n\n## B\n\nb text\n\n## A\n\na text\n\n### A.1\n\nnested\n"
+        );
+
+        let reparsed = parse_headings(&moved);
+        let texts: Vec<_> = reparsed.iter().map(|h| h.text.as_str()).collect();
+        assert_eq!(texts, ["Title", "B", "A", "A.1"]);
+
+        let crlf = "intro\r\n\r\n## A\r\n\r\na\r\n## B\r\nb\r\n";
+        let moved = apply_section_moves(crlf, &parse_headings(crlf), &[(1, 0)]).unwrap();
+        assert_eq!(moved, "intro\r\n\r\n## B\r\nb\r\n\r\n## A\r\n\r\na\r\n");
+    }
+
+    #[test]
+    fn test_section_moves_keep_text() {
+        let content = "# T\n\n\n## A\n\na\n\n\n\n## B\n\nb\n";
+        let headings = parse_headings(content);
+        assert_eq!(
+            apply_section_moves(content, &headings, &[]).unwrap(),
+            content
+        );
+        assert_eq!(
+            apply_section_moves(content, &headings, &[(2, 1), (2, 1)]).unwrap(),
+            content
+        );
+
+        // Only the junctions next to the moved section change
+        let moved = apply_section_moves(content, &headings, &[(2, 1)]).unwrap();
+        assert_eq!(moved, "# T\n\n\n## B\n\nb\n\n## A\n\na\n");
+
+        let mixed = "# T\n\n## A\r\na\r\n\r\n## B\nb";
+        let moved = apply_section_moves(mixed, &parse_headings(mixed), &[(2, 1)]).unwrap();
+        assert_eq!(moved, "# T\n\n## B\nb\n\n## A\r\na");
+    }
+
+    #[test]
+    fn test_reject_split_from_children() {
+        let content = "# Title\n\n## A\n\n### A.1\n\n## B\n";
+        let headings = parse_headings(content);
+
+        // Inserting B right before A.1 would make A.1 a child of B
+        let result = apply_section_moves(content, &headings, &[(3, 2)]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_replace_section_keeps_blank_lines() {
+        let content = "# Title\n\n## A\n\nold text\n\n### A.1\n\nnested\n\n\n## B\n\nb text\n";
+        let headings = parse_headings(content);
+
+        let replaced = replace_section(content, &headings, &headings[1], "new\ntext\n");
+        assert_eq!(
+            replaced,
+            "# Title\n\n## A\n\nnew\ntext\n\n\n## B\n\nb text\n"
+        );
+
+        let empty = "## A\n\n## B\n";
+        let headings = parse_headings(empty);
+        let filled = replace_section(empty, &headings, &headings[0], "body");
+        assert_eq!(filled, "## A\n\nbody\n\n## B\n");
+    }
+
+    #[test]
+    fn test_replace_section_crlf() {
+        let content = "# Title\r\n\r\nold\r\n";
+        let headings = parse_headings(content);
+
+        let replaced = replace_section(content, &headings, &headings[0], "one\ntwo");
+        assert_eq!(replaced, "# Title\r\n\r\none\r\ntwo\r\n");
+    }
+
+    #[test]
+    fn test_insert_section_positions() {
+        let content =
+            "# Changelog\n\n## 1.1\n\n- fix\n\n### Notes\n\nsome\n\n## 1.0\n\n- initial\n";
+        let headings = parse_headings(content);
+
+        let after = insert_section(
+            content,
+            &headings,
+            InsertPosition::After(&headings[1]),
+            None,
+            "1.0.1",
+            "- backport\n",
+        )
+        .unwrap();
+        assert_eq!(
+            after,
+            "# Changelog\n\n## 1.1\n\n- fix\n\n### Notes\n\nsome\n\n## 1.0.1\n\n- backport\n\n## 1.0\n\n- initial\n"
+        );
+
+        let child = insert_section(
+            content,
+            &headings,
+            InsertPosition::LastChildOf(&headings[1]),
+            None,
+            "Upgrading",
+            "# Steps\n\nrun it",
+        )
+        .unwrap();
+        let texts: Vec<_> = parse_headings(&child)
+            .into_iter()
+            .map(|h| (h.level, h.text))
+            .collect();
+        assert_eq!(texts[3], (3, "Upgrading".to_string()));
+        assert_eq!(texts[4], (4, "Steps".to_string()));
+
+        let first = insert_section(
+            content,
+            &headings,
+            InsertPosition::FirstChildOf(&headings[0]),
+            None,
+            "1.2",
+            "- new",
+        )
+        .unwrap();
+        assert!(first.starts_with("# Changelog\n\n## 1.2\n\n- new\n\n## 1.1\n"));
+
+        let before = insert_section(
+            content,
+            &headings,
+            InsertPosition::Before(&headings[3]),
+            None,
+            "1.0.1",
+            "",
+        )
+        .unwrap();
+        assert!(before.ends_with("some\n\n## 1.0.1\n\n## 1.0\n\n- initial\n"));
+
+        let end = insert_section(
+            "# A\n\ntext",
+            &parse_headings("# A\n\ntext"),
+            InsertPosition::EndOfDocument,
+            None,
+            "B",
+            "",
+        )
+        .unwrap();
+        assert_eq!(end, "# A\n\ntext\n\n# B\n");
+    }
+
+    #[test]
+    fn test_insert_section_rejects_bad_level() {
+        let content = "# A\n\n## B\n";
+        let headings = parse_headings(content);
+
+        let result = insert_section(
+            content,
+            &headings,
+            InsertPosition::LastChildOf(&headings[1]),
+            Some(2),
+            "C",
+            "",
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_delete_section() {
+        let content = "# Title\n\n## A\n\na text\n\n### A.1\n\nnested\n\n\n## B\n\nb text\n";
+        let headings = parse_headings(content);
+
+        let deleted = delete_section(content, &headings, &headings[1]);
+        assert_eq!(deleted, "# Title\n\n## B\n\nb text\n");
+
+        let headings = parse_headings(&deleted);
+        let last = delete_section(&deleted, &headings, &headings[1]);
+        assert_eq!(last, "# Title\n");
+    }
+
+    #[test]
+    fn test_unwrap_section() {
+        let content = "# Title\n\n## A\n\na text\n\n#### A.1\n\nnested\n\n##### A.1.1\n\n## B\n";
+        let headings = parse_headings(content);
+
+        let unwrapped = unwrap_section(content, &headings, &headings[1]);
+        assert_eq!(
+            unwrapped,
+            "# Title\n\n## A.1\n\nnested\n\n### A.1.1\n\n## B\n"
+        );
+
+        // Without subsections the whole section goes
+        let leaf = unwrap_section(content, &headings, &headings[4]);
+        assert_eq!(leaf, delete_section(content, &headings, &headings[4]));
+    }
+
+    #[test]
+    fn test_move_section() {
+        let content = "# Guide\n\n## Install\n\nsteps\n\n### Linux\n\napt\n\n## Usage\n\nrun it\n\n## FAQ\n\nask\n";
+        let headings = parse_headings(content);
+
+        // Install (with Linux) after Usage
+        let moved = move_section(
+            content,
+            &headings,
+            &headings[1],
+            MoveTarget::After(&headings[3]),
+        )
+        .unwrap();
+        assert_eq!(
+            moved,
+            "# Guide\n\n## Usage\n\nrun it\n\n## Install\n\nsteps\n\n### Linux\n\napt\n\n## FAQ\n\nask\n"
+        );
+
+        // FAQ under Usage becomes a level 3 heading
+        let nested = move_section(
+            content,
+            &headings,
+            &headings[4],
+            MoveTarget::Into(&headings[3]),
+        )
+        .unwrap();
+        assert_eq!(
+            nested,
+            "# Guide\n\n## Install\n\nsteps\n\n### Linux\n\napt\n\n## Usage\n\nrun it\n\n### FAQ\n\nask\n"
+        );
+
+        // Can't move a section into its own subsection
+        let result = move_section(
+            content,
+            &headings,
+            &headings[1],
+            MoveTarget::Into(&headings[2]),
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_move_section_rejects_levels_past_six() {
+        let content = "## A\n### B\n#### C\n##### D\n###### E\n## F\n### G\n#### H\n";
+        let headings = parse_headings(content);
+
+        // Under H, A becomes level 5 and C would need level 7
+        let err = move_section(
+            content,
+            &headings,
+            &headings[0],
+            MoveTarget::Into(&headings[7]),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("line 3 at level 7"));
+
+        // Next to H, A becomes level 4 and D would need level 7
+        let err = move_section(
+            content,
+            &headings,
+            &headings[0],
+            MoveTarget::After(&headings[7]),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("line 4 at level 7"));
+    }
+
+    #[test]
+    fn test_move_section_uses_given_headings() {
+        // Default parsing would find a heading inside the JSX block
+        let content = "<Note>\n# Inside JSX\n</Note>\n\n## A\n\na\n\n## B\n\nb\n\n## C\n\nc\n";
+        let options = crate::ParseOptions {
+            mdx: true,
+            ..Default::default()
+        };
+        let headings = crate::parse_headings_with(content, &options);
+
+        let moved = move_section(
+            content,
+            &headings,
+            &headings[2],
+            MoveTarget::Before(&headings[1]),
+        )
+        .unwrap();
+        assert_eq!(
+            moved,
+            "<Note>\n# Inside JSX\n</Note>\n\n## A\n\na\n\n## C\n\nc\n\n## B\n\nb\n"
+        );
+    }
+
+    #[test]
+    fn test_move_html_heading() {
+        let content = "## Usage\n\nrun\n\n<h2 id=\"x\">Install</h2>\n\n<H3>Linux</H3>\n";
+        let options = crate::ParseOptions {
+            html_headings: true,
+            ..Default::default()
+        };
+        let headings = crate::parse_headings_with(content, &options);
+
+        let moved = move_section(
+            content,
+            &headings,
+            &headings[1],
+            MoveTarget::Into(&headings[0]),
+        )
+        .unwrap();
+        assert_eq!(
+            moved,
+            "## Usage\n\nrun\n\n<h3 id=\"x\">Install</h3>\n\n<H4>Linux</H4>\n"
+        );
+    }
+
+    #[test]
+    fn test_setext_to_atx_keeps_attributes() {
+        let content = "# Top\n\nSetext {#sid .wide lang=en}\n------\n\ntext\n";
+        let headings = parse_headings(content);
+
+        let shifted = shift_section_levels(content, &headings, &headings[1], 1).unwrap();
+        assert_eq!(
+            shifted,
+            "# Top\n\n### Setext {#sid .wide lang=en}\n\ntext\n"
+        );
+        let reparsed = parse_headings(&shifted);
+        assert_eq!(reparsed[1].id, headings[1].id);
+        assert_eq!(reparsed[1].classes, headings[1].classes);
+        assert_eq!(reparsed[1].attributes, headings[1].attributes);
+
+        let plain = "  Plain\n  text\n---\n";
+        let headings = parse_headings(plain);
+        let shifted = shift_section_levels(plain, &headings, &headings[0], 2).unwrap();
+        assert_eq!(shifted, "  #### Plain text\n");
+    }
+
+    #[test]
+    fn test_shift_section_levels() {
+        let content = "# Title\n\nIntro\n=====\n\n## Details\n\n###### Deep\n\n# Next\n";
+        let headings = parse_headings(content);
+
+        let demoted = shift_section_levels(content, &headings, &headings[2], 1).unwrap_err();
+        assert!(demoted.to_string().contains("level 7"));
+
+        let promoted = shift_section_levels(content, &headings, &headings[2], -1).unwrap();
+        assert_eq!(
+            promoted,
+            "# Title\n\nIntro\n=====\n\n# Details\n\n##### Deep\n\n# Next\n"
+        );
+
+        let shallow = "# Title\n\nIntro\n=====\n\n## Details\n\n# Next\n";
+        let headings = parse_headings(shallow);
+        let demoted = shift_section_levels(shallow, &headings, &headings[1], 2).unwrap();
+        assert_eq!(demoted, "# Title\n\n### Intro\n\n#### Details\n\n# Next\n");
+        assert!(shift_section_levels(shallow, &headings, &headings[1], -1).is_err());
+
+        let other = parse_headings("## Elsewhere\n");
+        assert!(shift_section_levels(shallow, &headings, &other[0], 1).is_err());
+    }
+}
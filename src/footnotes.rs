@@ -0,0 +1,178 @@
+//! Footnote reference and definition extraction
+
+use crate::scan::CodeBlockTracker;
+use std::collections::HashSet;
+
+/// A footnote reference in running text (`[^label]`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FootnoteReference {
+    /// Footnote label without the `^`
+    pub label: String,
+    /// Line number of the reference (1-indexed)
+    pub line_number: usize,
+}
+
+/// A footnote definition (`[^label]: text`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FootnoteDefinition {
+    /// Footnote label without the `^`
+    pub label: String,
+    /// Line number of the definition (1-indexed)
+    pub line_number: usize,
+    /// Text on the definition's first line
+    pub text: String,
+}
+
+/// All footnote references and definitions in a document
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Footnotes {
+    /// References in document order
+    pub references: Vec<FootnoteReference>,
+    /// Definitions in document order
+    pub definitions: Vec<FootnoteDefinition>,
+}
+
+impl Footnotes {
+    /// References whose label has no definition
+    pub fn orphaned_references(&self) -> Vec<&FootnoteReference> {
+        let defined: HashSet<_> = self.definitions.iter().map(|d| &d.label).collect();
+        self.references
+            .iter()
+            .filter(|r| !defined.contains(&r.label))
+            .collect()
+    }
+
+    /// Definitions that are never referenced
+    pub fn orphaned_definitions(&self) -> Vec<&FootnoteDefinition> {
+        let referenced: HashSet<_> = self.references.iter().map(|r| &r.label).collect();
+        self.definitions
+            .iter()
+            .filter(|d| !referenced.contains(&d.label))
+            .collect()
+    }
+}
+
+/// Parse a footnote definition line into its label and text
+fn parse_definition(line: &str) -> Option<(&str, &str)> {
+    if line.len() - line.trim_start_matches(' ').len() > 3 {
+        return None;
+    }
+
+    let rest = line.trim_start().strip_prefix("[^")?;
+    let end = rest.find("]:")?;
+    let label = &rest[..end];
+    if label.is_empty() || label.contains(char::is_whitespace) {
+        return None;
+    }
+
+    Some((label, rest[end + 2..].trim()))
+}
+
+/// Find footnote reference labels on a line, skipping code spans
+fn scan_references(line: &str) -> Vec<&str> {
+    let mut labels = Vec::new();
+    let mut rest = line;
+    let mut in_code = false;
+
+    while let Some(pos) = rest.find(['`', '[']) {
+        let after = &rest[pos + 1..];
+        if rest[pos..].starts_with('`') {
+            in_code = !in_code;
+            rest = after;
+            continue;
+        }
+
+        if !in_code
+            && !rest[..pos].ends_with('\\')
+            && let Some(inner) = after.strip_prefix('^')
+            && let Some(end) = inner.find(']')
+            && end > 0
+            && !inner[..end].contains(char::is_whitespace)
+        {
+            labels.push(&inner[..end]);
+            rest = &inner[end + 1..];
+        } else {
+            rest = after;
+        }
+    }
+
+    labels
+}
+
+/// Extract footnote references and definitions from markdown content
+///
+/// Footnotes inside code blocks and code spans are ignored. Use
+/// [`Footnotes::orphaned_references`] and [`Footnotes::orphaned_definitions`] to
+/// find dangling footnotes before publishing.
+pub fn parse_footnotes(content: &str) -> Footnotes {
+    let mut code = CodeBlockTracker::default();
+    let mut footnotes = Footnotes::default();
+
+    for (idx, line) in content.lines().enumerate() {
+        if code.is_code(line) {
+            continue;
+        }
+
+        let line_number = idx + 1;
+        let body = match parse_definition(line) {
+            Some((label, text)) => {
+                footnotes.definitions.push(FootnoteDefinition {
+                    label: label.to_string(),
+                    line_number,
+                    text: text.to_string(),
+                });
+                // Definitions can themselves reference other footnotes
+                text
+            }
+            None => line,
+        };
+
+        footnotes
+            .references
+            .extend(
+                scan_references(body)
+                    .into_iter()
+                    .map(|label| FootnoteReference {
+                        label: label.to_string(),
+                        line_number,
+                    }),
+            );
+    }
+
+    footnotes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_footnotes() {
+        let content = "# Article\n\nA claim[^1] and another[^missing]. `[^code]`\n\n[^1]: Source one.\n[^unused]: Never cited.\n";
+        let footnotes = parse_footnotes(content);
+
+        assert_eq!(footnotes.references.len(), 2);
+        assert_eq!(footnotes.references[0].label, "1");
+        assert_eq!(footnotes.references[0].line_number, 3);
+        assert_eq!(footnotes.definitions.len(), 2);
+        assert_eq!(footnotes.definitions[0].text, "Source one.");
+        assert_eq!(footnotes.definitions[0].line_number, 5);
+
+        let orphans: Vec<_> = footnotes
+            .orphaned_references()
+            .iter()
+            .map(|r| r.label.as_str())
+            .collect();
+        assert_eq!(orphans, ["missing"]);
+
+        let unused: Vec<_> = footnotes
+            .orphaned_definitions()
+            .iter()
+            .map(|d| d.label.as_str())
+            .collect();
+        assert_eq!(unused, ["unused"]);
+    }
+}
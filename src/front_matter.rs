@@ -0,0 +1,194 @@
+//! Metadata blocks at the very start of a document
+
+/// The syntax of a front matter block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FrontMatterFormat {
+    /// YAML delimited by `---` lines (closed by `---` or `...`)
+    Yaml,
+    /// TOML delimited by `+++` lines, as used by Hugo
+    Toml,
+}
+
+impl FrontMatterFormat {
+    /// Detect the format from the first line of a document
+    pub(crate) fn from_opening(line: &str) -> Option<Self> {
+        match line.trim_end() {
+            "---" => Some(Self::Yaml),
+            "+++" => Some(Self::Toml),
+            _ => None,
+        }
+    }
+
+    /// Whether a block of this format can still be front matter after its
+    /// `count`th line, `line`, if no closing delimiter has been seen yet
+    ///
+    /// The first line must be a key (`title: ...`, `title = ...` or a TOML
+    /// `[table]`), so that a document starting with a `---` thematic break isn't
+    /// taken for front matter, and blocks longer than
+    /// [`MAX_FRONT_MATTER_LINES`] are given up.
+    pub(crate) fn may_continue(self, line: &str, count: usize) -> bool {
+        count <= MAX_FRONT_MATTER_LINES && (count > 1 || self.is_key_line(line))
+    }
+
+    fn is_key_line(self, line: &str) -> bool {
+        let key = |separator: char| {
+            line.split_once(separator).is_some_and(|(key, value)| {
+                !key.trim().is_empty()
+                    && !line.starts_with([' ', '\t', '#', '-'])
+                    && (separator == '=' || value.is_empty() || value.starts_with([' ', '\t']))
+            })
+        };
+        match self {
+            Self::Yaml => key(':'),
+            Self::Toml => line.starts_with('[') || key('='),
+        }
+    }
+
+    /// Whether `line` closes a block of this format
+    pub(crate) fn is_closing(self, line: &str) -> bool {
+        match self {
+            Self::Yaml => matches!(line.trim_end(), "---" | "..."),
+            Self::Toml => line.trim_end() == "+++",
+        }
+    }
+}
+
+/// Most lines a front matter block may have before its opening delimiter is
+/// taken for regular markdown
+pub(crate) const MAX_FRONT_MATTER_LINES: usize = 1000;
+
+/// A front matter block and its location
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FrontMatter {
+    /// Syntax of the block
+    pub format: FrontMatterFormat,
+    /// Text between the delimiter lines
+    pub raw: String,
+    /// Line number of the opening delimiter (always 1)
+    pub start_line: usize,
+    /// Line number of the closing delimiter
+    pub end_line: usize,
+}
+
+/// Detect a front matter block at the start of a document
+///
+/// The first line must be a delimiter, the next one a key such as `title:`, and
+/// a matching closing delimiter must follow within [`MAX_FRONT_MATTER_LINES`]
+/// lines; otherwise there is no front matter (the leading `---` is just a
+/// thematic break).
+pub fn parse_front_matter(content: &str) -> Option<FrontMatter> {
+    let content = content.strip_prefix(crate::scan::BOM).unwrap_or(content);
+    let mut lines = content.lines();
+    let format = FrontMatterFormat::from_opening(lines.next()?)?;
+
+    let mut raw = Vec::new();
+    for (idx, line) in lines.enumerate() {
+        if format.is_closing(line) {
+            return Some(FrontMatter {
+                format,
+                raw: raw.join("\n"),
+                start_line: 1,
+                end_line: idx + 2,
+            });
+        }
+        if !format.may_continue(line, idx + 1) {
+            return None;
+        }
+        raw.push(line);
+    }
+
+    None
+}
+
+#[cfg(feature = "yaml")]
+impl FrontMatter {
+    /// Deserialize the block as YAML
+    pub fn parse_yaml(&self) -> Result<serde_yaml::Value, serde_yaml::Error> {
+        serde_yaml::from_str(&self.raw)
+    }
+}
+
+#[cfg(feature = "toml")]
+impl FrontMatter {
+    /// Deserialize the block as TOML
+    pub fn parse_toml(&self) -> Result<toml::Table, toml::de::Error> {
+        self.raw.parse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_headings;
+
+    #[test]
+    fn test_parse_front_matter() {
+        let content = "---\ntitle: Guide\ntags: [a, b]\n---\n\n# Guide\n\nIntro\n---\n";
+        let front_matter = parse_front_matter(content).unwrap();
+
+        assert_eq!(front_matter.format, FrontMatterFormat::Yaml);
+        assert_eq!(front_matter.raw, "title: Guide\ntags: [a, b]");
+        assert_eq!((front_matter.start_line, front_matter.end_line), (1, 4));
+
+        // Front matter lines are not mistaken for setext headings
+        let headings = parse_headings(content);
+        let texts: Vec<_> = headings.iter().map(|h| h.text.as_str()).collect();
+        assert_eq!(texts, ["Guide", "Intro"]);
+        assert_eq!(headings[0].line_number, 6);
+    }
+
+    #[test]
+    fn test_unclosed_front_matter() {
+        let content = "---\n\n# Title\n\nText\n";
+        assert!(parse_front_matter(content).is_none());
+
+        // Headings after an unclosed delimiter are still found
+        let headings = parse_headings(content);
+        assert_eq!(headings.len(), 1);
+        assert_eq!(headings[0].line_number, 3);
+    }
+
+    #[test]
+    fn test_leading_thematic_break() {
+        // A `---` later on doesn't turn everything before it into front matter
+        let content = "---\n# Title\n\nIntro\n\n## Usage\n\n---\n\n## End\n";
+        assert!(parse_front_matter(content).is_none());
+
+        let headings = parse_headings(content);
+        let texts: Vec<_> = headings.iter().map(|h| h.text.as_str()).collect();
+        assert_eq!(texts, ["Title", "Usage", "End"]);
+        assert_eq!(&content[headings[1].byte_range()], "## Usage");
+    }
+
+    #[test]
+    fn test_toml_front_matter() {
+        let content = "+++\ntitle = \"Hugo page\"\n---\n+++\n\n# Page\n";
+        let front_matter = parse_front_matter(content).unwrap();
+
+        assert_eq!(front_matter.format, FrontMatterFormat::Toml);
+        assert_eq!(front_matter.raw, "title = \"Hugo page\"\n---");
+        assert_eq!(front_matter.end_line, 4);
+
+        let headings = parse_headings(content);
+        assert_eq!(headings.len(), 1);
+        assert_eq!(headings[0].line_number, 6);
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_parse_toml() {
+        let front_matter = parse_front_matter("+++\ntitle = \"Hugo page\"\n+++\n").unwrap();
+        let table = front_matter.parse_toml().unwrap();
+        assert_eq!(table["title"].as_str(), Some("Hugo page"));
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn test_parse_yaml() {
+        let front_matter = parse_front_matter("---\ntitle: Guide\n...\n").unwrap();
+        let value = front_matter.parse_yaml().unwrap();
+        assert_eq!(value["title"].as_str(), Some("Guide"));
+    }
+}
@@ -0,0 +1,116 @@
+//! Typo-tolerant heading lookup
+
+use crate::{Heading, find_section};
+
+/// Levenshtein distance between two strings, counted in characters
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+
+    row[b.len()]
+}
+
+/// Similarity of one query word to one heading word, from 0 to 1
+fn word_score(word: &str, query: &str) -> f64 {
+    if word == query {
+        return 1.0;
+    }
+    if word.starts_with(query) {
+        return 0.9;
+    }
+
+    let query_len = query.chars().count();
+    let word_len = word.chars().count();
+
+    // Abbreviations like "cfg" for "config": every character in order, same first letter
+    let mut chars = word.chars();
+    let subsequence =
+        word.chars().next() == query.chars().next() && query.chars().all(|q| chars.any(|c| c == q));
+    let subsequence_score = if subsequence {
+        0.5 + 0.3 * query_len as f64 / word_len as f64
+    } else {
+        0.0
+    };
+
+    let typo_score = 1.0 - edit_distance(word, query) as f64 / word_len.max(query_len) as f64;
+    subsequence_score.max(typo_score)
+}
+
+/// Similarity of heading text to a query, from 0 to 1
+///
+/// Each query word is scored against its best-matching word in the text, and the
+/// scores are averaged.
+pub(crate) fn fuzzy_score(text: &str, query: &str) -> f64 {
+    let text = text.to_lowercase();
+    let query = query.to_lowercase();
+    let words: Vec<&str> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect();
+    let query_words: Vec<&str> = query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect();
+    if words.is_empty() || query_words.is_empty() {
+        return 0.0;
+    }
+
+    let total: f64 = query_words
+        .iter()
+        .map(|q| words.iter().map(|w| word_score(w, q)).fold(0.0, f64::max))
+        .sum();
+    total / query_words.len() as f64
+}
+
+/// Minimum [`fuzzy_score`] for a heading to count as a match
+pub(crate) const FUZZY_THRESHOLD: f64 = 0.6;
+
+/// Find a section like [`find_section`], falling back to fuzzy matching
+///
+/// When neither a line number, an exact match nor a substring match is found,
+/// every heading is scored by how well its words match the query's words,
+/// tolerating typos (`"instalation"`) and abbreviations (`"cfg options"`). The
+/// best-scoring heading is returned, preferring the earliest on ties, or `None`
+/// if nothing is reasonably close.
+pub fn find_section_fuzzy<'a>(headings: &'a [Heading], query: &str) -> Option<&'a Heading> {
+    if let Some(h) = find_section(headings, query) {
+        return Some(h);
+    }
+
+    let mut best: Option<(f64, &Heading)> = None;
+    for h in headings {
+        let score = fuzzy_score(&h.text, query);
+        if score >= FUZZY_THRESHOLD && best.is_none_or(|(s, _)| score > s) {
+            best = Some((score, h));
+        }
+    }
+    best.map(|(_, h)| h)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_headings;
+
+    #[test]
+    fn test_find_section_fuzzy() {
+        let headings =
+            parse_headings("# Guide\n## Installation\n## Configuration Options\n## Contributing\n");
+
+        let find = |q| find_section_fuzzy(&headings, q).map(|h| h.text.as_str());
+        assert_eq!(find("instalation"), Some("Installation"));
+        assert_eq!(find("cfg options"), Some("Configuration Options"));
+        assert_eq!(find("contrib"), Some("Contributing"));
+        assert_eq!(find("zzz"), None);
+    }
+}
@@ -0,0 +1,61 @@
+//! Render extracted sections to HTML
+
+use crate::{Heading, extract_section, get_section_range};
+use pulldown_cmark::{Options, Parser, html};
+
+/// Extract a section and render it to an HTML fragment
+///
+/// Takes the same line range as [`extract_section`] and returns the rendered
+/// markup without any `<html>`/`<body>` wrapper. Headings keep their levels from
+/// the source, so a `##` section renders as `<h2>` with its subsections below it.
+pub fn render_section_html(content: &str, start: usize, end: Option<usize>) -> String {
+    let section = extract_section(content, start, end);
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_FOOTNOTES;
+
+    let mut output = String::new();
+    html::push_html(&mut output, Parser::new_ext(&section, options));
+    output
+}
+
+/// Render the section starting at `heading` to an HTML fragment
+///
+/// Looks up the section's range in `headings` like [`get_section_range`], then
+/// renders it as [`render_section_html`] does.
+pub fn render_heading_html(content: &str, headings: &[Heading], heading: &Heading) -> String {
+    let (start, end) = get_section_range(headings, heading);
+    render_section_html(content, start, end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_section_html() {
+        let content = "# Title\n\n## Install\n\nRun the installer.\n\n```bash\nmake install\n```\n\n## Next\n";
+
+        let html = render_section_html(content, 3, Some(11));
+        assert!(html.starts_with("<h2>Install</h2>\n<p>Run the installer.</p>\n"));
+        assert!(html.contains("<pre><code class=\"language-bash\">make install\n</code></pre>"));
+        assert!(!html.contains("Next"));
+        assert!(!html.contains("<html>"));
+    }
+
+    #[test]
+    fn test_render_heading_html() {
+        let content = "# Title\n\n## Install\n\n*Run* it.\n\n## Next\n";
+        let headings = crate::parse_headings(content);
+
+        assert_eq!(
+            render_heading_html(content, &headings, &headings[1]),
+            render_section_html(content, 3, Some(7))
+        );
+        assert_eq!(
+            render_heading_html(content, &headings, &headings[2]),
+            "<h2>Next</h2>\n"
+        );
+    }
+}
@@ -0,0 +1,157 @@
+//! Raw HTML headings (`<h2>Install</h2>`) in markdown
+
+use crate::Heading;
+use crate::scan::{CodeBlockTracker, strip_line_ending};
+
+/// Value of a `name="value"` (or single-quoted) attribute in an opening tag
+fn tag_attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let lower = tag.to_ascii_lowercase();
+    let mut from = 0;
+    while let Some(pos) = lower[from..].find(name).map(|p| p + from) {
+        from = pos + name.len();
+        let preceded = lower[..pos].ends_with(char::is_whitespace);
+        let rest = lower[from..].trim_start();
+        let Some(value) = rest.strip_prefix('=') else {
+            continue;
+        };
+        if !preceded {
+            continue;
+        }
+        let value_start = tag.len() - value.trim_start().len();
+        let quote = tag[value_start..].chars().next()?;
+        if quote != '"' && quote != '\'' {
+            continue;
+        }
+        let end = tag[value_start + 1..].find(quote)?;
+        return Some(&tag[value_start + 1..value_start + 1 + end]);
+    }
+    None
+}
+
+/// Remove tags from inline HTML, keeping the text between them
+fn strip_tags(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Parse a line holding a whole `<hN ...>text</hN>` element
+fn parse_html_heading(line: &str) -> Option<(u8, &str, Option<&str>, Option<&str>)> {
+    let indent = line.len() - line.trim_start().len();
+    let rest = &line[indent..];
+    let bytes = rest.as_bytes();
+    if indent > 3
+        || bytes.len() < 4
+        || bytes[0] != b'<'
+        || !bytes[1].eq_ignore_ascii_case(&b'h')
+        || !(b'1'..=b'6').contains(&bytes[2])
+        || !(bytes[3] == b'>' || bytes[3].is_ascii_whitespace())
+    {
+        return None;
+    }
+    let level = bytes[2] - b'0';
+
+    let open_end = rest.find('>')?;
+    let tag = &rest[..open_end];
+    let close = format!("</h{level}>");
+    let inner_end = rest.to_ascii_lowercase().rfind(&close)?;
+    if inner_end < open_end || !rest[inner_end + close.len()..].trim().is_empty() {
+        return None;
+    }
+
+    let inner = &rest[open_end + 1..inner_end];
+    Some((
+        level,
+        inner,
+        tag_attribute(tag, "id"),
+        tag_attribute(tag, "class"),
+    ))
+}
+
+/// Rewrite the tags of a line holding an HTML heading for another level (1-6),
+/// or `None` if the line isn't one
+pub(crate) fn relevel_html_heading(line: &str, level: u8) -> Option<String> {
+    let (old, ..) = parse_html_heading(line)?;
+    let indent = line.len() - line.trim_start().len();
+    let close = line.to_ascii_lowercase().rfind(&format!("</h{old}>"))?;
+
+    let digit = char::from(b'0' + level).to_string();
+    let mut output = line.to_string();
+    output.replace_range(close + 3..close + 4, &digit);
+    output.replace_range(indent + 2..indent + 3, &digit);
+    Some(output)
+}
+
+/// Find raw HTML headings written on a single line, outside code blocks
+pub(crate) fn parse_html_headings(content: &str) -> Vec<Heading> {
+    let mut code = CodeBlockTracker::default();
+    let mut headings = Vec::new();
+    let mut byte_offset = 0;
+
+    for (idx, raw) in content.split_inclusive('\n').enumerate() {
+        let line = strip_line_ending(raw);
+        let line_start = byte_offset;
+        byte_offset += raw.len();
+        if code.is_code(line) {
+            continue;
+        }
+        let Some((level, inner, id, class)) = parse_html_heading(line) else {
+            continue;
+        };
+
+        let trimmed = inner.trim();
+        let start = trimmed.as_ptr() as usize - line.as_ptr() as usize;
+        headings.push(Heading {
+            line_number: idx + 1,
+            level,
+            text: strip_tags(trimmed),
+            id: id.map(str::to_string),
+            classes: class
+                .map(|c| c.split_whitespace().map(str::to_string).collect())
+                .unwrap_or_default(),
+            byte_offset: line_start,
+            byte_len: line.len(),
+            column: line[..start].chars().count() + 1,
+            text_range: line_start + start..line_start + start + trimmed.len(),
+            ..Default::default()
+        });
+    }
+
+    headings
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ParseOptions, parse_headings_with};
+
+    #[test]
+    fn test_html_headings() {
+        let content = "# Guide\n\n<h2 id=\"install\" class=\"wide note\">Install <code>mdi</code></h2>\n\nText\n\n```html\n<h2>Example</h2>\n```\n\n<H3>Usage</H3>\n<h2>Open\n";
+        let options = ParseOptions {
+            html_headings: true,
+            ..Default::default()
+        };
+
+        let headings = parse_headings_with(content, &options);
+        let texts: Vec<_> = headings.iter().map(|h| h.text.as_str()).collect();
+        assert_eq!(texts, ["Guide", "Install mdi", "Usage"]);
+        assert_eq!(headings[1].line_number, 3);
+        assert_eq!(headings[1].id.as_deref(), Some("install"));
+        assert_eq!(headings[1].classes, ["wide", "note"]);
+        assert_eq!(headings[2].level, 3);
+        assert_eq!(&content[headings[2].text_range.clone()], "Usage");
+
+        assert_eq!(
+            parse_headings_with(content, &ParseOptions::default()).len(),
+            1
+        );
+    }
+}
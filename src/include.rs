@@ -0,0 +1,260 @@
+//! Resolving include directives into one combined document
+
+use crate::scan::CodeBlockTracker;
+use crate::workspace::normalize_path;
+use crate::{Heading, parse_headings};
+use anyhow::{Context, Result, bail};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Where a line of an [`IncludedDocument`] came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SourceLine {
+    /// File the line was read from
+    pub path: PathBuf,
+    /// Line number in that file (1-indexed)
+    pub line_number: usize,
+}
+
+/// A document with its include directives replaced by the included content
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IncludedDocument {
+    /// The combined content
+    pub content: String,
+    /// Origin of every line of `content`, in order
+    pub sources: Vec<SourceLine>,
+}
+
+impl IncludedDocument {
+    /// Origin of a line of the combined content (1-indexed)
+    pub fn source(&self, line_number: usize) -> Option<&SourceLine> {
+        self.sources.get(line_number.checked_sub(1)?)
+    }
+
+    /// Headings of the combined content, each with the file and line it came from
+    pub fn outline(&self) -> Vec<(Heading, &SourceLine)> {
+        parse_headings(&self.content)
+            .into_iter()
+            .filter_map(|h| {
+                let source = self.source(h.line_number)?;
+                Some((h, source))
+            })
+            .collect()
+    }
+}
+
+/// An include directive: the path and an optional 1-indexed inclusive line range
+struct Directive<'a> {
+    path: &'a str,
+    first: Option<usize>,
+    last: Option<usize>,
+}
+
+/// Parse a line consisting of `<!-- include: path -->` or `{{#include path}}`
+///
+/// The mdbook form accepts a line range: `path:3:10`, `path:3:`, `path::10` or
+/// `path:3` for a single line.
+fn parse_directive(line: &str, in_code: bool) -> Option<Directive<'_>> {
+    let line = line.trim();
+    if !in_code
+        && let Some(path) = line
+            .strip_prefix("<!--")
+            .and_then(|rest| rest.strip_suffix("-->"))
+            .and_then(|inner| inner.trim().strip_prefix("include:"))
+    {
+        let path = path.trim();
+        return (!path.is_empty()).then_some(Directive {
+            path,
+            first: None,
+            last: None,
+        });
+    }
+
+    let spec = line.strip_prefix("{{#include")?.strip_suffix("}}")?.trim();
+    let mut parts = spec.splitn(3, ':');
+    let path = parts.next().filter(|p| !p.is_empty())?;
+    let bound = |part: Option<&str>| -> Option<Option<usize>> {
+        match part {
+            None | Some("") => Some(None),
+            Some(n) => n.parse().ok().map(Some),
+        }
+    };
+    let first = bound(parts.next());
+    let (first, last) = match (first, parts.next()) {
+        // `path:3` means just that line
+        (Some(Some(n)), None) => (Some(n), Some(n)),
+        (Some(first), last) => (first, bound(last)?),
+        // Not a line range (mdbook anchors aren't supported), so part of the path
+        (None, _) => {
+            return Some(Directive {
+                path: spec,
+                first: None,
+                last: None,
+            });
+        }
+    };
+    Some(Directive { path, first, last })
+}
+
+/// Recursively inline `path` into `document`
+fn inline_file(
+    path: &Path,
+    range: (Option<usize>, Option<usize>),
+    load: &mut dyn FnMut(&Path) -> std::io::Result<String>,
+    stack: &mut Vec<PathBuf>,
+    document: &mut IncludedDocument,
+) -> Result<()> {
+    if let Some(pos) = stack.iter().position(|p| p == path) {
+        let cycle: Vec<_> = stack[pos..]
+            .iter()
+            .chain([&path.to_path_buf()])
+            .map(|p| p.display().to_string())
+            .collect();
+        bail!("Include cycle: {}", cycle.join(" -> "));
+    }
+    let content = load(path).with_context(|| format!("Failed to read include: {:?}", path))?;
+    stack.push(path.to_path_buf());
+
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let mut code = CodeBlockTracker::default();
+    let (first, last) = (range.0.unwrap_or(1), range.1.unwrap_or(usize::MAX));
+
+    for (idx, raw) in content.split_inclusive('\n').enumerate() {
+        let line_number = idx + 1;
+        if line_number < first || line_number > last {
+            continue;
+        }
+        let line = raw.trim_end_matches(['\n', '\r']);
+        let in_code = code.is_code(line);
+
+        match parse_directive(line, in_code) {
+            Some(directive) => {
+                let target = normalize_path(&dir.join(directive.path));
+                inline_file(
+                    &target,
+                    (directive.first, directive.last),
+                    load,
+                    stack,
+                    document,
+                )?;
+            }
+            None => {
+                document.content.push_str(raw);
+                if !raw.ends_with('\n') {
+                    document.content.push('\n');
+                }
+                document.sources.push(SourceLine {
+                    path: path.to_path_buf(),
+                    line_number,
+                });
+            }
+        }
+    }
+
+    stack.pop();
+    Ok(())
+}
+
+/// Read a file and recursively replace its include directives with the
+/// referenced files
+///
+/// Recognizes `<!-- include: other.md -->` (outside code blocks) and mdbook's
+/// `{{#include other.md}}` (anywhere, so code can be pulled into fences), each on
+/// a line of its own. Paths are relative to the including file. Fails on
+/// unreadable files and on include cycles.
+pub fn resolve_includes(path: &Path) -> Result<IncludedDocument> {
+    resolve_includes_with(path, |p| fs::read_to_string(p))
+}
+
+/// Like [`resolve_includes`], but reading files through `load`
+pub fn resolve_includes_with(
+    path: &Path,
+    mut load: impl FnMut(&Path) -> std::io::Result<String>,
+) -> Result<IncludedDocument> {
+    let mut document = IncludedDocument {
+        content: String::new(),
+        sources: Vec::new(),
+    };
+    inline_file(
+        &normalize_path(path),
+        (None, None),
+        &mut load,
+        &mut Vec::new(),
+        &mut document,
+    )?;
+    Ok(document)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::io;
+
+    fn loader(files: &[(&str, &str)]) -> impl FnMut(&Path) -> io::Result<String> + use<> {
+        let files: HashMap<PathBuf, String> = files
+            .iter()
+            .map(|(path, content)| (PathBuf::from(path), content.to_string()))
+            .collect();
+        move |path| {
+            files
+                .get(path)
+                .cloned()
+                .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))
+        }
+    }
+
+    #[test]
+    fn test_resolve_includes() {
+        let load = loader(&[
+            (
+                "book/main.md",
+                "# Book\n\n<!-- include: parts/intro.md -->\n\n```rust\n{{#include ../src/lib.rs:2:3}}\n```\n",
+            ),
+            (
+                "book/parts/intro.md",
+                "## Intro\n\nHello\n{{#include ../notes.md}}",
+            ),
+            ("book/notes.md", "### Notes"),
+            (
+                "src/lib.rs",
+                "// skipped\nfn a() {}\nfn b() {}\n// skipped\n",
+            ),
+        ]);
+
+        let document = resolve_includes_with(Path::new("book/main.md"), load).unwrap();
+        assert_eq!(
+            document.content,
+            "# Book\n\n## Intro\n\nHello\n### Notes\n\n```rust\nfn a() {}\nfn b() {}\n```\n"
+        );
+
+        let outline: Vec<_> = document
+            .outline()
+            .into_iter()
+            .map(|(h, source)| (h.text, source.path.clone(), source.line_number))
+            .collect();
+        assert_eq!(
+            outline,
+            [
+                ("Book".to_string(), PathBuf::from("book/main.md"), 1),
+                ("Intro".to_string(), PathBuf::from("book/parts/intro.md"), 1),
+                ("Notes".to_string(), PathBuf::from("book/notes.md"), 1),
+            ]
+        );
+        assert_eq!(document.source(9).unwrap().line_number, 2);
+    }
+
+    #[test]
+    fn test_include_errors() {
+        let load = loader(&[
+            ("a.md", "# A\n<!-- include: b.md -->\n"),
+            ("b.md", "# B\n<!-- include: ./a.md -->\n"),
+        ]);
+        let err = resolve_includes_with(Path::new("a.md"), load).unwrap_err();
+        assert_eq!(err.to_string(), "Include cycle: a.md -> b.md -> a.md");
+
+        let load = loader(&[("a.md", "<!-- include: missing.md -->\n")]);
+        assert!(resolve_includes_with(Path::new("a.md"), load).is_err());
+    }
+}
@@ -0,0 +1,178 @@
+//! Inline markdown handling (emphasis, code spans, links)
+
+use crate::Heading;
+
+/// Find the index of the bracket closing the one at `open`, honoring nesting and escapes
+pub(crate) fn matching_bracket(
+    chars: &[char],
+    open: usize,
+    open_char: char,
+    close_char: char,
+) -> Option<usize> {
+    let mut depth = 0;
+    let mut i = open;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 1,
+            c if c == open_char => depth += 1,
+            c if c == close_char => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Whether the character at `idx` is whitespace or outside the text
+fn is_space_at(chars: &[char], idx: Option<usize>) -> bool {
+    idx.and_then(|i| chars.get(i))
+        .is_none_or(|c| c.is_whitespace())
+}
+
+/// Strip inline markdown syntax, keeping the text a reader would see
+///
+/// Removes emphasis and strikethrough markers, unwraps code spans, replaces links
+/// and images with their text, unwraps autolinks and resolves backslash escapes.
+/// Underscores inside words (`snake_case`) and lone `*` between spaces are kept.
+pub fn strip_inline_markdown(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '\\' if chars.get(i + 1).is_some_and(|n| n.is_ascii_punctuation()) => {
+                out.push(chars[i + 1]);
+                i += 2;
+            }
+            '`' => {
+                let run = chars[i..].iter().take_while(|&&c| c == '`').count();
+                let body_start = i + run;
+                let close = (body_start..chars.len()).find(|&j| {
+                    chars[j..].iter().take_while(|&&c| c == '`').count() == run
+                        && (j == 0 || chars[j - 1] != '`')
+                });
+                match close {
+                    Some(end) => {
+                        let body: String = chars[body_start..end].iter().collect();
+                        out.push_str(body.trim());
+                        i = end + run;
+                    }
+                    None => {
+                        out.extend(&chars[i..body_start]);
+                        i = body_start;
+                    }
+                }
+            }
+            '!' if chars.get(i + 1) == Some(&'[') => i += 1,
+            '[' => match matching_bracket(&chars, i, '[', ']') {
+                Some(close) => {
+                    let label: String = chars[i + 1..close].iter().collect();
+                    out.push_str(&strip_inline_markdown(&label));
+                    i = close + 1;
+
+                    // Skip an inline destination or reference label
+                    match chars.get(i) {
+                        Some('(') => {
+                            if let Some(end) = matching_bracket(&chars, i, '(', ')') {
+                                i = end + 1;
+                            }
+                        }
+                        Some('[') => {
+                            if let Some(end) = matching_bracket(&chars, i, '[', ']') {
+                                i = end + 1;
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                None => {
+                    out.push(c);
+                    i += 1;
+                }
+            },
+            '<' => {
+                let close = chars[i..].iter().position(|&c| c == '>').map(|p| i + p);
+                let autolink = close.map(|end| chars[i + 1..end].iter().collect::<String>());
+                match autolink {
+                    Some(inner)
+                        if !inner.contains(char::is_whitespace)
+                            && (inner.contains(':') || inner.contains('@')) =>
+                    {
+                        out.push_str(&inner);
+                        i += inner.chars().count() + 2;
+                    }
+                    _ => {
+                        out.push(c);
+                        i += 1;
+                    }
+                }
+            }
+            '*' | '_' | '~' => {
+                let run = chars[i..].iter().take_while(|&&x| x == c).count();
+                let before = i.checked_sub(1);
+                let after = Some(i + run);
+                let spaced = is_space_at(&chars, before) && is_space_at(&chars, after);
+                let intraword = c == '_'
+                    && !is_space_at(&chars, before)
+                    && !is_space_at(&chars, after)
+                    && chars[i - 1].is_alphanumeric()
+                    && chars[i + run].is_alphanumeric();
+                let single_tilde = c == '~' && run == 1;
+
+                if spaced || intraword || single_tilde {
+                    out.extend(&chars[i..i + run]);
+                }
+                i += run;
+            }
+            _ => {
+                out.push(c);
+                i += 1;
+            }
+        }
+    }
+
+    out
+}
+
+impl Heading {
+    /// Heading text with inline markdown (emphasis, code spans, links) removed
+    pub fn plain_text(&self) -> String {
+        strip_inline_markdown(&self.text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_inline_markdown() {
+        assert_eq!(
+            strip_inline_markdown("**Bold** `code` [link](https://example.com)"),
+            "Bold code link"
+        );
+        assert_eq!(
+            strip_inline_markdown("_Emphasis_ and ~~gone~~ with snake_case"),
+            "Emphasis and gone with snake_case"
+        );
+        assert_eq!(
+            strip_inline_markdown("![Logo](logo.png) [ref][1]"),
+            "Logo ref"
+        );
+        assert_eq!(
+            strip_inline_markdown("`` a`b ``, 2 * 3, \\*x\\*"),
+            "a`b, 2 * 3, *x*"
+        );
+        assert_eq!(
+            strip_inline_markdown("<https://example.com> <b>"),
+            "https://example.com <b>"
+        );
+    }
+}
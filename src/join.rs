@@ -0,0 +1,91 @@
+//! Concatenating documents into one, the inverse of splitting
+
+use crate::edit::relevel_headings;
+use crate::front_matter::parse_front_matter;
+use crate::parse_headings;
+
+/// Options for [`join_documents`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JoinOptions {
+    /// Levels to demote every heading by (clamped to 6)
+    pub shift: u8,
+    /// H1 heading to put above all documents
+    pub title: Option<String>,
+}
+
+impl Default for JoinOptions {
+    fn default() -> Self {
+        Self {
+            shift: 1,
+            title: None,
+        }
+    }
+}
+
+/// Join documents, given as `(name, content)` pairs, into a single document
+///
+/// Each document's front matter is dropped and its headings are demoted by
+/// `shift` levels so they nest under the title. Documents are separated by a
+/// blank line and a `<!-- file: name -->` comment marking where each begins.
+pub fn join_documents(documents: &[(&str, &str)], options: &JoinOptions) -> String {
+    let mut output = String::new();
+    if let Some(title) = &options.title {
+        output.push_str(&format!("# {}\n", title));
+    }
+
+    for (name, content) in documents {
+        let skip = parse_front_matter(content).map_or(0, |fm| fm.end_line);
+        let body_start = content
+            .split_inclusive('\n')
+            .take(skip)
+            .map(str::len)
+            .sum::<usize>();
+        let body = &content[body_start..];
+
+        let headings = parse_headings(body);
+        let shifted = relevel_headings(
+            body,
+            headings
+                .iter()
+                .map(|h| (h, h.level.saturating_add(options.shift).min(6))),
+        );
+
+        if !output.is_empty() {
+            output.push('\n');
+        }
+        output.push_str(&format!("<!-- file: {} -->\n\n", name));
+        output.push_str(shifted.trim_matches('\n'));
+        output.push('\n');
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_join_documents() {
+        let options = JoinOptions {
+            title: Some("Handout".to_string()),
+            ..Default::default()
+        };
+        let joined = join_documents(
+            &[
+                ("a.md", "---\ntitle: A\n---\n\n# Intro\n\nHello.\n"),
+                ("b.md", "Usage\n=====\n\n##### Deep\n\n###### Deepest\n"),
+            ],
+            &options,
+        );
+
+        assert_eq!(
+            joined,
+            "# Handout\n\n<!-- file: a.md -->\n\n## Intro\n\nHello.\n\n<!-- file: b.md -->\n\nUsage\n-----\n\n###### Deep\n\n###### Deepest\n"
+        );
+        assert_eq!(
+            join_documents(&[("a.md", "# A\n")], &JoinOptions::default()),
+            "<!-- file: a.md -->\n\n## A\n"
+        );
+    }
+}
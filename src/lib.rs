@@ -3,8 +3,101 @@
 //! This library provides functions to parse markdown headings and extract
 //! sections from documents based on their outline structure.
 
+mod chunk;
+mod code;
+#[cfg(feature = "commonmark")]
+mod commonmark;
+mod diff;
+mod document;
+mod edit;
+mod footnotes;
+mod front_matter;
+mod fuzzy;
+#[cfg(feature = "html")]
+mod html;
+mod html_headings;
+mod include;
+mod inline;
+mod join;
+mod links;
+mod lint;
+mod mdx;
+mod merge;
+mod numbering;
+mod outline;
+mod plain;
+mod query;
+mod reflow;
+mod remap;
+mod scan;
+mod search;
+mod slug;
+mod split;
+mod stats;
+mod table;
+mod tasks;
+mod toc;
+mod tree;
+mod walk;
+mod wikilinks;
+mod workspace;
+
+pub use chunk::{Chunk, ChunkOptions, chunk_document};
+pub use code::{CodeBlock, parse_code_blocks};
+pub use diff::{HeadingChange, OutlineDiff, diff_outlines};
+pub use document::Document;
+pub use edit::{
+    InsertPosition, MoveTarget, apply_section_moves, delete_section, insert_section, move_section,
+    replace_section, shift_section_levels, unwrap_section,
+};
+pub use footnotes::{FootnoteDefinition, FootnoteReference, Footnotes, parse_footnotes};
+pub use front_matter::{FrontMatter, FrontMatterFormat, parse_front_matter};
+pub use fuzzy::find_section_fuzzy;
+#[cfg(feature = "html")]
+pub use html::{render_heading_html, render_section_html};
+pub use include::{IncludedDocument, SourceLine, resolve_includes, resolve_includes_with};
+pub use inline::strip_inline_markdown;
+pub use join::{JoinOptions, join_documents};
+pub use links::{
+    Image, Link, LinkKind, parse_images, parse_links, validate_anchors, validate_anchors_with,
+};
+pub use lint::{LintFinding, LintKind, lint_document, lint_structure};
+pub use merge::{MergeResult, merge_sections};
+pub use numbering::{apply_section_numbers, section_numbers};
+#[cfg(feature = "json")]
+pub use outline::outline_to_json;
+#[cfg(feature = "yaml")]
+pub use outline::outline_to_yaml;
+pub use outline::{OutlineNode, nested_outline};
+pub use plain::to_plain_text;
+pub use query::query;
+pub use reflow::reflow_section;
+pub use remap::{LineEdit, remap_headings, remap_line};
+pub use scan::{parse_headings_bytes, parse_headings_from_reader};
+pub use search::{GrepHit, grep_with_context};
+#[cfg(feature = "regex")]
+pub use search::{find_sections_matching, grep_regex};
+pub use slug::{
+    DuplicateHeadings, DuplicateKind, Slugger, find_duplicate_headings, heading_anchor,
+    heading_anchors,
+};
+pub use split::{SplitSection, split_document, split_index};
+pub use stats::{SectionStats, section_stats};
+pub use table::{Alignment, Table, parse_tables};
+pub use tasks::{TaskItem, parse_tasks};
+pub use toc::{
+    TOC_END_MARKER, TOC_START_MARKER, TocOptions, generate_toc, insert_or_update_toc, update_toc,
+};
+pub use tree::{Section, SectionTree, build_section_tree};
+pub use walk::{collect_markdown_files, is_markdown_file};
+pub use wikilinks::{WikiLink, parse_wikilinks};
+pub use workspace::{
+    BrokenLink, BrokenLinkReason, ResolvedLink, Workspace, WorkspaceFile, percent_decode,
+};
+
 /// A markdown heading with its location and level
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Heading {
     /// Line number (1-indexed)
     pub line_number: usize,
@@ -12,55 +105,157 @@ pub struct Heading {
     pub level: u8,
     /// The heading text (without the # prefix)
     pub text: String,
+    /// Explicit identifier from a trailing `{#id}` attribute, stripped from `text`
+    pub id: Option<String>,
+    /// Classes from a trailing attribute block (`{.unnumbered}`), without the `.`;
+    /// Pandoc's `{-}` shorthand is reported as `unnumbered`
+    pub classes: Vec<String>,
+    /// `key=value` pairs from a trailing attribute block, in source order
+    pub attributes: Vec<(String, String)>,
+    /// Byte offset of the start of the heading line in the document
+    pub byte_offset: usize,
+    /// Length in bytes of the heading's source (excluding the final line ending);
+    /// for setext headings this spans the text lines and the underline
+    pub byte_len: usize,
+    /// Column (1-indexed, in characters) where the heading text starts on its line
+    pub column: usize,
+    /// Byte range of the heading text as written in the document, excluding `#`
+    /// marks, closing hashes and a `{#id}` attribute; for multi-line setext
+    /// headings this runs from the first text line to the end of the last one
+    pub text_range: std::ops::Range<usize>,
+    /// Whether the heading is inside a block quote (`> ## Quoted`); such headings
+    /// are only reported with [`ParseOptions::blockquotes`]
+    pub in_blockquote: bool,
+}
+
+impl Default for Heading {
+    /// An empty level 1 heading at the start of the document
+    fn default() -> Self {
+        Self {
+            line_number: 0,
+            level: 1,
+            text: String::new(),
+            id: None,
+            classes: Vec::new(),
+            attributes: Vec::new(),
+            byte_offset: 0,
+            byte_len: 0,
+            column: 0,
+            text_range: 0..0,
+            in_blockquote: false,
+        }
+    }
+}
+
+impl Heading {
+    /// Whether the heading's attribute block lists `class`
+    pub fn has_class(&self, class: &str) -> bool {
+        self.classes.iter().any(|c| c == class)
+    }
+
+    /// Byte range of the heading's source in the document
+    pub fn byte_range(&self) -> std::ops::Range<usize> {
+        self.byte_offset..self.byte_offset + self.byte_len
+    }
 }
 
 /// Parse all headings from markdown content
 ///
 /// Returns a list of headings in document order with their line numbers and levels.
-/// Skips headings inside fenced code blocks.
+/// Recognizes both ATX (`## Title`) and setext (`Title` underlined with `===` or
+/// `---`) headings, and skips headings inside fenced and indented code blocks and
+/// front matter. Headings inside block quotes are skipped (see
+/// [`ParseOptions::blockquotes`]). A leading UTF-8 byte order mark is ignored.
+///
+/// With the `commonmark` feature, headings are extracted with pulldown-cmark
+/// instead, which also finds headings nested in list items. The same backend is
+/// used by [`parse_headings_with`], [`parse_headings_bytes`] (for valid UTF-8)
+/// and [`Document`]; only the streaming [`parse_headings_from_reader`] always
+/// uses the line scanner.
 pub fn parse_headings(content: &str) -> Vec<Heading> {
-    let mut headings = Vec::new();
-    let mut in_code_block = false;
+    parse_with_backend(content, false)
+}
 
-    for (idx, line) in content.lines().enumerate() {
-        let line_number = idx + 1;
-        let trimmed = line.trim_start();
+/// Parse headings with the scanner or, with the `commonmark` feature, pulldown-cmark
+fn parse_with_backend(content: &str, blockquotes: bool) -> Vec<Heading> {
+    #[cfg(feature = "commonmark")]
+    return commonmark::parse_headings(content, blockquotes);
+    #[cfg(not(feature = "commonmark"))]
+    scan::scan_headings(content, blockquotes)
+}
 
-        // Toggle code block state on fence markers
-        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
-            in_code_block = !in_code_block;
-            continue;
-        }
+/// Syntax extensions recognized by [`parse_headings_with`]
+///
+/// The default enables none of them, which parses plain markdown as
+/// [`parse_headings`] does.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Treat the input as MDX: skip `import`/`export` statements, JSX blocks and
+    /// `{/* */}` comments
+    pub mdx: bool,
+    /// Include raw HTML headings (`<h2>Install</h2>`) written on a single line
+    pub html_headings: bool,
+    /// Include headings inside block quotes, marked with
+    /// [`in_blockquote`](Heading::in_blockquote)
+    pub blockquotes: bool,
+}
 
-        // Skip lines inside code blocks
-        if in_code_block {
-            continue;
-        }
+/// Parse all headings with syntax extensions enabled
+///
+/// Byte offsets and line numbers always refer to `content` as given.
+pub fn parse_headings_with(content: &str, options: &ParseOptions) -> Vec<Heading> {
+    let masked;
+    let content = if options.mdx {
+        masked = mdx::mask_mdx(content);
+        &masked
+    } else {
+        content
+    };
 
-        if let Some(rest) = trimmed.strip_prefix('#') {
-            let mut level = 1_u8;
-            let mut remaining = rest;
+    let mut headings = parse_with_backend(content, options.blockquotes);
+    if options.html_headings {
+        headings.extend(html_headings::parse_html_headings(content));
+        headings.sort_by_key(|h| h.byte_offset);
+    }
+    headings
+}
 
-            while let Some(r) = remaining.strip_prefix('#') {
-                level += 1;
-                remaining = r;
-                if level >= 6 {
-                    break;
-                }
-            }
+/// Controls how [`find_section_with`] matches a query against headings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FindOptions {
+    /// Treat a numeric query as a line number first
+    pub line_numbers: bool,
+    /// Compare text case-sensitively
+    pub case_sensitive: bool,
+    /// Accept headings that start with the query
+    pub prefix: bool,
+    /// Accept headings that contain the query
+    pub substring: bool,
+}
 
-            // Must have space after #'s
-            if let Some(text) = remaining.strip_prefix(' ') {
-                headings.push(Heading {
-                    line_number,
-                    level,
-                    text: text.trim().to_string(),
-                });
-            }
+impl Default for FindOptions {
+    /// The matching used by [`find_section`]: line numbers, then exact text, then
+    /// case-insensitive substrings
+    fn default() -> Self {
+        Self {
+            line_numbers: true,
+            case_sensitive: false,
+            prefix: false,
+            substring: true,
         }
     }
+}
 
-    headings
+impl FindOptions {
+    /// Only accept headings whose text equals the query exactly
+    pub fn exact() -> Self {
+        Self {
+            line_numbers: false,
+            case_sensitive: true,
+            prefix: false,
+            substring: false,
+        }
+    }
 }
 
 /// Find a section by line number or heading text
@@ -69,22 +264,224 @@ pub fn parse_headings(content: &str) -> Vec<Heading> {
 /// 1. If `section` parses as a number, find heading at that line
 /// 2. Exact text match
 /// 3. Case-insensitive substring match
+///
+/// A numeric query that matches no heading's line is matched as text, so a
+/// heading named "2024" can still be found. Use [`find_section_with`] to change
+/// the strategy.
 pub fn find_section<'a>(headings: &'a [Heading], section: &str) -> Option<&'a Heading> {
-    // Try parsing as line number first
-    if let Ok(line_num) = section.parse::<usize>() {
-        return headings.iter().find(|h| h.line_number == line_num);
+    find_section_with(headings, section, &FindOptions::default())
+}
+
+/// Find a section using the given matching strategy
+///
+/// Tries, in order and as enabled by `opts`: the heading at a line number, an
+/// exact match (case-sensitive first, then case-insensitive unless
+/// `case_sensitive` is set), a prefix match and a substring match. Each step
+/// returns the first heading in document order.
+pub fn find_section_with<'a>(
+    headings: &'a [Heading],
+    section: &str,
+    opts: &FindOptions,
+) -> Option<&'a Heading> {
+    if opts.line_numbers
+        && let Ok(line_num) = section.parse::<usize>()
+        && let Some(h) = headings.iter().find(|h| h.line_number == line_num)
+    {
+        return Some(h);
     }
 
-    // Try exact match first
     if let Some(h) = headings.iter().find(|h| h.text == section) {
         return Some(h);
     }
 
-    // Try case-insensitive contains
+    let fold = |text: &str| {
+        if opts.case_sensitive {
+            text.to_string()
+        } else {
+            text.to_lowercase()
+        }
+    };
+    let query = fold(section);
+
+    if !opts.case_sensitive
+        && let Some(h) = headings.iter().find(|h| fold(&h.text) == query)
+    {
+        return Some(h);
+    }
+
+    if opts.prefix
+        && let Some(h) = headings.iter().find(|h| fold(&h.text).starts_with(&query))
+    {
+        return Some(h);
+    }
+
+    if opts.substring {
+        return headings.iter().find(|h| fold(&h.text).contains(&query));
+    }
+
+    None
+}
+
+/// Find every section matching a line number or heading text
+///
+/// Unlike [`find_section`], which takes the first match, this returns all
+/// headings that match: the heading at a line number if `section` parses as one,
+/// otherwise every heading containing `section` as a case-insensitive substring
+/// (which includes exact matches), in document order.
+pub fn find_all_sections<'a>(headings: &'a [Heading], section: &str) -> Vec<&'a Heading> {
+    if let Ok(line_num) = section.parse::<usize>() {
+        return headings
+            .iter()
+            .filter(|h| h.line_number == line_num)
+            .collect();
+    }
+
     let section_lower = section.to_lowercase();
     headings
         .iter()
-        .find(|h| h.text.to_lowercase().contains(&section_lower))
+        .filter(|h| h.text.to_lowercase().contains(&section_lower))
+        .collect()
+}
+
+/// Split a section path on `/`, allowing `\/` for a literal slash in a heading
+fn split_section_path(path: &str) -> Vec<String> {
+    let mut segments = vec![String::new()];
+    let mut chars = path.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'/') => {
+                segments.last_mut().unwrap().push('/');
+                chars.next();
+            }
+            '/' => segments.push(String::new()),
+            _ => segments.last_mut().unwrap().push(c),
+        }
+    }
+
+    segments.iter().map(|s| s.trim().to_string()).collect()
+}
+
+/// Resolve the remaining path segments among `candidates`, backtracking on ambiguity
+fn resolve_path<'a>(
+    headings: &'a [Heading],
+    candidates: &[&'a Heading],
+    segments: &[String],
+) -> Option<&'a Heading> {
+    let (segment, rest) = segments.split_first()?;
+
+    // Exact matches are preferred over case-insensitive substring matches
+    let segment_lower = segment.to_lowercase();
+    let exact = candidates.iter().filter(|h| h.text == *segment);
+    let partial = candidates
+        .iter()
+        .filter(|h| h.text != *segment && h.text.to_lowercase().contains(&segment_lower));
+
+    for &heading in exact.chain(partial) {
+        if rest.is_empty() {
+            return Some(heading);
+        }
+
+        let (start, end) = get_section_range(headings, heading);
+        let descendants: Vec<&Heading> = headings
+            .iter()
+            .filter(|h| h.line_number > start && end.is_none_or(|e| h.line_number < e))
+            .collect();
+
+        if let Some(found) = resolve_path(headings, &descendants, rest) {
+            return Some(found);
+        }
+    }
+
+    None
+}
+
+/// Find a section by a slash-delimited path of heading texts
+///
+/// Each segment of a path like `"Guide/Installation/Linux"` is matched (exactly,
+/// then as a case-insensitive substring) against the headings nested inside the
+/// previous segment's section, at any depth. This disambiguates headings that are
+/// reused across a document, such as an "Examples" subsection in every chapter.
+/// Use `\/` for a slash that is part of a heading's text.
+pub fn find_section_by_path<'a>(headings: &'a [Heading], path: &str) -> Option<&'a Heading> {
+    let segments = split_section_path(path);
+    if segments.iter().any(|s| s.is_empty()) {
+        return None;
+    }
+
+    let all: Vec<&Heading> = headings.iter().collect();
+    resolve_path(headings, &all, &segments)
+}
+
+/// Find every section whose heading text, or path of heading texts, is exactly `query`
+///
+/// Unlike [`find_section`] and [`find_section_by_path`] there is no line number
+/// lookup and no case-insensitive or partial fallback, and all matches are
+/// returned, so callers that rewrite a document can tell an unambiguous match
+/// from a typo or a reused heading. Path segments are separated by `/` as in
+/// [`find_section_by_path`]. Results are in document order.
+pub fn find_sections_exact<'a>(headings: &'a [Heading], query: &str) -> Vec<&'a Heading> {
+    let segments = split_section_path(query);
+    let mut found: Vec<&Heading> = headings.iter().filter(|h| h.text == query).collect();
+
+    if segments.iter().all(|s| !s.is_empty()) {
+        let mut candidates: Vec<&Heading> = headings.iter().collect();
+        for (idx, segment) in segments.iter().enumerate() {
+            let matched: Vec<&Heading> = candidates
+                .into_iter()
+                .filter(|h| h.text == *segment)
+                .collect();
+            if idx + 1 == segments.len() {
+                found.extend(matched);
+                break;
+            }
+            candidates = headings
+                .iter()
+                .filter(|h| {
+                    matched.iter().any(|m| {
+                        let (start, end) = get_section_range(headings, m);
+                        h.line_number > start && end.is_none_or(|e| h.line_number < e)
+                    })
+                })
+                .collect();
+        }
+    }
+
+    found.sort_by_key(|h| h.line_number);
+    found.dedup_by_key(|h| h.line_number);
+    found
+}
+
+/// Match quality of `text` against `query` (both lowercase), lower is better
+fn match_rank(text: &str, query: &str) -> Option<u8> {
+    if text == query {
+        Some(0)
+    } else if text.starts_with(query) {
+        Some(1)
+    } else if text.contains(query) {
+        Some(2)
+    } else {
+        // Subsequence: every query character appears in order
+        let mut chars = text.chars();
+        query.chars().all(|q| chars.any(|c| c == q)).then_some(3)
+    }
+}
+
+/// Rank headings by how well their text matches a query
+///
+/// Matching is case-insensitive and ordered by quality: exact, prefix, substring,
+/// then subsequence (all query characters appear in order). Ties keep document
+/// order, headings that don't match are dropped, and an empty query returns all
+/// headings.
+pub fn rank_sections<'a>(headings: &'a [Heading], query: &str) -> Vec<&'a Heading> {
+    let query_lower = query.to_lowercase();
+    let mut ranked: Vec<(u8, &Heading)> = headings
+        .iter()
+        .filter_map(|h| match_rank(&h.text.to_lowercase(), &query_lower).map(|rank| (rank, h)))
+        .collect();
+
+    ranked.sort_by_key(|(rank, _)| *rank);
+    ranked.into_iter().map(|(_, h)| h).collect()
 }
 
 /// Get the line range for a section (start line, end line)
@@ -104,24 +501,165 @@ pub fn get_section_range(headings: &[Heading], heading: &Heading) -> (usize, Opt
     (start, end)
 }
 
+/// Get the line number of the last non-blank line belonging to a section
+///
+/// Unlike the end returned by [`get_section_range`], this skips the trailing blank
+/// lines before the next heading, which makes it the insertion point for appending
+/// to a section. A section without body text returns the heading's own line.
+pub fn section_last_content_line(content: &str, headings: &[Heading], heading: &Heading) -> usize {
+    let lines: Vec<&str> = content.lines().collect();
+    let (start, end) = get_section_range(headings, heading);
+    let end_idx = end
+        .map(|e| e.saturating_sub(1))
+        .unwrap_or(lines.len())
+        .min(lines.len());
+
+    // Body lines follow the heading line (index `start` is the line after it)
+    lines[start.min(end_idx)..end_idx]
+        .iter()
+        .rposition(|line| !line.trim().is_empty())
+        .map(|idx| start + idx + 1)
+        .unwrap_or(start)
+}
+
+/// Byte offsets where each line of `content` starts
+///
+/// A trailing newline doesn't start another line, so empty content has none.
+pub(crate) fn line_starts(content: &str) -> Vec<usize> {
+    std::iter::once(0)
+        .chain(content.match_indices('\n').map(|(idx, _)| idx + 1))
+        .filter(|&offset| offset < content.len())
+        .collect()
+}
+
+/// Byte range covering lines `start` to `end` (exclusive, 1-indexed), including
+/// their line endings
+fn line_span(content: &str, start: usize, end: Option<usize>) -> std::ops::Range<usize> {
+    let mut from = content.len();
+    let mut to = content.len();
+    let mut offset = 0;
+
+    for (idx, raw) in content.split_inclusive('\n').enumerate() {
+        let line_number = idx + 1;
+        if line_number == start.max(1) {
+            from = offset;
+        }
+        if end == Some(line_number) {
+            to = offset;
+            break;
+        }
+        offset += raw.len();
+    }
+
+    from.min(to)..to
+}
+
+/// Get the byte range of a section, including its subsections
+///
+/// Covers the same text as [`extract_section`] with the range from
+/// [`get_section_range`], for editors that work with offsets rather than lines
+/// (folding ranges, selections).
+pub fn get_section_byte_range(
+    content: &str,
+    headings: &[Heading],
+    heading: &Heading,
+) -> std::ops::Range<usize> {
+    let (start, end) = get_section_range(headings, heading);
+    line_span(content, start, end)
+}
+
 /// Extract a section's content from the document
 ///
 /// Returns the text from `start` line to `end` line (exclusive),
-/// or to the end of the document if `end` is None.
+/// or to the end of the document if `end` is None. The lines are returned
+/// exactly as written, keeping `\r\n` line endings and the final line's
+/// newline (or lack of one), so extracted sections round-trip byte for byte.
 pub fn extract_section(content: &str, start: usize, end: Option<usize>) -> String {
-    let lines: Vec<&str> = content.lines().collect();
-    let start_idx = start.saturating_sub(1);
-    let end_idx = end.map(|e| e.saturating_sub(1)).unwrap_or(lines.len());
+    extract_section_str(content, start, end).to_string()
+}
 
-    lines[start_idx..end_idx].join("\n")
+/// Borrow a section's content from the document without allocating
+///
+/// Same as [`extract_section`], but returns a slice of `content`.
+pub fn extract_section_str(content: &str, start: usize, end: Option<usize>) -> &str {
+    &content[line_span(content, start, end)]
+}
+
+/// Extract several sections at once, in the order of `selected`
+///
+/// `headings` is the document's full heading list, used to find where each
+/// selected section ends. The document's lines are indexed once, so this is
+/// cheaper than calling [`extract_section`] per heading on large documents.
+pub fn extract_sections(content: &str, headings: &[Heading], selected: &[&Heading]) -> Vec<String> {
+    let line_starts = line_starts(content);
+    let offset = |line: usize| line_starts.get(line - 1).copied().unwrap_or(content.len());
+
+    selected
+        .iter()
+        .map(|heading| {
+            let (start, end) = get_section_range(headings, heading);
+            let to = end.map_or(content.len(), offset);
+            let from = offset(start.max(1)).min(to);
+            content[from..to].to_string()
+        })
+        .collect()
+}
+
+/// Extract a section's body: its content without the heading line(s) and the
+/// blank lines directly after them
+///
+/// Subsections are included, as with [`extract_section`]. Useful for templating
+/// and transclusion, where the surrounding document supplies its own heading.
+pub fn extract_section_body(content: &str, headings: &[Heading], heading: &Heading) -> String {
+    let (start, end) = get_section_range(headings, heading);
+    strip_heading(content, line_span(content, start, end), heading).to_string()
+}
+
+/// Borrow the section at `range` of `content` without the heading's source and
+/// the blank lines following it
+pub(crate) fn strip_heading<'a>(
+    content: &'a str,
+    range: std::ops::Range<usize>,
+    heading: &Heading,
+) -> &'a str {
+    // The section can start before the heading, e.g. at a byte order mark
+    let cut = heading.byte_range().end.clamp(range.start, range.end);
+    let rest = &content[cut..range.end];
+    let mut rest = rest
+        .strip_prefix("\r\n")
+        .or_else(|| rest.strip_prefix('\n'))
+        .unwrap_or(rest);
+    while let Some(pos) = rest.find('\n')
+        && rest[..pos].trim().is_empty()
+    {
+        rest = &rest[pos + 1..];
+    }
+    rest
 }
 
 /// Format a heading as an outline entry with line number and indentation
 pub fn format_outline_entry(heading: &Heading) -> String {
-    let indent = "  ".repeat((heading.level - 1) as usize);
+    let indent = "  ".repeat(heading.level.saturating_sub(1) as usize);
     format!("{:>4}:{}{}", heading.line_number, indent, heading.text)
 }
 
+/// Collapse an outline to `max_depth`, pairing each visible heading with the
+/// number of deeper headings hidden below it
+///
+/// Hidden headings that precede the first visible one are not counted.
+pub fn prune_outline(headings: &[Heading], max_depth: u8) -> Vec<(&Heading, usize)> {
+    let mut pruned: Vec<(&Heading, usize)> = Vec::new();
+    for h in headings {
+        if h.level <= max_depth {
+            pruned.push((h, 0));
+        } else if let Some((_, hidden)) = pruned.last_mut() {
+            // Anything deeper than the cutoff nests under the last visible heading
+            *hidden += 1;
+        }
+    }
+    pruned
+}
+
 /// Get subsection headings within a section's range
 pub fn get_subsections(
     headings: &[Heading],
@@ -145,6 +683,72 @@ pub fn get_first_subsection<'a>(headings: &'a [Heading], heading: &Heading) -> O
         .find(|h| h.line_number > start && h.level > heading.level)
 }
 
+/// Get the breadcrumb of a heading: its enclosing headings from the outermost
+/// down, ending with the heading itself
+///
+/// Skipped levels are simply absent, so an H3 directly below an H1 yields two
+/// entries.
+pub fn heading_path<'a>(headings: &'a [Heading], heading: &Heading) -> Vec<&'a Heading> {
+    search::ancestry_at(headings, heading.line_number)
+}
+
+/// Get the enclosing heading: the closest preceding one with a lower level
+pub fn parent_of<'a>(headings: &'a [Heading], heading: &Heading) -> Option<&'a Heading> {
+    headings
+        .iter()
+        .take_while(|h| h.line_number < heading.line_number)
+        .filter(|h| h.level < heading.level)
+        .last()
+}
+
+/// Headings directly below `start`, i.e. those whose closest preceding heading
+/// with a lower level is `start` (the document root when `start` is `None`)
+fn direct_children<'a>(
+    headings: &'a [Heading],
+    start: Option<&Heading>,
+) -> impl Iterator<Item = &'a Heading> {
+    let after = start.map_or(0, |s| s.line_number);
+    let mut min_level = u8::MAX;
+    headings
+        .iter()
+        .skip_while(move |h| h.line_number <= after)
+        .take_while(move |h| start.is_none_or(|s| h.level > s.level))
+        .filter(move |h| {
+            // A child is not nested under any heading in between
+            let child = h.level <= min_level;
+            min_level = min_level.min(h.level);
+            child
+        })
+}
+
+/// Get the headings nested directly below a heading, in document order
+///
+/// A skipped level still counts as a direct child, so the `###` in
+/// `# A` / `### B` is a child of `A`.
+pub fn children_of<'a>(headings: &'a [Heading], heading: &Heading) -> Vec<&'a Heading> {
+    direct_children(headings, Some(heading)).collect()
+}
+
+/// Headings sharing a heading's parent, including the heading itself
+fn siblings_of<'a>(headings: &'a [Heading], heading: &Heading) -> Vec<&'a Heading> {
+    direct_children(headings, parent_of(headings, heading)).collect()
+}
+
+/// Get the next heading with the same parent
+pub fn next_sibling<'a>(headings: &'a [Heading], heading: &Heading) -> Option<&'a Heading> {
+    siblings_of(headings, heading)
+        .into_iter()
+        .find(|h| h.line_number > heading.line_number)
+}
+
+/// Get the previous heading with the same parent
+pub fn prev_sibling<'a>(headings: &'a [Heading], heading: &Heading) -> Option<&'a Heading> {
+    siblings_of(headings, heading)
+        .into_iter()
+        .take_while(|h| h.line_number < heading.line_number)
+        .last()
+}
+
 /// Extract section summary: intro text up to first subsection
 ///
 /// Returns the text from the section start to the first subsection heading,
@@ -237,6 +841,25 @@ pub fn extract_section_shallow(
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_find_sections_exact() {
+        let content = "# Guide\n\n## Install\n\n### Examples\n\n## Uninstall\n\n### Examples\n";
+        let headings = parse_headings(content);
+
+        let lines = |query: &str| -> Vec<usize> {
+            find_sections_exact(&headings, query)
+                .iter()
+                .map(|h| h.line_number)
+                .collect()
+        };
+        assert_eq!(lines("Install"), vec![3]);
+        assert_eq!(lines("install"), Vec::<usize>::new());
+        assert_eq!(lines("Examples"), vec![5, 9]);
+        assert_eq!(lines("Uninstall/Examples"), vec![9]);
+        assert_eq!(lines("Guide/Examples"), vec![5, 9]);
+        assert_eq!(lines("3"), Vec::<usize>::new());
+    }
+
     #[test]
     fn test_parse_headings() {
         let content = "# Title\n\nSome text\n\n## Section 1\n\nMore text\n\n### Subsection\n";
@@ -256,6 +879,92 @@ mod tests {
         assert_eq!(headings[2].line_number, 9);
     }
 
+    #[test]
+    fn test_heading_byte_offsets() {
+        let content = "# Title\r\n\r\nText\n## Section \n";
+        let headings = parse_headings(content);
+
+        assert_eq!(headings[0].byte_offset, 0);
+        assert_eq!(headings[0].byte_len, 7);
+        assert_eq!(&content[headings[1].byte_range()], "## Section ");
+    }
+
+    #[test]
+    fn test_find_all_sections() {
+        let headings = parse_headings(
+            "# Guide\n## Install\n### Examples\n## Usage\n### More examples\n### Notes\n",
+        );
+
+        let found: Vec<_> = find_all_sections(&headings, "examples")
+            .iter()
+            .map(|h| h.line_number)
+            .collect();
+        assert_eq!(found, [3, 5]);
+        assert_eq!(find_all_sections(&headings, "4")[0].text, "Usage");
+        assert!(find_all_sections(&headings, "missing").is_empty());
+    }
+
+    #[test]
+    fn test_find_section_with_options() {
+        let headings = parse_headings("# Releases\n## 2024\n## Notes 2023\n## Install Guide\n");
+
+        // No heading on line 2024, so the number is matched as text
+        assert_eq!(find_section(&headings, "2024").unwrap().line_number, 2);
+
+        let no_lines = FindOptions {
+            line_numbers: false,
+            ..Default::default()
+        };
+        assert_eq!(
+            find_section_with(&headings, "2", &no_lines).unwrap().text,
+            "2024"
+        );
+        assert_eq!(find_section(&headings, "2").unwrap().text, "2024");
+
+        let exact = FindOptions::exact();
+        assert!(find_section_with(&headings, "install guide", &exact).is_none());
+        assert!(find_section_with(&headings, "Install Guide", &exact).is_some());
+
+        let prefix_only = FindOptions {
+            substring: false,
+            prefix: true,
+            ..Default::default()
+        };
+        assert!(find_section_with(&headings, "guide", &prefix_only).is_none());
+        assert_eq!(
+            find_section_with(&headings, "notes", &prefix_only)
+                .unwrap()
+                .text,
+            "Notes 2023"
+        );
+    }
+
+    #[test]
+    fn test_extract_section_preserves_line_endings() {
+        let content = "# Title\r\n\r\nintro\r\n\r\n## Next\r\ntail";
+        let headings = parse_headings(content);
+
+        let (start, end) = get_section_range(&headings, &headings[1]);
+        assert_eq!(extract_section(content, start, end), "## Next\r\ntail");
+
+        let (start, end) = get_section_range(&headings, &headings[0]);
+        assert_eq!(extract_section(content, start, end), content);
+        assert_eq!(
+            extract_section(content, 1, Some(5)),
+            "# Title\r\n\r\nintro\r\n\r\n"
+        );
+    }
+
+    #[test]
+    fn test_extract_section_str_borrows() {
+        let content = "# A\n\na\n\n# B\n\nb\n";
+        let section = extract_section_str(content, 5, None);
+
+        assert_eq!(section, "# B\n\nb\n");
+        assert_eq!(section.as_ptr(), content[8..].as_ptr());
+        assert_eq!(extract_section_str(content, 1, Some(5)), "# A\n\na\n\n");
+    }
+
     #[test]
     fn test_find_section_by_line() {
         let headings = vec![
@@ -263,11 +972,13 @@ mod tests {
                 line_number: 1,
                 level: 1,
                 text: "Title".into(),
+                ..Default::default()
             },
             Heading {
                 line_number: 5,
                 level: 2,
                 text: "Section".into(),
+                ..Default::default()
             },
         ];
 
@@ -283,11 +994,13 @@ mod tests {
                 line_number: 1,
                 level: 1,
                 text: "Title".into(),
+                ..Default::default()
             },
             Heading {
                 line_number: 5,
                 level: 2,
                 text: "My Section".into(),
+                ..Default::default()
             },
         ];
 
@@ -301,6 +1014,199 @@ mod tests {
         assert_eq!(found.unwrap().text, "My Section");
     }
 
+    #[test]
+    fn test_find_section_by_path() {
+        let content =
+            "# Guide\n\n## Install\n\n### Examples\n\n## Usage\n\n### Examples\n\n### I/O\n";
+        let headings = parse_headings(content);
+
+        let found = find_section_by_path(&headings, "Guide/Usage/Examples").unwrap();
+        assert_eq!(found.line_number, 9);
+
+        // Segments can skip levels and match partially
+        let found = find_section_by_path(&headings, "guide/examples").unwrap();
+        assert_eq!(found.line_number, 5);
+
+        let found = find_section_by_path(&headings, "Usage/I\\/O").unwrap();
+        assert_eq!(found.line_number, 11);
+
+        assert!(find_section_by_path(&headings, "Install/I\\/O").is_none());
+        assert!(find_section_by_path(&headings, "Guide//Examples").is_none());
+    }
+
+    #[test]
+    fn test_default_heading() {
+        let heading = Heading::default();
+        assert_eq!(heading.level, 1);
+        assert_eq!(format_outline_entry(&heading), "   0:");
+
+        let level_zero = Heading {
+            level: 0,
+            ..Heading::default()
+        };
+        assert_eq!(format_outline_entry(&level_zero), "   0:");
+    }
+
+    #[test]
+    fn test_heading_path() {
+        let content = "# Guide\n\n## Install\n\n#### Linux\n\n## Usage\n";
+        let headings = parse_headings(content);
+
+        let path: Vec<_> = heading_path(&headings, &headings[2])
+            .iter()
+            .map(|h| h.text.as_str())
+            .collect();
+        assert_eq!(path, ["Guide", "Install", "Linux"]);
+        assert_eq!(heading_path(&headings, &headings[3]).len(), 2);
+    }
+
+    #[test]
+    fn test_get_section_byte_range() {
+        let content = "# Guide\n\n## Install\n\nsteps\n\n## Usage\n\nrun";
+        let headings = parse_headings(content);
+
+        let range = get_section_byte_range(content, &headings, &headings[1]);
+        assert_eq!(range.start, headings[1].byte_offset);
+        assert_eq!(&content[range], "## Install\n\nsteps\n\n");
+        assert_eq!(
+            get_section_byte_range(content, &headings, &headings[0]),
+            0..content.len()
+        );
+    }
+
+    #[test]
+    fn test_line_starts() {
+        assert_eq!(line_starts(""), Vec::<usize>::new());
+        assert_eq!(line_starts("a"), [0]);
+        assert_eq!(line_starts("a\n"), [0]);
+        assert_eq!(line_starts("a\r\n\nb"), [0, 3, 4]);
+    }
+
+    #[test]
+    fn test_extract_sections() {
+        let content = "# Guide\r\n\r\n## Install\r\n\r\nsteps\r\n\r\n## Usage\r\n\r\nrun";
+        let headings = parse_headings(content);
+        let selected = [&headings[2], &headings[0], &headings[1]];
+
+        let sections = extract_sections(content, &headings, &selected);
+        for (heading, section) in selected.iter().zip(&sections) {
+            let (start, end) = get_section_range(&headings, heading);
+            assert_eq!(section, &extract_section(content, start, end));
+        }
+        assert_eq!(sections[0], "## Usage\r\n\r\nrun");
+        assert!(extract_sections(content, &headings, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_extract_section_body() {
+        let content =
+            "# Guide\n\n## Install\n\n  \nRun it.\n\n### Linux\n\napt\n\nSetext\n------\nbody\n";
+        let headings = parse_headings(content);
+
+        assert_eq!(
+            extract_section_body(content, &headings, &headings[1]),
+            "Run it.\n\n### Linux\n\napt\n\n"
+        );
+        assert_eq!(
+            extract_section_body(content, &headings, &headings[3]),
+            "body\n"
+        );
+
+        // The first section starts at the byte order mark, before its heading
+        let bom = "\u{feff}# Title\n\nbody text\n";
+        let headings = parse_headings(bom);
+        assert_eq!(
+            extract_section_body(bom, &headings, &headings[0]),
+            "body text\n"
+        );
+        assert_eq!(
+            Document::new(bom.to_string()).extract_section_body(&headings[0]),
+            "body text\n"
+        );
+
+        // A heading without body yields nothing
+        let headings = parse_headings("## Empty\n## Next\n");
+        assert_eq!(
+            extract_section_body("## Empty\n## Next\n", &headings, &headings[0]),
+            ""
+        );
+    }
+
+    #[test]
+    fn test_prune_outline() {
+        let content =
+            "## Intro\n\n# Guide\n\n## Config\n\n### Keys\n\n#### Nested\n\n### Env\n\n## Usage\n";
+        let headings = parse_headings(content);
+
+        let pruned: Vec<_> = prune_outline(&headings, 2)
+            .into_iter()
+            .map(|(h, hidden)| (h.text.as_str(), hidden))
+            .collect();
+        assert_eq!(
+            pruned,
+            [("Intro", 0), ("Guide", 0), ("Config", 3), ("Usage", 0)]
+        );
+
+        let top = prune_outline(&headings, 1);
+        assert_eq!(top.len(), 1);
+        assert_eq!(top[0].1, 5);
+    }
+
+    #[test]
+    fn test_heading_navigation() {
+        let content =
+            "# Guide\n\n## Install\n\n#### Linux\n\n### Windows\n\n## Usage\n\n# Appendix\n";
+        let headings = parse_headings(content);
+        let texts = |found: Vec<&Heading>| -> Vec<String> {
+            found.into_iter().map(|h| h.text.clone()).collect()
+        };
+
+        assert_eq!(parent_of(&headings, &headings[2]).unwrap().text, "Install");
+        assert!(parent_of(&headings, &headings[0]).is_none());
+        assert_eq!(
+            texts(children_of(&headings, &headings[0])),
+            ["Install", "Usage"]
+        );
+        assert_eq!(
+            texts(children_of(&headings, &headings[1])),
+            ["Linux", "Windows"]
+        );
+
+        // Siblings share a parent even when their levels differ
+        assert_eq!(
+            next_sibling(&headings, &headings[2]).unwrap().text,
+            "Windows"
+        );
+        assert_eq!(prev_sibling(&headings, &headings[3]).unwrap().text, "Linux");
+        assert_eq!(
+            next_sibling(&headings, &headings[0]).unwrap().text,
+            "Appendix"
+        );
+        assert!(next_sibling(&headings, &headings[4]).is_none());
+        assert!(prev_sibling(&headings, &headings[1]).is_none());
+    }
+
+    #[test]
+    fn test_rank_sections() {
+        let content = "# Install\n\n## Configuration\n\n## Config files\n\n## Contributing\n";
+        let headings = parse_headings(content);
+
+        let ranked: Vec<_> = rank_sections(&headings, "config")
+            .iter()
+            .map(|h| h.text.as_str())
+            .collect();
+        assert_eq!(ranked, ["Configuration", "Config files"]);
+
+        // Subsequence matches come last
+        let ranked: Vec<_> = rank_sections(&headings, "ctb")
+            .iter()
+            .map(|h| h.text.as_str())
+            .collect();
+        assert_eq!(ranked, ["Contributing"]);
+
+        assert_eq!(rank_sections(&headings, "").len(), 4);
+    }
+
     #[test]
     fn test_section_range() {
         let headings = vec![
@@ -308,16 +1214,19 @@ mod tests {
                 line_number: 1,
                 level: 1,
                 text: "Title".into(),
+                ..Default::default()
             },
             Heading {
                 line_number: 5,
                 level: 2,
                 text: "Section 1".into(),
+                ..Default::default()
             },
             Heading {
                 line_number: 10,
                 level: 2,
                 text: "Section 2".into(),
+                ..Default::default()
             },
         ];
 
@@ -330,6 +1239,30 @@ mod tests {
         assert_eq!(end, None);
     }
 
+    #[test]
+    fn test_section_last_content_line() {
+        let content = "# Title\n\n## First\n\nIntro\n\n## Empty\n\n## Last\n\nFinal text\n\n\n";
+        let headings = parse_headings(content);
+
+        // Trailing blanks before the next heading are skipped
+        assert_eq!(
+            section_last_content_line(content, &headings, &headings[1]),
+            5
+        );
+
+        // A section with no body returns its own heading line
+        assert_eq!(
+            section_last_content_line(content, &headings, &headings[2]),
+            7
+        );
+
+        // A section ending the document ignores the trailing blank lines
+        assert_eq!(
+            section_last_content_line(content, &headings, &headings[3]),
+            11
+        );
+    }
+
     #[test]
     fn test_skip_code_blocks() {
         let content = r#"# Title
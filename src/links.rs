@@ -0,0 +1,438 @@
+//! Link and image extraction
+
+use crate::inline::matching_bracket;
+use crate::scan::CodeBlockTracker;
+use crate::{Heading, heading_anchors, parse_headings};
+use std::collections::HashMap;
+
+/// How a link is written in the source
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LinkKind {
+    /// `[text](destination "title")`
+    Inline,
+    /// `[text][label]`, `[label][]` or `[label]` with a matching definition
+    Reference,
+    /// `<https://example.com>` or `<user@example.com>`
+    Autolink,
+}
+
+/// A link found in a document
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Link {
+    /// Line number of the link (1-indexed)
+    pub line_number: usize,
+    /// How the link is written
+    pub kind: LinkKind,
+    /// Link text as written (for autolinks, the address itself)
+    pub text: String,
+    /// Destination URL or path; for reference links, taken from the definition
+    pub destination: String,
+    /// Optional link title
+    pub title: Option<String>,
+}
+
+/// An image found in a document
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Image {
+    /// Line number of the image (1-indexed)
+    pub line_number: usize,
+    /// Alt text as written
+    pub alt: String,
+    /// Image path or URL; for reference images, taken from the definition
+    pub source: String,
+    /// Optional image title
+    pub title: Option<String>,
+}
+
+/// Whether a link destination points outside the document's files (has a URL scheme)
+pub(crate) fn is_external(destination: &str) -> bool {
+    destination.contains("://") || destination.starts_with("mailto:")
+}
+
+impl Link {
+    /// Whether the link points to a URL rather than a file or anchor in the project
+    ///
+    /// Autolinks always count as external, including bare email addresses.
+    pub fn is_external(&self) -> bool {
+        self.kind == LinkKind::Autolink || is_external(&self.destination)
+    }
+}
+
+/// Where an inline link or image points to, before resolving references
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Target {
+    Inline {
+        destination: String,
+        title: Option<String>,
+    },
+    Reference(String),
+    Autolink(String),
+}
+
+/// A link or image found by [`scan_inline_links`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct InlineLink {
+    pub(crate) image: bool,
+    pub(crate) text: String,
+    pub(crate) target: Target,
+}
+
+/// A link reference definition (`[label]: destination "title"`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Definition {
+    pub(crate) destination: String,
+    pub(crate) title: Option<String>,
+}
+
+/// Normalize a reference label for case-insensitive matching
+fn normalize_label(label: &str) -> String {
+    label
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Parse `destination "title"` as found inside `(...)` or after a definition label
+fn parse_destination_and_title(text: &str) -> Option<(String, Option<String>)> {
+    let text = text.trim();
+    let (destination, rest) = if let Some(inner) = text.strip_prefix('<') {
+        let end = inner.find('>')?;
+        (&inner[..end], &inner[end + 1..])
+    } else {
+        let end = text.find(char::is_whitespace).unwrap_or(text.len());
+        (&text[..end], &text[end..])
+    };
+
+    let rest = rest.trim();
+    let title = match rest.chars().next() {
+        None => None,
+        Some(open @ ('"' | '\'' | '(')) => {
+            let close = if open == '(' { ')' } else { open };
+            Some(rest.strip_prefix(open)?.strip_suffix(close)?.to_string())
+        }
+        Some(_) => return None,
+    };
+
+    Some((destination.to_string(), title))
+}
+
+/// Parse a link reference definition line, returning its normalized label
+fn parse_definition(line: &str) -> Option<(String, Definition)> {
+    if line.len() - line.trim_start().len() > 3 {
+        return None;
+    }
+
+    let rest = line.trim_start().strip_prefix('[')?;
+    let end = rest.find("]:")?;
+    let label = &rest[..end];
+    if label.trim().is_empty() || label.starts_with('^') {
+        return None;
+    }
+
+    let (destination, title) = parse_destination_and_title(&rest[end + 2..])?;
+    if destination.is_empty() {
+        return None;
+    }
+
+    Some((normalize_label(label), Definition { destination, title }))
+}
+
+/// Collect all link reference definitions outside code blocks
+pub(crate) fn parse_definitions(content: &str) -> HashMap<String, Definition> {
+    let mut code = CodeBlockTracker::default();
+    let mut definitions = HashMap::new();
+
+    for line in content.lines() {
+        if code.is_code(line) {
+            continue;
+        }
+        if let Some((label, definition)) = parse_definition(line) {
+            // The first definition of a label wins
+            definitions.entry(label).or_insert(definition);
+        }
+    }
+
+    definitions
+}
+
+/// Index just past the code span whose opening backticks start at `start`
+///
+/// An unclosed run of backticks is literal text, so only the run is skipped.
+pub(crate) fn skip_code_span(chars: &[char], start: usize) -> usize {
+    let run = chars[start..].iter().take_while(|&&c| c == '`').count();
+    let close = (start + run..chars.len()).find(|&j| {
+        chars[j..].iter().take_while(|&&c| c == '`').count() == run && chars[j - 1] != '`'
+    });
+    close.map_or(start + run, |end| end + run)
+}
+
+/// Find links and images on a single line, skipping code spans
+pub(crate) fn scan_inline_links(line: &str) -> Vec<InlineLink> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut found = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 2,
+            '`' => i = skip_code_span(&chars, i),
+            '<' => {
+                let end = chars[i..].iter().position(|&c| c == '>').map(|p| i + p);
+                let inner: Option<String> = end.map(|end| chars[i + 1..end].iter().collect());
+                match inner {
+                    Some(inner)
+                        if !inner.is_empty()
+                            && !inner.contains(char::is_whitespace)
+                            && (inner.contains("://")
+                                || inner.starts_with("mailto:")
+                                || inner.contains('@')) =>
+                    {
+                        i += inner.chars().count() + 2;
+                        found.push(InlineLink {
+                            image: false,
+                            text: inner.clone(),
+                            target: Target::Autolink(inner),
+                        });
+                    }
+                    _ => i += 1,
+                }
+            }
+            '[' => {
+                let image = i > 0 && chars[i - 1] == '!' && (i < 2 || chars[i - 2] != '\\');
+                let Some(close) = matching_bracket(&chars, i, '[', ']') else {
+                    i += 1;
+                    continue;
+                };
+                let text: String = chars[i + 1..close].iter().collect();
+
+                match chars.get(close + 1) {
+                    Some('(') => {
+                        if let Some(end) = matching_bracket(&chars, close + 1, '(', ')') {
+                            let inner: String = chars[close + 2..end].iter().collect();
+                            if let Some((destination, title)) = parse_destination_and_title(&inner)
+                            {
+                                found.push(InlineLink {
+                                    image,
+                                    text,
+                                    target: Target::Inline { destination, title },
+                                });
+                                i = end + 1;
+                                continue;
+                            }
+                        }
+                    }
+                    Some('[') => {
+                        if let Some(end) = matching_bracket(&chars, close + 1, '[', ']') {
+                            let label: String = chars[close + 2..end].iter().collect();
+                            let label = if label.trim().is_empty() {
+                                text.clone()
+                            } else {
+                                label
+                            };
+                            found.push(InlineLink {
+                                image,
+                                text,
+                                target: Target::Reference(normalize_label(&label)),
+                            });
+                            i = end + 1;
+                            continue;
+                        }
+                    }
+                    // `[label]:` starts a definition, not a link
+                    Some(':') => {}
+                    _ => {
+                        found.push(InlineLink {
+                            image,
+                            target: Target::Reference(normalize_label(&text)),
+                            text,
+                        });
+                        i = close + 1;
+                        continue;
+                    }
+                }
+                i += 1;
+            }
+            _ => i += 1,
+        }
+    }
+
+    found
+}
+
+/// Walk all inline links and images outside code blocks, resolving references
+///
+/// Reference-style items whose label has no definition are not links and are
+/// skipped.
+pub(crate) fn resolved_inline_links(
+    content: &str,
+) -> impl Iterator<Item = (usize, InlineLink, LinkKind, Definition)> {
+    let definitions = parse_definitions(content);
+    let mut code = CodeBlockTracker::default();
+    let mut resolved = Vec::new();
+
+    for (idx, line) in content.lines().enumerate() {
+        if code.is_code(line) || parse_definition(line).is_some() {
+            continue;
+        }
+
+        for item in scan_inline_links(line) {
+            let (kind, definition) = match &item.target {
+                Target::Inline { destination, title } => (
+                    LinkKind::Inline,
+                    Definition {
+                        destination: destination.clone(),
+                        title: title.clone(),
+                    },
+                ),
+                Target::Autolink(address) => (
+                    LinkKind::Autolink,
+                    Definition {
+                        destination: address.clone(),
+                        title: None,
+                    },
+                ),
+                Target::Reference(label) => match definitions.get(label) {
+                    Some(definition) => (LinkKind::Reference, definition.clone()),
+                    None => continue,
+                },
+            };
+            resolved.push((idx + 1, item, kind, definition));
+        }
+    }
+
+    resolved.into_iter()
+}
+
+/// Extract all links from markdown content
+///
+/// Finds inline links, reference links (full, collapsed and shortcut forms, with
+/// destinations resolved from their definitions) and autolinks, in document
+/// order. Links inside code blocks and code spans are ignored, as are images.
+pub fn parse_links(content: &str) -> Vec<Link> {
+    resolved_inline_links(content)
+        .filter(|(_, item, _, _)| !item.image)
+        .map(|(line_number, item, kind, definition)| Link {
+            line_number,
+            kind,
+            text: item.text,
+            destination: definition.destination,
+            title: definition.title,
+        })
+        .collect()
+}
+
+/// Extract all images from markdown content
+///
+/// Finds inline (`![alt](src)`) and reference-style images in document order,
+/// skipping code blocks and code spans. Combine the line numbers with
+/// [`get_section_range`](crate::get_section_range) to group images by section.
+pub fn parse_images(content: &str) -> Vec<Image> {
+    resolved_inline_links(content)
+        .filter(|(_, item, _, _)| item.image)
+        .map(|(line_number, item, _, definition)| Image {
+            line_number,
+            alt: item.text,
+            source: definition.destination,
+            title: definition.title,
+        })
+        .collect()
+}
+
+/// Find in-page links (`[text](#fragment)`) that point to no heading
+///
+/// Fragments are checked against [`heading_anchors`], which covers both
+/// generated slugs and explicit `{#id}` attributes. Returns the broken links in
+/// document order.
+pub fn validate_anchors(content: &str) -> Vec<Link> {
+    validate_anchors_with(content, &parse_headings(content))
+}
+
+/// Find in-page links that point to none of `headings`
+///
+/// Same as [`validate_anchors`], for headings parsed with other options
+/// (see [`parse_headings_with`](crate::parse_headings_with)).
+pub fn validate_anchors_with(content: &str, headings: &[Heading]) -> Vec<Link> {
+    let anchors = heading_anchors(headings);
+
+    parse_links(content)
+        .into_iter()
+        .filter(|link| {
+            link.destination
+                .strip_prefix('#')
+                .is_some_and(|fragment| !anchors.iter().any(|a| a == fragment))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_links() {
+        let content = r#"# Links
+
+See [the guide](docs/guide.md "Guide") and [API][api] or [Api][].
+Visit <https://example.com> or mail <team@example.com>.
+Not a link: `[code](x.md)` and [undefined][nope], ![logo](logo.png).
+
+```
+[fenced](ignored.md)
+```
+
+[api]: https://example.com/api 'API docs'
+"#;
+        let links = parse_links(content);
+
+        assert_eq!(links.len(), 5);
+        assert_eq!(links[0].kind, LinkKind::Inline);
+        assert_eq!(links[0].text, "the guide");
+        assert_eq!(links[0].destination, "docs/guide.md");
+        assert_eq!(links[0].title.as_deref(), Some("Guide"));
+        assert_eq!(links[0].line_number, 3);
+
+        assert_eq!(links[1].kind, LinkKind::Reference);
+        assert_eq!(links[1].destination, "https://example.com/api");
+        assert_eq!(links[1].title.as_deref(), Some("API docs"));
+        assert!(links[1].is_external() && !links[0].is_external());
+        assert_eq!(links[2].text, "Api");
+        assert_eq!(links[2].destination, "https://example.com/api");
+
+        assert_eq!(links[3].kind, LinkKind::Autolink);
+        assert_eq!(links[3].destination, "https://example.com");
+        assert_eq!(links[4].destination, "team@example.com");
+    }
+
+    #[test]
+    fn test_parse_images() {
+        let content = "# Assets\n\n![Logo](img/logo.png \"The logo\") and [a link](x.md)\n\n\
+                       ## Diagrams\n\n![arch][diagram] `![not](code.png)`\n\n\
+                       [diagram]: img/arch.svg\n";
+        let images = parse_images(content);
+
+        assert_eq!(images.len(), 2);
+        assert_eq!(images[0].alt, "Logo");
+        assert_eq!(images[0].source, "img/logo.png");
+        assert_eq!(images[0].title.as_deref(), Some("The logo"));
+        assert_eq!(images[0].line_number, 3);
+        assert_eq!(images[1].alt, "arch");
+        assert_eq!(images[1].source, "img/arch.svg");
+        assert_eq!(images[1].line_number, 7);
+    }
+
+    #[test]
+    fn test_validate_anchors() {
+        let content = "# Guide\n\n- [Setup](#setup)\n- [Usage](#usage)\n- [FAQ][faq]\n- [Web](https://example.com)\n\n## Setup\n\n## Usage again {#use}\n\n[faq]: #faq\n";
+        let broken = validate_anchors(content);
+
+        let lines: Vec<_> = broken
+            .iter()
+            .map(|l| (l.line_number, l.destination.as_str()))
+            .collect();
+        assert_eq!(lines, [(4, "#usage"), (5, "#faq")]);
+    }
+}
@@ -0,0 +1,234 @@
+//! Structural checks for a document's heading hierarchy
+
+use crate::{Heading, find_duplicate_headings, section_last_content_line, validate_anchors_with};
+use std::fmt;
+
+/// The kind of structural problem found by [`lint_structure`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LintKind {
+    /// A heading is more than one level deeper than the heading before it
+    /// (e.g. an H3 directly below an H1)
+    LevelSkip { from: u8, to: u8 },
+    /// A second or later level 1 heading
+    MultipleH1,
+    /// A section with neither body text nor subsections
+    EmptySection,
+    /// A heading with the same anchor as an earlier one, which links can't reach
+    DuplicateHeading { first_line: usize },
+    /// An in-page link (`#fragment`) that matches no heading
+    BrokenAnchor { fragment: String },
+}
+
+impl fmt::Display for LintKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LintKind::LevelSkip { from, to } => {
+                write!(f, "heading level skips from H{} to H{}", from, to)
+            }
+            LintKind::MultipleH1 => write!(f, "document has more than one H1"),
+            LintKind::EmptySection => write!(f, "section is empty"),
+            LintKind::DuplicateHeading { first_line } => {
+                write!(f, "heading duplicates the anchor of line {}", first_line)
+            }
+            LintKind::BrokenAnchor { fragment } => {
+                write!(f, "link to #{} matches no heading", fragment)
+            }
+        }
+    }
+}
+
+/// A structural problem at a heading (or, for broken anchors, at a link)
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LintFinding {
+    /// Line number of the offending heading or link (1-indexed)
+    pub line_number: usize,
+    /// What is wrong
+    pub kind: LintKind,
+}
+
+impl fmt::Display for LintFinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line_number, self.kind)
+    }
+}
+
+/// Check the heading hierarchy for structural problems
+///
+/// Reports level skips, every H1 after the first, and sections that contain
+/// neither text nor subsections. A section whose first child is deeper than
+/// `level + 1` is reported as a level skip at that child. Findings are in
+/// document order.
+pub fn lint_structure(content: &str, headings: &[Heading]) -> Vec<LintFinding> {
+    let mut findings = Vec::new();
+    let mut seen_h1 = false;
+
+    for (idx, h) in headings.iter().enumerate() {
+        if let Some(prev) = idx.checked_sub(1).map(|i| &headings[i])
+            && h.level > prev.level + 1
+        {
+            findings.push(LintFinding {
+                line_number: h.line_number,
+                kind: LintKind::LevelSkip {
+                    from: prev.level,
+                    to: h.level,
+                },
+            });
+        }
+
+        if h.level == 1 {
+            if seen_h1 {
+                findings.push(LintFinding {
+                    line_number: h.line_number,
+                    kind: LintKind::MultipleH1,
+                });
+            }
+            seen_h1 = true;
+        }
+
+        let has_children = headings
+            .get(idx + 1)
+            .is_some_and(|next| next.level > h.level);
+        let source_lines = content
+            .get(h.byte_range())
+            .map_or(1, |source| source.lines().count().max(1));
+        let has_text =
+            section_last_content_line(content, headings, h) >= h.line_number + source_lines;
+        if !has_children && !has_text {
+            findings.push(LintFinding {
+                line_number: h.line_number,
+                kind: LintKind::EmptySection,
+            });
+        }
+    }
+
+    findings
+}
+
+/// Run every document check: [`lint_structure`], duplicate headings and broken
+/// in-page anchors
+///
+/// Each heading that repeats an earlier heading's anchor is reported (see
+/// [`find_duplicate_headings`]), as is every link to a `#fragment` that matches
+/// none of `headings` (see [`validate_anchors_with`]). Findings are sorted by
+/// line.
+pub fn lint_document(content: &str, headings: &[Heading]) -> Vec<LintFinding> {
+    let mut findings = lint_structure(content, headings);
+
+    for group in find_duplicate_headings(headings) {
+        let first_line = group.line_numbers[0];
+        findings.extend(
+            group.line_numbers[1..]
+                .iter()
+                .map(|&line_number| LintFinding {
+                    line_number,
+                    kind: LintKind::DuplicateHeading { first_line },
+                }),
+        );
+    }
+    findings.extend(
+        validate_anchors_with(content, headings)
+            .into_iter()
+            .map(|link| LintFinding {
+                line_number: link.line_number,
+                kind: LintKind::BrokenAnchor {
+                    fragment: link.destination.trim_start_matches('#').to_string(),
+                },
+            }),
+    );
+
+    findings.sort_by_key(|f| f.line_number);
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_headings;
+
+    #[test]
+    fn test_lint_structure() {
+        let content = "# Title\n\nIntro\n\n### Skipped\n\ntext\n\n## Empty\n\n# Second\n\nSetext\n------\n\nbody\n";
+        let headings = parse_headings(content);
+        let findings = lint_structure(content, &headings);
+
+        assert_eq!(
+            findings,
+            [
+                LintFinding {
+                    line_number: 5,
+                    kind: LintKind::LevelSkip { from: 1, to: 3 },
+                },
+                LintFinding {
+                    line_number: 9,
+                    kind: LintKind::EmptySection,
+                },
+                LintFinding {
+                    line_number: 11,
+                    kind: LintKind::MultipleH1,
+                },
+            ]
+        );
+        assert_eq!(
+            findings[0].to_string(),
+            "line 5: heading level skips from H1 to H3"
+        );
+    }
+
+    #[test]
+    fn test_lint_document() {
+        let content =
+            "# Title\n\nSee [setup](#setup) and [gone](#gone).\n\n## Setup\n\nx\n\n## Setup\n\ny\n";
+        let headings = parse_headings(content);
+        let findings = lint_document(content, &headings);
+
+        assert_eq!(
+            findings,
+            [
+                LintFinding {
+                    line_number: 3,
+                    kind: LintKind::BrokenAnchor {
+                        fragment: "gone".to_string()
+                    },
+                },
+                LintFinding {
+                    line_number: 9,
+                    kind: LintKind::DuplicateHeading { first_line: 5 },
+                },
+            ]
+        );
+        assert_eq!(
+            findings[1].to_string(),
+            "line 9: heading duplicates the anchor of line 5"
+        );
+    }
+
+    #[test]
+    fn test_lint_document_uses_given_headings() {
+        let broken = |content: &str, options: crate::ParseOptions| -> Vec<String> {
+            let headings = crate::parse_headings_with(content, &options);
+            lint_document(content, &headings)
+                .into_iter()
+                .filter_map(|f| match f.kind {
+                    LintKind::BrokenAnchor { fragment } => Some(fragment),
+                    _ => None,
+                })
+                .collect()
+        };
+
+        let html = "<h2 id=\"install\">Install</h2>\n\n[a](#install)\n";
+        let options = crate::ParseOptions {
+            html_headings: true,
+            ..Default::default()
+        };
+        assert!(broken(html, options).is_empty());
+
+        let mdx = "# Guide\n\n<Note>\n## Hidden\n</Note>\n\n[b](#hidden)\n";
+        let options = crate::ParseOptions {
+            mdx: true,
+            ..Default::default()
+        };
+        assert_eq!(broken(mdx, options), ["hidden"]);
+    }
+}
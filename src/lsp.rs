@@ -0,0 +1,402 @@
+//! Language server for `mdi lsp`
+//!
+//! Speaks JSON-RPC over stdio with full document sync and answers document
+//! symbol (the outline), folding range (sections and code blocks) and definition
+//! (jump from a `#heading` link to the heading) requests.
+
+use crate::parse_input;
+use anyhow::{Context, Result};
+use markdown_inspector::{
+    Heading, Section, SectionTree, heading_anchors, parse_code_blocks, parse_links, percent_decode,
+    section_last_content_line,
+};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+/// LSP's `SymbolKind.String`, which markdown servers use for headings
+const SYMBOL_KIND_STRING: u8 = 15;
+
+/// JSON-RPC's "parse error" code
+const PARSE_ERROR: i64 = -32700;
+
+/// JSON-RPC's "method not found" error code
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// Read the body of one `Content-Length` framed message, or `None` at end of input
+fn read_message(input: &mut impl BufRead) -> Result<Option<Vec<u8>>> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':')
+            && name.eq_ignore_ascii_case("content-length")
+        {
+            length = Some(value.trim().parse::<usize>()?);
+        }
+    }
+
+    let mut body = vec![0; length.context("Message without Content-Length")?];
+    input.read_exact(&mut body)?;
+    Ok(Some(body))
+}
+
+fn write_message(output: &mut impl Write, message: &Value) -> Result<()> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()?;
+    Ok(())
+}
+
+/// Length of a line in UTF-16 code units, the unit of LSP positions
+fn utf16_len(text: &str) -> usize {
+    text.encode_utf16().count()
+}
+
+/// Range from the start of line `first` to the end of line `last` (1-indexed)
+fn line_range(lines: &[&str], first: usize, last: usize) -> Value {
+    let end = lines.get(last - 1).map_or(0, |line| utf16_len(line));
+    json!({
+        "start": { "line": first - 1, "character": 0 },
+        "end": { "line": last - 1, "character": end },
+    })
+}
+
+fn document_symbol(
+    section: Section<'_>,
+    content: &str,
+    headings: &[Heading],
+    lines: &[&str],
+) -> Value {
+    let heading = section.heading();
+    let last = section_last_content_line(content, headings, heading);
+    let children: Vec<Value> = section
+        .children()
+        .map(|child| document_symbol(child, content, headings, lines))
+        .collect();
+    json!({
+        "name": heading.text,
+        "detail": "#".repeat(heading.level as usize),
+        "kind": SYMBOL_KIND_STRING,
+        "range": line_range(lines, heading.line_number, last),
+        "selectionRange": line_range(lines, heading.line_number, heading.line_number),
+        "children": children,
+    })
+}
+
+fn document_symbols(content: &str, headings: &[Heading]) -> Value {
+    let lines: Vec<&str> = content.lines().collect();
+    let tree = SectionTree::from_headings(headings);
+    tree.roots()
+        .map(|root| document_symbol(root, content, headings, &lines))
+        .collect()
+}
+
+fn folding_ranges(content: &str, headings: &[Heading]) -> Value {
+    let sections = headings.iter().filter_map(|h| {
+        let last = section_last_content_line(content, headings, h);
+        (last > h.line_number).then_some((h.line_number, last))
+    });
+    let code_blocks = parse_code_blocks(content)
+        .into_iter()
+        .filter(|block| block.end_line > block.start_line)
+        .map(|block| (block.start_line, block.end_line));
+
+    sections
+        .chain(code_blocks)
+        .map(|(start, end)| json!({ "startLine": start - 1, "endLine": end - 1 }))
+        .collect()
+}
+
+/// Resolve a relative path against the directory of a `file://` URI
+fn join_uri(base: &str, path: &str) -> String {
+    let (dir, _) = base.rsplit_once('/').unwrap_or((base, ""));
+    let mut segments: Vec<&str> = dir.split('/').collect();
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            _ => segments.push(segment),
+        }
+    }
+    segments.join("/")
+}
+
+#[derive(Default)]
+struct Server {
+    /// Open documents by URI
+    documents: HashMap<String, String>,
+}
+
+impl Server {
+    /// The text of a document, from the editor if it's open, otherwise from disk
+    fn text(&self, uri: &str) -> Option<String> {
+        self.documents.get(uri).cloned().or_else(|| {
+            let path = percent_decode(uri.strip_prefix("file://")?);
+            fs::read_to_string(path).ok()
+        })
+    }
+
+    fn notify(&mut self, method: &str, params: &Value) {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+        match method {
+            "textDocument/didOpen" => {
+                let text = params["textDocument"]["text"].as_str().unwrap_or_default();
+                self.documents.insert(uri.to_string(), text.to_string());
+            }
+            "textDocument/didChange" => {
+                // Full sync: the last change holds the whole document
+                if let Some(text) = params["contentChanges"]
+                    .as_array()
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change["text"].as_str())
+                {
+                    self.documents.insert(uri.to_string(), text.to_string());
+                }
+            }
+            "textDocument/didClose" => {
+                self.documents.remove(uri);
+            }
+            _ => {}
+        }
+    }
+
+    /// Find the heading a link under the cursor points to
+    fn definition(&self, uri: &str, position: &Value) -> Option<Value> {
+        let content = self.documents.get(uri)?;
+        let line_idx = position["line"].as_u64()? as usize;
+        let character = position["character"].as_u64()? as usize;
+        let line = content.lines().nth(line_idx)?;
+
+        // Byte offset of the cursor, converted from UTF-16 units
+        let mut units = 0;
+        let cursor = line
+            .char_indices()
+            .find(|(_, c)| {
+                units += c.len_utf16();
+                units > character
+            })
+            .map_or(line.len(), |(idx, _)| idx);
+
+        // Links don't carry columns, so pick the one written closest to the cursor
+        let link = parse_links(content)
+            .into_iter()
+            .filter(|link| link.line_number == line_idx + 1)
+            .filter_map(|link| {
+                let end = line.find(&link.destination)? + link.destination.len();
+                let start = line.find(&link.text).unwrap_or(end).min(end);
+                let distance = start.saturating_sub(cursor) + cursor.saturating_sub(end);
+                Some((distance, link))
+            })
+            .min_by_key(|(distance, _)| *distance)?
+            .1;
+        if link.is_external() {
+            return None;
+        }
+
+        let (path, fragment) = link
+            .destination
+            .split_once('#')
+            .unwrap_or((&link.destination, ""));
+        let target_uri = if path.is_empty() {
+            uri.to_string()
+        } else {
+            join_uri(uri, path)
+        };
+        let target = self.text(&target_uri)?;
+
+        let line_number = if fragment.is_empty() {
+            1
+        } else {
+            let headings = parse_input(Path::new(&target_uri), &target);
+            let anchors = heading_anchors(&headings);
+            let idx = anchors.iter().position(|anchor| anchor == fragment)?;
+            headings[idx].line_number
+        };
+        let lines: Vec<&str> = target.lines().collect();
+        Some(json!({
+            "uri": target_uri,
+            "range": line_range(&lines, line_number, line_number),
+        }))
+    }
+
+    fn request(&self, method: &str, params: &Value) -> Result<Value, (i64, String)> {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+        let document = || {
+            let content = self.documents.get(uri).map_or("", String::as_str);
+            (content, parse_input(Path::new(uri), content))
+        };
+
+        match method {
+            "initialize" => Ok(json!({
+                "capabilities": {
+                    "textDocumentSync": 1,
+                    "documentSymbolProvider": true,
+                    "foldingRangeProvider": true,
+                    "definitionProvider": true,
+                },
+                "serverInfo": { "name": "mdi", "version": env!("CARGO_PKG_VERSION") },
+            })),
+            "shutdown" => Ok(Value::Null),
+            "textDocument/documentSymbol" => {
+                let (content, headings) = document();
+                Ok(document_symbols(content, &headings))
+            }
+            "textDocument/foldingRange" => {
+                let (content, headings) = document();
+                Ok(folding_ranges(content, &headings))
+            }
+            "textDocument/definition" => Ok(self
+                .definition(uri, &params["position"])
+                .unwrap_or(Value::Null)),
+            _ => Err((METHOD_NOT_FOUND, format!("Unsupported method: {}", method))),
+        }
+    }
+}
+
+/// Serve LSP requests on stdin/stdout until the client sends `exit`
+pub fn run() -> Result<()> {
+    serve(&mut io::stdin().lock(), &mut io::stdout().lock())
+}
+
+fn serve(input: &mut impl BufRead, output: &mut impl Write) -> Result<()> {
+    let mut server = Server::default();
+
+    while let Some(body) = read_message(input)? {
+        let message: Value = match serde_json::from_slice(&body) {
+            Ok(message) => message,
+            Err(err) => {
+                let error = json!({
+                    "jsonrpc": "2.0",
+                    "id": null,
+                    "error": { "code": PARSE_ERROR, "message": err.to_string() },
+                });
+                write_message(output, &error)?;
+                continue;
+            }
+        };
+
+        // Messages without a method are responses to requests we never send
+        let Some(method) = message["method"].as_str() else {
+            continue;
+        };
+        if method == "exit" {
+            break;
+        }
+
+        let params = &message["params"];
+        match message.get("id") {
+            Some(id) => {
+                let response = match server.request(method, params) {
+                    Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                    Err((code, text)) => json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": { "code": code, "message": text },
+                    }),
+                };
+                write_message(output, &response)?;
+            }
+            None => server.notify(method, params),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use markdown_inspector::parse_headings;
+
+    fn frame(body: &str) -> String {
+        format!("Content-Length: {}\r\n\r\n{}", body.len(), body)
+    }
+
+    #[test]
+    fn test_read_message() {
+        let input = format!("{}{}", frame(r#"{"a":1}"#), frame("[]"));
+        let mut input = input.as_bytes();
+
+        assert_eq!(read_message(&mut input).unwrap().unwrap(), br#"{"a":1}"#);
+        assert_eq!(read_message(&mut input).unwrap().unwrap(), b"[]");
+        assert!(read_message(&mut input).unwrap().is_none());
+
+        // Header names are case-insensitive and other headers are skipped
+        let mut input = "content-type: x\r\ncontent-length: 2\r\n\r\n{}".as_bytes();
+        assert_eq!(read_message(&mut input).unwrap().unwrap(), b"{}");
+
+        let mut input = "Content-Type: x\r\n\r\n{}".as_bytes();
+        assert!(read_message(&mut input).is_err());
+    }
+
+    #[test]
+    fn test_parse_error_keeps_serving() {
+        let request = r#"{"jsonrpc":"2.0","id":1,"method":"shutdown"}"#;
+        let input = format!("{}{}", frame("{not json"), frame(request));
+        let mut output = Vec::new();
+        serve(&mut input.as_bytes(), &mut output).unwrap();
+
+        let mut output = output.as_slice();
+        let error: Value =
+            serde_json::from_slice(&read_message(&mut output).unwrap().unwrap()).unwrap();
+        assert_eq!(error["error"]["code"], PARSE_ERROR);
+        assert_eq!(error["id"], Value::Null);
+
+        let reply: Value =
+            serde_json::from_slice(&read_message(&mut output).unwrap().unwrap()).unwrap();
+        assert_eq!(reply["id"], 1);
+        assert_eq!(reply["result"], Value::Null);
+    }
+
+    #[test]
+    fn test_join_uri() {
+        let base = "file:///docs/guide/intro.md";
+        assert_eq!(join_uri(base, "setup.md"), "file:///docs/guide/setup.md");
+        assert_eq!(join_uri(base, "./a/b.md"), "file:///docs/guide/a/b.md");
+        assert_eq!(join_uri(base, "../README.md"), "file:///docs/README.md");
+    }
+
+    #[test]
+    fn test_definition_counts_utf16_units() {
+        let uri = "file:///doc.md";
+        let content = "# Intro\n\n## Setup\n\n😀😀 [setup](#setup) and [intro](#intro)\n";
+        let mut server = Server::default();
+        server
+            .documents
+            .insert(uri.to_string(), content.to_string());
+
+        // Each emoji is two UTF-16 units but four bytes, so `[intro` starts at
+        // character 25 (byte 29)
+        let target = |character| {
+            let position = json!({ "line": 4, "character": character });
+            server.definition(uri, &position).unwrap()["range"]["start"]["line"].clone()
+        };
+        assert_eq!(target(6), 2);
+        assert_eq!(target(25), 0);
+    }
+
+    #[test]
+    fn test_folding_ranges() {
+        let content = "# Title\n\ntext\n\n```\ncode\n```\n\n## Empty\n## Last\n\nend\n";
+        let headings = parse_headings(content);
+        let ranges = folding_ranges(content, &headings);
+        assert_eq!(
+            ranges,
+            json!([
+                { "startLine": 0, "endLine": 11 },
+                { "startLine": 9, "endLine": 11 },
+                { "startLine": 4, "endLine": 6 },
+            ])
+        );
+    }
+}
@@ -1,12 +1,41 @@
 use anyhow::{Context, Result};
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
+#[cfg(feature = "regex")]
+use markdown_inspector::grep_regex;
 use markdown_inspector::{
-    extract_section, extract_section_intro, extract_section_shallow, find_section,
-    format_outline_entry, get_first_subsection, get_section_range, get_subsections, parse_headings,
+    BrokenLinkReason, FrontMatter, FrontMatterFormat, Heading, InsertPosition, JoinOptions, Link,
+    MoveTarget, OutlineDiff, ParseOptions, Section, SectionStats, SectionTree, TocOptions,
+    Workspace, collect_markdown_files, delete_section, diff_outlines, extract_section,
+    extract_section_body, extract_section_intro, extract_section_shallow, find_duplicate_headings,
+    find_section, find_section_by_path, find_sections_exact, format_outline_entry, generate_toc,
+    get_first_subsection, get_section_range, get_subsections, grep_with_context, heading_anchors,
+    insert_or_update_toc, insert_section, is_markdown_file, join_documents, lint_document,
+    move_section, nested_outline, parse_code_blocks, parse_front_matter, parse_headings_with,
+    parse_links, prune_outline, replace_section, section_stats, split_document, split_index,
+    to_plain_text, unwrap_section, validate_anchors_with,
 };
+use output::{
+    AnchorEntry, BrokenEntry, FileEntry, LintEntry, OutputFormat, SectionEntry, SectionList,
+    print_structured,
+};
+#[cfg(feature = "regex")]
+use output::{HitCount, HitList};
 use std::fs;
 use std::io::{self, Read};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "http")]
+mod check;
+#[cfg(feature = "lsp")]
+mod lsp;
+#[cfg(feature = "mcp")]
+mod mcp;
+mod output;
+#[cfg(feature = "pick")]
+mod pick;
+#[cfg(feature = "serve")]
+mod serve;
+mod watch;
 
 #[derive(Parser)]
 #[command(
@@ -14,6 +43,19 @@ use std::path::PathBuf;
     about = "Markdown Inspector - explore markdown document structure"
 )]
 struct Cli {
+    /// Output format; json and yaml require the matching feature and are ignored
+    /// by commands that print markdown (toc, join, pick). `table` is accepted as
+    /// an alias for text
+    #[arg(
+        short,
+        long,
+        global = true,
+        value_enum,
+        env = "MDI_FORMAT",
+        default_value_t = OutputFormat::Text
+    )]
+    format: OutputFormat,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -22,12 +64,25 @@ struct Cli {
 enum Commands {
     /// Show document outline with line numbers
     Outline {
-        /// Markdown file to inspect (use - for stdin)
+        /// Markdown file or directory to inspect (use - for stdin)
         file: PathBuf,
 
         /// Maximum heading depth to show (1-6)
         #[arg(short, long, default_value = "6")]
         depth: u8,
+
+        /// Minimum heading depth to show (e.g. 2 to hide the document title)
+        #[arg(long, default_value = "1")]
+        min_depth: u8,
+
+        /// Don't honor .gitignore/.mdignore when outlining a directory
+        #[arg(long)]
+        #[cfg_attr(not(feature = "ignore"), arg(hide = true))]
+        no_ignore: bool,
+
+        /// Annotate each heading with the number of deeper headings hidden by --depth
+        #[arg(short, long)]
+        counts: bool,
     },
 
     /// Read a specific section
@@ -35,25 +90,462 @@ enum Commands {
         /// Markdown file to inspect (use - for stdin)
         file: PathBuf,
 
-        /// Section to read: line number or heading text (partial match)
-        section: String,
+        /// Sections to read: line number or heading text (partial match); several
+        /// are printed in document order, skipping any inside another selected
+        /// section unless --summary or --shallow leaves them out of its output
+        #[arg(required = true)]
+        sections: Vec<String>,
+
+        /// Line printed between sections when reading several
+        #[arg(long, default_value = "", allow_hyphen_values = true)]
+        delimiter: String,
 
         /// Show only the heading outline of subsections
-        #[arg(short, long)]
+        #[arg(short, long, conflicts_with_all = ["summary", "shallow", "html"])]
         outline: bool,
 
         /// Show section intro text, then subsections as outline
-        #[arg(short, long)]
+        #[arg(short, long, conflicts_with_all = ["shallow", "html"])]
         summary: bool,
 
         /// Show section content with subsections collapsed to outline entries
-        #[arg(long)]
+        #[arg(long, conflicts_with = "html")]
         shallow: bool,
 
+        /// Render the section to an HTML fragment (requires the `html` feature)
+        #[arg(long)]
+        html: bool,
+
+        /// Print the section without its heading line
+        #[arg(long, conflicts_with_all = ["outline", "summary", "shallow", "html"])]
+        body: bool,
+
+        /// Strip markdown syntax and print the section as plain text
+        #[arg(long, conflicts_with_all = ["outline", "summary", "shallow", "html"])]
+        plain: bool,
+
         /// Maximum heading depth for outline/summary mode (1-6)
         #[arg(short, long, default_value = "6")]
         depth: u8,
     },
+
+    /// Print a markdown table of contents linking to the headings
+    Toc {
+        /// Markdown file to inspect (use - for stdin)
+        file: PathBuf,
+
+        /// Shallowest heading level to include (1-6)
+        #[arg(long, default_value = "1")]
+        min_depth: u8,
+
+        /// Deepest heading level to include (1-6)
+        #[arg(long, default_value = "6")]
+        max_depth: u8,
+
+        /// List style of the entries
+        #[arg(long, value_enum, default_value_t = TocStyle::Bullet)]
+        style: TocStyle,
+
+        /// Update the TOC between `<!-- toc -->` markers in the file instead of
+        /// printing it (adding the markers after the first H1 if missing)
+        #[arg(short, long)]
+        write: bool,
+    },
+
+    /// Search body text, grouping matches by their containing section
+    Search {
+        /// Markdown file to search (use - for stdin)
+        file: PathBuf,
+
+        /// Text to find (case-insensitive)
+        pattern: String,
+
+        /// Ignore matches inside code blocks
+        #[arg(long)]
+        skip_code: bool,
+    },
+
+    /// List links with their line numbers and destinations
+    Links {
+        /// Markdown file to inspect (use - for stdin)
+        file: PathBuf,
+
+        /// Only list links in this section: line number, heading text or path
+        #[arg(short, long)]
+        section: Option<String>,
+
+        /// Only list links to files and anchors in the project
+        #[arg(long, conflicts_with = "external")]
+        internal: bool,
+
+        /// Only list links to URLs
+        #[arg(long)]
+        external: bool,
+
+        /// Only list broken links (missing files or anchors) and fail if there are any
+        #[arg(long)]
+        check: bool,
+
+        /// With --check, also request external URLs (requires the `http` feature)
+        #[arg(long, requires = "check")]
+        online: bool,
+
+        /// Timeout in seconds for each external request
+        #[arg(long, default_value = "10", requires = "online")]
+        timeout: u64,
+
+        /// Extra attempts for external requests that fail with a network error,
+        /// 429 or 5xx
+        #[arg(long, default_value = "2", requires = "online")]
+        retries: u32,
+
+        /// Number of external requests in flight at once
+        #[arg(long, default_value = "8", requires = "online")]
+        jobs: usize,
+    },
+
+    /// Print fenced code blocks
+    Code {
+        /// Markdown file to inspect (use - for stdin)
+        file: PathBuf,
+
+        /// Only blocks in this language (first word of the info string)
+        #[arg(short, long)]
+        lang: Option<String>,
+
+        /// Only blocks in this section: line number, heading text or path
+        #[arg(short, long)]
+        section: Option<String>,
+
+        /// Only the Nth matching block (starting at 1)
+        #[arg(short, long)]
+        index: Option<usize>,
+    },
+
+    /// List the anchor each heading can be linked to with
+    Anchors {
+        /// Markdown file to inspect (use - for stdin)
+        file: PathBuf,
+    },
+
+    /// Print the front matter, or the value of one key
+    Frontmatter {
+        /// Markdown file to inspect (use - for stdin)
+        file: PathBuf,
+
+        /// Key to print, with `.` for nested keys and array indices (`author.name`);
+        /// requires the `yaml` or `toml` feature for the block's format
+        key: Option<String>,
+    },
+
+    /// Show word, line, code block and link counts per section
+    Stats {
+        /// Markdown file to inspect (use - for stdin)
+        file: PathBuf,
+    },
+
+    /// Show the outline as a tree, like `tree` does for directories
+    Tree {
+        /// Markdown file to inspect (use - for stdin)
+        file: PathBuf,
+
+        /// Maximum heading depth to show (1-6)
+        #[arg(short, long, default_value = "6")]
+        depth: u8,
+
+        /// Show the number of lines in each section, including subsections
+        #[arg(short, long)]
+        lines: bool,
+    },
+
+    /// Write each section at a given level into its own file
+    Split {
+        /// Markdown file to split (use - for stdin)
+        file: PathBuf,
+
+        /// Heading level of the sections to split out
+        #[arg(short, long, default_value = "2")]
+        level: u8,
+
+        /// Directory to write the files to (created if missing)
+        #[arg(short, long, default_value = ".")]
+        out_dir: PathBuf,
+
+        /// Also write index.md with the text before the first section and links
+        /// to every file
+        #[arg(long)]
+        index: bool,
+
+        /// Overwrite files that already exist
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Concatenate documents, nesting their headings under a common title
+    Join {
+        /// Markdown files to join, in order
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+
+        /// Levels to demote every heading by
+        #[arg(long, default_value = "1")]
+        shift: u8,
+
+        /// Title for the joined document (defaults to one made from the first
+        /// file's name when --shift is non-zero)
+        #[arg(short, long)]
+        title: Option<String>,
+
+        /// Insert a table of contents below the title
+        #[arg(long)]
+        toc: bool,
+    },
+
+    /// Replace a section's content, keeping its heading, and write the file back
+    Edit {
+        /// Markdown file to edit
+        file: PathBuf,
+
+        /// Section to replace: exact heading text or a path like Guide/Install
+        #[arg(required_unless_present = "line")]
+        section: Option<String>,
+
+        /// Pick the section by the line number of its heading instead
+        #[arg(long, conflicts_with = "section")]
+        line: Option<usize>,
+
+        /// File with the new content (use - for stdin)
+        #[arg(long, default_value = "-")]
+        from: PathBuf,
+
+        /// Print the edited document instead of writing it
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Insert a new section next to or under an existing heading
+    Insert {
+        /// Markdown file to edit
+        file: PathBuf,
+
+        /// Title of the new section
+        #[arg(short, long)]
+        title: String,
+
+        /// Insert after this section, as its sibling
+        #[arg(long, group = "position")]
+        after: Option<String>,
+
+        /// Insert before this section, as its sibling
+        #[arg(long, group = "position")]
+        before: Option<String>,
+
+        /// Insert as the last subsection of this section
+        #[arg(long, group = "position")]
+        child_of: Option<String>,
+
+        /// Insert as the first subsection of this section
+        #[arg(long, group = "position")]
+        first_child_of: Option<String>,
+
+        /// Heading level of the new section (1-6, or auto to fit the position)
+        #[arg(short, long, default_value = "auto")]
+        level: String,
+
+        /// File with the section's content (use - for stdin)
+        #[arg(long)]
+        body: Option<PathBuf>,
+
+        /// Print the edited document instead of writing it
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Remove a section and its subsections, and write the file back
+    Delete {
+        /// Markdown file to edit
+        file: PathBuf,
+
+        /// Section to remove: exact heading text or a path like Guide/Install
+        #[arg(required_unless_present = "line")]
+        section: Option<String>,
+
+        /// Pick the section by the line number of its heading instead
+        #[arg(long, conflicts_with = "section")]
+        line: Option<usize>,
+
+        /// Remove only the heading and its own text, promoting the subsections
+        #[arg(long)]
+        keep_children: bool,
+
+        /// Print the edited document instead of writing it
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Move a section with its subsections to another place in the file
+    #[command(group(clap::ArgGroup::new("target").required(true)))]
+    Move {
+        /// Markdown file to edit
+        file: PathBuf,
+
+        /// Section to move: exact heading text or a path like Guide/Install
+        #[arg(required_unless_present = "line")]
+        section: Option<String>,
+
+        /// Pick the section by the line number of its heading instead
+        #[arg(long, conflicts_with = "section")]
+        line: Option<usize>,
+
+        /// Place it after this section, as its sibling
+        #[arg(long, group = "target")]
+        after: Option<String>,
+
+        /// Place it before this section, as its sibling
+        #[arg(long, group = "target")]
+        before: Option<String>,
+
+        /// Place it as the last subsection of this section
+        #[arg(long, group = "target")]
+        into: Option<String>,
+
+        /// Print the edited document instead of writing it
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Compare the outlines of two files
+    Diff {
+        /// Original markdown file
+        old: PathBuf,
+
+        /// Changed markdown file
+        new: PathBuf,
+    },
+
+    /// Report structural problems and broken in-page links
+    Lint {
+        /// Markdown file or directory to check (use - for stdin)
+        file: PathBuf,
+
+        /// Don't honor .gitignore/.mdignore when checking a directory
+        #[arg(long)]
+        #[cfg_attr(not(feature = "ignore"), arg(hide = true))]
+        no_ignore: bool,
+    },
+
+    /// Find lines matching a regular expression
+    #[cfg(feature = "regex")]
+    Grep {
+        /// Markdown file or directory to search (use - for stdin)
+        file: PathBuf,
+
+        /// Regular expression to match against each line
+        pattern: String,
+
+        /// Only search this section: line number, heading text or path
+        #[arg(short, long)]
+        section: Option<String>,
+
+        /// Match case-insensitively
+        #[arg(short, long)]
+        ignore_case: bool,
+
+        /// Only print the names of files with matches
+        #[arg(short = 'l', long, conflicts_with = "count")]
+        files_with_matches: bool,
+
+        /// Only print the number of matching lines
+        #[arg(short, long)]
+        count: bool,
+
+        /// Ignore matches inside code blocks
+        #[arg(long)]
+        skip_code: bool,
+
+        /// Don't honor .gitignore/.mdignore when searching a directory
+        #[arg(long)]
+        #[cfg_attr(not(feature = "ignore"), arg(hide = true))]
+        no_ignore: bool,
+    },
+
+    /// Reprint the outline or a section whenever the file changes
+    Watch {
+        /// Markdown file to watch
+        file: PathBuf,
+
+        /// What to show
+        #[arg(long, value_enum, default_value_t = WatchView::Outline)]
+        command: WatchView,
+
+        /// Section for `--command read`: line number, heading text or path
+        #[arg(required_if_eq("command", "read"))]
+        section: Option<String>,
+
+        /// Maximum heading depth for the outline (1-6)
+        #[arg(short, long, default_value = "6")]
+        depth: u8,
+
+        /// Milliseconds between checks for changes
+        #[arg(long, default_value = "500")]
+        interval: u64,
+    },
+
+    /// Serve outlines and sections of a directory's files over HTTP
+    #[cfg(feature = "serve")]
+    Serve {
+        /// Directory with the markdown files to serve
+        #[arg(default_value = ".")]
+        dir: PathBuf,
+
+        /// Port to listen on
+        #[arg(short, long, default_value = "8080")]
+        port: u16,
+
+        /// Address to bind to
+        #[arg(long, default_value = "127.0.0.1")]
+        bind: String,
+    },
+
+    /// Run a language server on stdin/stdout for outlines, folding and heading links
+    #[cfg(feature = "lsp")]
+    Lsp,
+
+    /// Run a Model Context Protocol server on stdin/stdout with outline, read,
+    /// search and chunk tools
+    #[cfg(feature = "mcp")]
+    Mcp,
+
+    /// Pick a section interactively with a fuzzy finder
+    #[cfg(feature = "pick")]
+    Pick {
+        /// Markdown file to inspect (use - for stdin)
+        file: PathBuf,
+
+        /// Print the picked section's outline instead of its content
+        #[arg(short, long)]
+        outline: bool,
+
+        /// Maximum heading depth for outline mode (1-6)
+        #[arg(short, long, default_value = "6")]
+        depth: u8,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum WatchView {
+    /// The document outline
+    Outline,
+    /// One section's content
+    Read,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum TocStyle {
+    /// `- ` bullets
+    Bullet,
+    /// `1.` numbered entries
+    Numbered,
 }
 
 fn read_input(file: &PathBuf) -> Result<String> {
@@ -68,78 +560,1581 @@ fn read_input(file: &PathBuf) -> Result<String> {
     }
 }
 
-fn print_outline(headings: &[&markdown_inspector::Heading], max_depth: u8) {
-    for h in headings {
-        if h.level <= max_depth {
-            println!("{}", format_outline_entry(h));
+fn is_mdx(file: &Path) -> bool {
+    file.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("mdx"))
+}
+
+/// Parse headings from an input file's content, in MDX mode for `.mdx` files
+fn parse_input(file: &Path, content: &str) -> Vec<Heading> {
+    let options = ParseOptions {
+        mdx: is_mdx(file),
+        ..Default::default()
+    };
+    parse_headings_with(content, &options)
+}
+
+/// Parse the headings of an input file, memory-mapping it when built with the
+/// `mmap` feature so huge files aren't copied into memory
+#[cfg(feature = "mmap")]
+fn read_headings(file: &PathBuf) -> Result<Vec<Heading>> {
+    if file.as_os_str() == "-" || is_mdx(file) {
+        return Ok(parse_input(file, &read_input(file)?));
+    }
+    let handle =
+        fs::File::open(file).with_context(|| format!("Failed to read file: {:?}", file))?;
+    // SAFETY: the map is only read while parsing; like any reader, a file changing
+    // underneath us yields inconsistent output
+    let map = unsafe { memmap2::Mmap::map(&handle) }
+        .with_context(|| format!("Failed to map file: {:?}", file))?;
+    Ok(markdown_inspector::parse_headings_bytes(&map))
+}
+
+#[cfg(not(feature = "mmap"))]
+fn read_headings(file: &PathBuf) -> Result<Vec<Heading>> {
+    Ok(parse_input(file, &read_input(file)?))
+}
+
+/// Whether directory walks honor ignore files, warning that `--no-ignore` has
+/// nothing to turn off without the `ignore` feature
+fn respect_ignore(no_ignore: bool) -> bool {
+    #[cfg(not(feature = "ignore"))]
+    if no_ignore {
+        eprintln!("warning: --no-ignore has no effect, mdi was built without the `ignore` feature");
+    }
+    !no_ignore
+}
+
+/// Resolve a section argument, treating it as a heading path if it contains `/`
+fn find_heading<'a>(headings: &'a [Heading], section: &str) -> Result<&'a Heading> {
+    section
+        .contains('/')
+        .then(|| find_section_by_path(headings, section))
+        .flatten()
+        .or_else(|| find_section(headings, section))
+        .with_context(|| format!("Section not found: {}", section))
+}
+
+/// Find the section a write command changes by its exact heading text or path
+///
+/// Unlike [`find_heading`] there is no line number lookup or partial match, and
+/// a query matching several headings is an error rather than picking the first.
+fn find_heading_exact<'a>(headings: &'a [Heading], section: &str) -> Result<&'a Heading> {
+    match find_sections_exact(headings, section).as_slice() {
+        [heading] => Ok(heading),
+        [] => anyhow::bail!(
+            "Section not found: {} (write commands need the exact heading text or a path)",
+            section
+        ),
+        matches => {
+            let listed: Vec<String> = matches.iter().map(|h| format_diff_heading(h)).collect();
+            anyhow::bail!(
+                "Section {:?} is ambiguous, it matches:\n  {}\nUse a path like Parent/{} or --line",
+                section,
+                listed.join("\n  "),
+                section
+            )
         }
     }
 }
 
-fn main() -> Result<()> {
-    let cli = Cli::parse();
+/// Drop sections nested in an earlier one of `selected` (sorted by line), whose
+/// text is already printed as part of it
+fn outermost_sections<'a>(headings: &[Heading], selected: Vec<&'a Heading>) -> Vec<&'a Heading> {
+    let mut covered_until = 0;
+    selected
+        .into_iter()
+        .filter(|h| {
+            if h.line_number < covered_until {
+                return false;
+            }
+            covered_until = get_section_range(headings, h).1.unwrap_or(usize::MAX);
+            true
+        })
+        .collect()
+}
 
-    match cli.command {
-        Commands::Outline { file, depth } => {
-            let content = read_input(&file)?;
-            let headings = parse_headings(&content);
-            let heading_refs: Vec<_> = headings.iter().collect();
-            print_outline(&heading_refs, depth);
+/// Find the section a write command changes: the heading at `line`, or the
+/// exact match for `section`
+fn select_heading<'a>(
+    headings: &'a [Heading],
+    section: Option<&str>,
+    line: Option<usize>,
+) -> Result<&'a Heading> {
+    match (line, section) {
+        (Some(line), _) => headings
+            .iter()
+            .find(|h| h.line_number == line)
+            .with_context(|| format!("No heading at line {}", line)),
+        (None, Some(section)) => find_heading_exact(headings, section),
+        (None, None) => anyhow::bail!("Give a section or --line"),
+    }
+}
+
+/// Check relative file links and anchors among `links`, which were parsed from `file`
+fn find_broken_local_links(
+    file: &Path,
+    content: &str,
+    headings: &[Heading],
+    links: &[Link],
+) -> Result<Vec<(Link, String)>> {
+    // Load linked markdown files too, so their anchors can be checked
+    let mut workspace = Workspace::new();
+    workspace.add_file(file, content.to_string());
+    let base = file.parent().unwrap_or(Path::new(""));
+    for link in links.iter().filter(|l| !l.is_external()) {
+        let path = link.destination.split('#').next().unwrap_or_default();
+        let target = base.join(path);
+        if !path.is_empty() && is_markdown_file(&target) && target.is_file() {
+            let target_content = fs::read_to_string(&target)
+                .with_context(|| format!("Failed to read file: {:?}", target))?;
+            workspace.add_file(&target, target_content);
         }
+    }
 
-        Commands::Read {
-            file,
-            section,
-            outline,
-            summary,
-            shallow,
-            depth,
-        } => {
-            let content = read_input(&file)?;
-            let headings = parse_headings(&content);
+    let source = &workspace.files()[0].path;
+    let mut broken: Vec<(Link, String)> = workspace
+        .validate_links()
+        .into_iter()
+        .filter(|b| &b.path == source && links.contains(&b.link))
+        .map(|b| {
+            let reason = match b.reason {
+                BrokenLinkReason::MissingFile => "file not found",
+                BrokenLinkReason::MissingHeading => "no matching heading",
+            };
+            (b.link, reason.to_string())
+        })
+        .collect();
+    broken.extend(
+        validate_anchors_with(content, headings)
+            .into_iter()
+            .filter(|link| links.contains(link))
+            .map(|link| (link, "no matching heading".to_string())),
+    );
+    broken.sort_by_key(|(link, _)| link.line_number);
+    Ok(broken)
+}
 
-            let heading = find_section(&headings, &section)
-                .with_context(|| format!("Section not found: {}", section))?;
+/// Request every http(s) URL among `links`, returning the unreachable ones
+#[cfg(feature = "http")]
+fn find_broken_urls(
+    links: &[Link],
+    timeout: std::time::Duration,
+    retries: u32,
+    jobs: usize,
+) -> Result<Vec<(Link, String)>> {
+    let mut urls: Vec<String> = links
+        .iter()
+        .map(|l| l.destination.clone())
+        .filter(|d| d.starts_with("http://") || d.starts_with("https://"))
+        .collect();
+    urls.sort();
+    urls.dedup();
 
-            let (start, end) = get_section_range(&headings, heading);
+    let options = check::CheckOptions {
+        timeout,
+        retries,
+        jobs,
+    };
+    let failures = check::check_urls(&urls, &options);
+    Ok(links
+        .iter()
+        .filter_map(|link| {
+            failures
+                .iter()
+                .find(|(url, _)| *url == link.destination)
+                .map(|(_, error)| (link.clone(), error.clone()))
+        })
+        .collect())
+}
 
-            if summary {
-                // Show intro text up to first subsection, then outline of subsections
-                let first_sub = get_first_subsection(&headings, heading);
-                let intro = extract_section_intro(&content, heading, first_sub, end);
-                print!("{}", intro);
-                if !intro.ends_with('\n') {
-                    println!();
-                }
+#[cfg(not(feature = "http"))]
+fn find_broken_urls(
+    _links: &[Link],
+    _timeout: std::time::Duration,
+    _retries: u32,
+    _jobs: usize,
+) -> Result<Vec<(Link, String)>> {
+    anyhow::bail!("--online requires mdi to be built with the `http` feature")
+}
 
-                // Show subsections as outline (skip the section heading itself)
-                if first_sub.is_some() {
-                    println!();
-                    let subsections: Vec<_> = get_subsections(&headings, start, end, depth)
-                        .into_iter()
-                        .filter(|h| h.line_number > heading.line_number)
-                        .collect();
-                    print_outline(&subsections, depth);
-                }
-            } else if shallow {
-                // Show section content with subsections collapsed to outline entries
-                let section_content = extract_section_shallow(&content, &headings, heading, end);
-                print!("{}", section_content);
-                if !section_content.ends_with('\n') {
-                    println!();
-                }
-            } else if outline {
-                let subsections = get_subsections(&headings, start, end, depth);
-                print_outline(&subsections, depth);
-            } else {
-                let section_content = extract_section(&content, start, end);
-                print!("{}", section_content);
-                if !section_content.ends_with('\n') {
-                    println!();
-                }
-            }
-        }
+fn print_stats_table(stats: &[SectionStats]) {
+    println!(
+        "{:>5} {:>6} {:>6} {:>5} {:>5}  SECTION",
+        "LINE", "WORDS", "LINES", "CODE", "LINKS"
+    );
+    for s in stats {
+        println!(
+            "{:>5} {:>6} {:>6} {:>5} {:>5}  {}{}",
+            s.line_number,
+            s.words,
+            s.lines,
+            s.code_blocks,
+            s.links,
+            "  ".repeat(s.level.saturating_sub(1) as usize),
+            s.text
+        );
+    }
+}
+
+/// Print a YAML front matter block, or one key of it
+#[cfg(feature = "yaml")]
+fn print_yaml_front_matter(
+    front_matter: &FrontMatter,
+    key: Option<&str>,
+    format: OutputFormat,
+) -> Result<()> {
+    let root = front_matter
+        .parse_yaml()
+        .context("Invalid YAML front matter")?;
+    let value = match key {
+        Some(key) => key
+            .split('.')
+            .try_fold(&root, |value, part| match part.parse::<usize>() {
+                Ok(idx) => value.get(idx),
+                Err(_) => value.get(part),
+            })
+            .with_context(|| format!("Key not found: {}", key))?,
+        None => &root,
+    };
+
+    match value {
+        _ if format != OutputFormat::Text => print_structured(format, value)?,
+        serde_yaml::Value::String(text) => println!("{}", text),
+        _ => print!("{}", serde_yaml::to_string(value)?),
     }
+    Ok(())
+}
+
+#[cfg(not(feature = "yaml"))]
+fn print_yaml_front_matter(
+    _front_matter: &FrontMatter,
+    _key: Option<&str>,
+    _format: OutputFormat,
+) -> Result<()> {
+    anyhow::bail!("Reading YAML front matter requires mdi to be built with the `yaml` feature")
+}
+
+/// Print a TOML front matter block, or one key of it
+#[cfg(feature = "toml")]
+fn print_toml_front_matter(
+    front_matter: &FrontMatter,
+    key: Option<&str>,
+    format: OutputFormat,
+) -> Result<()> {
+    let root = toml::Value::Table(
+        front_matter
+            .parse_toml()
+            .context("Invalid TOML front matter")?,
+    );
+    let value = match key {
+        Some(key) => key
+            .split('.')
+            .try_fold(&root, |value, part| match part.parse::<usize>() {
+                Ok(idx) => value.get(idx),
+                Err(_) => value.get(part),
+            })
+            .with_context(|| format!("Key not found: {}", key))?,
+        None => &root,
+    };
 
+    match value {
+        _ if format != OutputFormat::Text => print_structured(format, value)?,
+        toml::Value::String(text) => println!("{}", text),
+        toml::Value::Table(table) => print!("{}", toml::to_string(table)?),
+        _ => println!("{}", value),
+    }
     Ok(())
 }
+
+#[cfg(not(feature = "toml"))]
+fn print_toml_front_matter(
+    _front_matter: &FrontMatter,
+    _key: Option<&str>,
+    _format: OutputFormat,
+) -> Result<()> {
+    anyhow::bail!("Reading TOML front matter requires mdi to be built with the `toml` feature")
+}
+
+#[cfg(feature = "html")]
+fn render_html(content: &str, start: usize, end: Option<usize>) -> Result<String> {
+    Ok(markdown_inspector::render_section_html(content, start, end))
+}
+
+#[cfg(not(feature = "html"))]
+fn render_html(_content: &str, _start: usize, _end: Option<usize>) -> Result<String> {
+    anyhow::bail!("HTML output requires mdi to be built with the `html` feature")
+}
+
+/// Join a heading path into `Guide > Install > Linux`
+fn format_breadcrumb(path: &[String]) -> String {
+    if path.is_empty() {
+        "(before first heading)".to_string()
+    } else {
+        path.join(" > ")
+    }
+}
+
+/// Outline entries, one per line, for headings down to `max_depth`
+fn format_outline(headings: &[&Heading], max_depth: u8) -> String {
+    headings
+        .iter()
+        .filter(|h| h.level <= max_depth)
+        .map(|h| format_outline_entry(h) + "\n")
+        .collect()
+}
+
+fn print_outline(headings: &[&Heading], max_depth: u8) {
+    print!("{}", format_outline(headings, max_depth));
+}
+
+fn print_pruned_outline(headings: &[Heading], max_depth: u8) {
+    for (h, hidden) in prune_outline(headings, max_depth) {
+        if hidden > 0 {
+            println!("{} (+{})", format_outline_entry(h), hidden);
+        } else {
+            println!("{}", format_outline_entry(h));
+        }
+    }
+}
+
+/// Print a section and its subsections below `prefix` with box-drawing connectors
+fn print_tree_section(
+    section: Section<'_>,
+    prefix: &str,
+    last: bool,
+    max_depth: u8,
+    line_counts: Option<&dyn Fn(&Heading) -> usize>,
+) {
+    let heading = section.heading();
+    let connector = if last { "└── " } else { "├── " };
+    match line_counts {
+        Some(count) => println!("{prefix}{connector}{} ({})", heading.text, count(heading)),
+        None => println!("{prefix}{connector}{}", heading.text),
+    }
+
+    let children: Vec<_> = section
+        .children()
+        .filter(|child| child.heading().level <= max_depth)
+        .collect();
+    let prefix = format!("{prefix}{}", if last { "    " } else { "│   " });
+    for (idx, child) in children.iter().enumerate() {
+        let last = idx + 1 == children.len();
+        print_tree_section(*child, &prefix, last, max_depth, line_counts);
+    }
+}
+
+/// Write an edited document back to `file`, or print it for `--dry-run`
+fn write_document(file: &Path, updated: &str, dry_run: bool) -> Result<()> {
+    if dry_run {
+        print!("{}", updated);
+        return Ok(());
+    }
+    write_atomically(file, updated)
+}
+
+/// Write `text` to `file` through a temporary file next to it that is renamed
+/// over the original, so an interrupted write never leaves a truncated file
+/// behind; a symlink keeps pointing at the updated file
+fn write_atomically(file: &Path, text: &str) -> Result<()> {
+    let target = match fs::canonicalize(file) {
+        Ok(target) => target,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => file.to_path_buf(),
+        Err(err) => {
+            return Err(err).with_context(|| format!("Failed to resolve file: {:?}", file));
+        }
+    };
+    let name = target.file_name().unwrap_or_default().to_string_lossy();
+    let temp = target.with_file_name(format!(".{}.{}.tmp", name, std::process::id()));
+
+    let result = fs::write(&temp, text)
+        .and_then(|()| match fs::metadata(&target) {
+            Ok(metadata) => fs::set_permissions(&temp, metadata.permissions()),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(err),
+        })
+        .and_then(|()| fs::rename(&temp, &target));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result.with_context(|| format!("Failed to write file: {:?}", file))
+}
+
+/// Remove the section `mdi delete` selects, or only its heading and own text
+/// with `keep_children`
+fn delete_from(
+    content: &str,
+    headings: &[Heading],
+    section: Option<&str>,
+    line: Option<usize>,
+    keep_children: bool,
+) -> Result<String> {
+    let heading = select_heading(headings, section, line)?;
+    Ok(if keep_children {
+        unwrap_section(content, headings, heading)
+    } else {
+        delete_section(content, headings, heading)
+    })
+}
+
+/// Resolve `mdi insert`'s position options, of which at most one is given; without
+/// any the section goes at the end of the document
+fn insert_position<'a>(
+    headings: &'a [Heading],
+    after: Option<&str>,
+    before: Option<&str>,
+    child_of: Option<&str>,
+    first_child_of: Option<&str>,
+) -> Result<InsertPosition<'a>> {
+    let find = |query| find_heading_exact(headings, query);
+    Ok(match (after, before, child_of, first_child_of) {
+        (None, None, None, None) => InsertPosition::EndOfDocument,
+        (Some(query), None, None, None) => InsertPosition::After(find(query)?),
+        (None, Some(query), None, None) => InsertPosition::Before(find(query)?),
+        (None, None, Some(query), None) => InsertPosition::LastChildOf(find(query)?),
+        (None, None, None, Some(query)) => InsertPosition::FirstChildOf(find(query)?),
+        _ => anyhow::bail!("Give at most one of --after, --before, --child-of or --first-child-of"),
+    })
+}
+
+/// Resolve the one of `mdi move`'s --after, --before and --into that is given
+fn move_target<'a>(
+    headings: &'a [Heading],
+    after: Option<&str>,
+    before: Option<&str>,
+    into: Option<&str>,
+) -> Result<MoveTarget<'a>> {
+    Ok(match (after, before, into) {
+        (Some(query), None, None) => MoveTarget::After(find_heading_exact(headings, query)?),
+        (None, Some(query), None) => MoveTarget::Before(find_heading_exact(headings, query)?),
+        (None, None, Some(query)) => MoveTarget::Into(find_heading_exact(headings, query)?),
+        _ => anyhow::bail!("Give exactly one of --after, --before or --into"),
+    })
+}
+
+/// Parse a `--level` value: a heading level, or `auto` to let the edit choose
+fn parse_level(level: &str) -> Result<Option<u8>> {
+    if level == "auto" {
+        return Ok(None);
+    }
+    match level.parse() {
+        Ok(n @ 1..=6) => Ok(Some(n)),
+        _ => anyhow::bail!("Invalid level: {} (expected 1-6 or auto)", level),
+    }
+}
+
+/// Make a title from a file name: `getting-started.md` becomes `Getting started`
+fn title_from_file_name(path: &Path) -> Option<String> {
+    let stem = path.file_stem()?.to_str()?.replace(['-', '_'], " ");
+    let mut chars = stem.trim().chars();
+    let first = chars.next()?;
+    Some(first.to_uppercase().chain(chars).collect())
+}
+
+/// Format a heading as `line 12 ## Install`
+fn format_diff_heading(heading: &Heading) -> String {
+    format!(
+        "line {} {} {}",
+        heading.line_number,
+        "#".repeat(heading.level as usize),
+        heading.text
+    )
+}
+
+fn print_outline_diff(diff: &OutlineDiff) {
+    for h in &diff.removed {
+        println!("removed    {}", format_diff_heading(h));
+    }
+    for h in &diff.added {
+        println!("added      {}", format_diff_heading(h));
+    }
+    for change in &diff.renamed {
+        println!(
+            "renamed    {} -> {}",
+            format_diff_heading(&change.old),
+            format_diff_heading(&change.new)
+        );
+    }
+    for change in &diff.moved {
+        println!(
+            "moved      {} -> line {}",
+            format_diff_heading(&change.old),
+            change.new.line_number
+        );
+    }
+    for change in &diff.releveled {
+        println!(
+            "releveled  {} -> {}",
+            format_diff_heading(&change.old),
+            format_diff_heading(&change.new)
+        );
+    }
+}
+
+/// Print the outline of `visible`, the headings at or below the minimum depth
+fn print_file_outline(visible: &[Heading], max_depth: u8, counts: bool) {
+    if counts {
+        print_pruned_outline(visible, max_depth);
+    } else {
+        let heading_refs: Vec<_> = visible.iter().collect();
+        print_outline(&heading_refs, max_depth);
+    }
+}
+
+/// The structured form of [`print_file_outline`]; `headings` are all of the
+/// file's headings, used to find where each section ends
+fn outline_entries<'a>(
+    headings: &[Heading],
+    visible: &'a [Heading],
+    max_depth: u8,
+    counts: bool,
+) -> Vec<SectionEntry<'a>> {
+    if counts {
+        prune_outline(visible, max_depth)
+            .into_iter()
+            .map(|(h, hidden)| SectionEntry {
+                hidden: Some(hidden),
+                ..SectionEntry::new(headings, h)
+            })
+            .collect()
+    } else {
+        visible
+            .iter()
+            .filter(|h| h.level <= max_depth)
+            .map(|h| SectionEntry::new(headings, h))
+            .collect()
+    }
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let format = cli.format;
+
+    match cli.command {
+        Commands::Outline {
+            file,
+            depth,
+            min_depth,
+            no_ignore,
+            counts,
+        } => {
+            // A directory gets a combined outline of every markdown file below it
+            let (files, multiple) = if file.is_dir() {
+                let files = collect_markdown_files(&file, respect_ignore(no_ignore))
+                    .with_context(|| format!("Failed to walk directory: {:?}", file))?;
+                (files, true)
+            } else {
+                (vec![file], false)
+            };
+
+            let mut outlines = Vec::new();
+            for path in &files {
+                let headings = read_headings(path)?;
+                let mut visible = headings.clone();
+                visible.retain(|h| h.level >= min_depth);
+                outlines.push((path, headings, visible));
+            }
+
+            if format == OutputFormat::Text {
+                for (idx, (path, _, visible)) in outlines.iter().enumerate() {
+                    if multiple {
+                        if idx > 0 {
+                            println!();
+                        }
+                        println!("{}", path.display());
+                    }
+                    print_file_outline(visible, depth, counts);
+                }
+            } else if multiple {
+                let entries: Vec<_> = outlines
+                    .iter()
+                    .map(|(path, headings, visible)| FileEntry {
+                        file: path.display().to_string(),
+                        value: SectionList {
+                            sections: outline_entries(headings, visible, depth, counts),
+                        },
+                    })
+                    .collect();
+                print_structured(format, &entries)?;
+            } else {
+                let (_, headings, visible) = &outlines[0];
+                print_structured(format, &outline_entries(headings, visible, depth, counts))?;
+            }
+        }
+
+        Commands::Read {
+            file,
+            sections,
+            delimiter,
+            outline,
+            summary,
+            shallow,
+            html,
+            body,
+            plain,
+            depth,
+        } => {
+            let content = read_input(&file)?;
+            let headings = parse_input(&file, &content);
+
+            let mut selected = sections
+                .iter()
+                .map(|section| find_heading(&headings, section))
+                .collect::<Result<Vec<_>>>()?;
+            selected.sort_by_key(|h| h.line_number);
+            selected.dedup();
+            if !summary && !shallow {
+                selected = outermost_sections(&headings, selected);
+            }
+
+            let mut entries = Vec::new();
+            for heading in selected {
+                let (start, end) = get_section_range(&headings, heading);
+
+                let text = if summary {
+                    // Show intro text up to first subsection, then outline of subsections
+                    let first_sub = get_first_subsection(&headings, heading);
+                    let mut text = extract_section_intro(&content, heading, first_sub, end);
+                    if !text.ends_with('\n') {
+                        text.push('\n');
+                    }
+
+                    // Show subsections as outline (skip the section heading itself)
+                    if first_sub.is_some() {
+                        text.push('\n');
+                        let subsections: Vec<_> = get_subsections(&headings, start, end, depth)
+                            .into_iter()
+                            .filter(|h| h.line_number > heading.line_number)
+                            .collect();
+                        text.push_str(&format_outline(&subsections, depth));
+                    }
+                    text
+                } else if shallow {
+                    // Show section content with subsections collapsed to outline entries
+                    extract_section_shallow(&content, &headings, heading, end)
+                } else if outline {
+                    let subsections = get_subsections(&headings, start, end, depth);
+                    format_outline(&subsections, depth)
+                } else if html {
+                    render_html(&content, start, end)?
+                } else {
+                    let section_content = if body {
+                        extract_section_body(&content, &headings, heading)
+                    } else {
+                        extract_section(&content, start, end)
+                    };
+                    if plain {
+                        to_plain_text(&section_content)
+                    } else {
+                        section_content
+                    }
+                };
+
+                entries.push(SectionEntry {
+                    content: Some(text),
+                    ..SectionEntry::new(&headings, heading)
+                });
+            }
+
+            if format != OutputFormat::Text {
+                print_structured(format, &entries)?;
+                return Ok(());
+            }
+            for (idx, entry) in entries.iter().enumerate() {
+                if idx > 0 {
+                    println!("{}", delimiter);
+                }
+                let text = entry.content.as_deref().unwrap_or_default();
+                print!("{}", text);
+                if !outline && !html && !text.ends_with('\n') {
+                    println!();
+                }
+            }
+        }
+
+        Commands::Toc {
+            file,
+            min_depth,
+            max_depth,
+            style,
+            write,
+        } => {
+            let content = read_input(&file)?;
+            let options = TocOptions {
+                min_level: min_depth,
+                max_level: max_depth,
+                numbered: matches!(style, TocStyle::Numbered),
+                ..Default::default()
+            };
+
+            if write {
+                if file.as_os_str() == "-" {
+                    anyhow::bail!("--write needs a file, not stdin");
+                }
+                let updated = insert_or_update_toc(&content, &options);
+                if updated != content {
+                    write_document(&file, &updated, false)?;
+                }
+            } else {
+                let headings = parse_input(&file, &content);
+                print!("{}", generate_toc(&headings, &options));
+            }
+        }
+
+        Commands::Search {
+            file,
+            pattern,
+            skip_code,
+        } => {
+            let content = read_input(&file)?;
+            let headings = parse_input(&file, &content);
+
+            let hits: Vec<_> = grep_with_context(&content, &headings, &pattern, skip_code)
+                .into_iter()
+                .filter(|hit| !headings.iter().any(|h| h.line_number == hit.line_number))
+                .collect();
+            if format != OutputFormat::Text {
+                print_structured(format, &hits)?;
+                return Ok(());
+            }
+
+            let mut current: Option<&[String]> = None;
+            for hit in &hits {
+                if current != Some(hit.heading_path.as_slice()) {
+                    if current.is_some() {
+                        println!();
+                    }
+                    println!("{}:", format_breadcrumb(&hit.heading_path));
+                    current = Some(&hit.heading_path);
+                }
+                println!("  line {}: {}", hit.line_number, hit.line.trim());
+            }
+        }
+
+        Commands::Links {
+            file,
+            section,
+            internal,
+            external,
+            check,
+            online,
+            timeout,
+            retries,
+            jobs,
+        } => {
+            let content = read_input(&file)?;
+            let headings = parse_input(&file, &content);
+            let range = match &section {
+                Some(query) => Some(get_section_range(
+                    &headings,
+                    find_heading(&headings, query)?,
+                )),
+                None => None,
+            };
+
+            let links: Vec<_> = parse_links(&content)
+                .into_iter()
+                .filter(|link| {
+                    range.is_none_or(|(start, end)| {
+                        link.line_number >= start && end.is_none_or(|e| link.line_number < e)
+                    }) && (!internal || !link.is_external())
+                        && (!external || link.is_external())
+                })
+                .collect();
+
+            if !check {
+                if format != OutputFormat::Text {
+                    print_structured(format, &links)?;
+                    return Ok(());
+                }
+                for link in &links {
+                    println!(
+                        "{:>4}: {} -> {}",
+                        link.line_number, link.text, link.destination
+                    );
+                }
+                return Ok(());
+            }
+
+            let mut broken = find_broken_local_links(&file, &content, &headings, &links)?;
+            if online {
+                let timeout = std::time::Duration::from_secs(timeout);
+                broken.extend(find_broken_urls(&links, timeout, retries, jobs)?);
+                broken.sort_by_key(|(link, _)| link.line_number);
+            }
+
+            if format == OutputFormat::Text {
+                for (link, reason) in &broken {
+                    println!("{:>4}: {} ({})", link.line_number, link.destination, reason);
+                }
+            } else {
+                let entries: Vec<_> = broken
+                    .iter()
+                    .map(|(link, reason)| BrokenEntry {
+                        line_number: link.line_number,
+                        destination: &link.destination,
+                        reason,
+                    })
+                    .collect();
+                print_structured(format, &entries)?;
+            }
+            if !broken.is_empty() {
+                anyhow::bail!("{} broken link(s)", broken.len());
+            }
+        }
+
+        Commands::Code {
+            file,
+            lang,
+            section,
+            index,
+        } => {
+            let content = read_input(&file)?;
+            let headings = parse_input(&file, &content);
+            let range = match &section {
+                Some(query) => Some(get_section_range(
+                    &headings,
+                    find_heading(&headings, query)?,
+                )),
+                None => None,
+            };
+
+            let blocks: Vec<_> = parse_code_blocks(&content)
+                .into_iter()
+                .filter(|block| {
+                    range.is_none_or(|(start, end)| {
+                        block.start_line >= start && end.is_none_or(|e| block.start_line < e)
+                    }) && lang.as_ref().is_none_or(|lang| {
+                        block
+                            .language()
+                            .is_some_and(|l| l.eq_ignore_ascii_case(lang))
+                    })
+                })
+                .collect();
+
+            let selected = match index {
+                Some(n) => {
+                    let block =
+                        n.checked_sub(1)
+                            .and_then(|i| blocks.get(i))
+                            .with_context(|| {
+                                format!("No code block {} ({} matching)", n, blocks.len())
+                            })?;
+                    std::slice::from_ref(block)
+                }
+                None => &blocks[..],
+            };
+            if format != OutputFormat::Text {
+                print_structured(format, &selected)?;
+                return Ok(());
+            }
+            for (idx, block) in selected.iter().enumerate() {
+                if idx > 0 {
+                    println!();
+                }
+                print!("{}", block.body);
+                if !block.body.is_empty() && !block.body.ends_with('\n') {
+                    println!();
+                }
+            }
+        }
+
+        Commands::Anchors { file } => {
+            let content = read_input(&file)?;
+            let headings = parse_input(&file, &content);
+            let anchors = heading_anchors(&headings);
+            let duplicates = find_duplicate_headings(&headings);
+            let collides_with = |line_number: usize| {
+                duplicates
+                    .iter()
+                    .find(|d| d.line_numbers[1..].contains(&line_number))
+                    .map(|d| d.line_numbers[0])
+            };
+
+            let entries: Vec<_> = headings
+                .iter()
+                .zip(&anchors)
+                .map(|(h, anchor)| AnchorEntry {
+                    line_number: h.line_number,
+                    text: &h.text,
+                    anchor,
+                    explicit: h.id.is_some(),
+                    collides_with: collides_with(h.line_number),
+                })
+                .collect();
+            if format != OutputFormat::Text {
+                print_structured(format, &entries)?;
+                return Ok(());
+            }
+
+            let width = anchors.iter().map(|a| a.len()).max().unwrap_or(0);
+            for entry in &entries {
+                let mut notes = Vec::new();
+                if entry.explicit {
+                    notes.push("explicit id".to_string());
+                }
+                if let Some(first) = entry.collides_with {
+                    notes.push(format!("collides with line {}", first));
+                }
+                let notes = if notes.is_empty() {
+                    String::new()
+                } else {
+                    format!("  ({})", notes.join(", "))
+                };
+                println!(
+                    "{:>4}: #{:<width$}  {}{}",
+                    entry.line_number, entry.anchor, entry.text, notes
+                );
+            }
+        }
+
+        Commands::Frontmatter { file, key } => {
+            let content = read_input(&file)?;
+            let front_matter = parse_front_matter(&content).context("No front matter found")?;
+
+            if key.is_none() && format == OutputFormat::Text {
+                println!("{}", front_matter.raw);
+                return Ok(());
+            }
+            match front_matter.format {
+                FrontMatterFormat::Yaml => {
+                    print_yaml_front_matter(&front_matter, key.as_deref(), format)?
+                }
+                FrontMatterFormat::Toml => {
+                    print_toml_front_matter(&front_matter, key.as_deref(), format)?
+                }
+            }
+        }
+
+        Commands::Stats { file } => {
+            let content = read_input(&file)?;
+            let headings = parse_input(&file, &content);
+            let stats = section_stats(&content, &headings);
+
+            if format == OutputFormat::Text {
+                print_stats_table(&stats);
+            } else {
+                print_structured(format, &stats)?;
+            }
+        }
+
+        Commands::Tree { file, depth, lines } => {
+            let content = read_input(&file)?;
+            let headings = parse_input(&file, &content);
+            if format != OutputFormat::Text {
+                let mut visible = headings;
+                visible.retain(|h| h.level <= depth);
+                print_structured(format, &nested_outline(&visible))?;
+                return Ok(());
+            }
+
+            let total_lines = content.lines().count();
+            let line_count = |heading: &Heading| {
+                let (start, end) = get_section_range(&headings, heading);
+                end.unwrap_or(total_lines + 1) - start
+            };
+
+            let tree = SectionTree::from_headings(&headings);
+            let roots: Vec<_> = tree
+                .roots()
+                .filter(|root| root.heading().level <= depth)
+                .collect();
+            println!("{}", file.display());
+            for (idx, root) in roots.iter().enumerate() {
+                let line_counts = lines.then_some(&line_count as &dyn Fn(&Heading) -> usize);
+                print_tree_section(*root, "", idx + 1 == roots.len(), depth, line_counts);
+            }
+        }
+
+        Commands::Split {
+            file,
+            level,
+            out_dir,
+            index,
+            force,
+        } => {
+            let content = read_input(&file)?;
+            let headings = parse_input(&file, &content);
+            let sections = split_document(&content, &headings, level);
+            if sections.is_empty() {
+                anyhow::bail!("No level {} headings found", level);
+            }
+
+            let mut outputs: Vec<(String, &str)> = sections
+                .iter()
+                .map(|s| (s.file_name.clone(), s.content.as_str()))
+                .collect();
+            let index_content = split_index(&content, &sections);
+            if index {
+                if outputs.iter().any(|(name, _)| name == "index.md") {
+                    anyhow::bail!(
+                        "A section would be written to index.md; rename it or drop --index"
+                    );
+                }
+                outputs.push(("index.md".to_string(), &index_content));
+            }
+
+            let paths: Vec<PathBuf> = outputs.iter().map(|(name, _)| out_dir.join(name)).collect();
+            if !force && let Some(existing) = paths.iter().find(|p| p.exists()) {
+                anyhow::bail!(
+                    "{} already exists (use --force to overwrite)",
+                    existing.display()
+                );
+            }
+
+            fs::create_dir_all(&out_dir)
+                .with_context(|| format!("Failed to create directory: {:?}", out_dir))?;
+            for (path, (_, text)) in paths.iter().zip(&outputs) {
+                write_atomically(path, text)?;
+                if format == OutputFormat::Text {
+                    println!("{}", path.display());
+                }
+            }
+            if format != OutputFormat::Text {
+                print_structured(format, &paths)?;
+            }
+        }
+
+        Commands::Join {
+            files,
+            shift,
+            title,
+            toc,
+        } => {
+            let contents = files.iter().map(read_input).collect::<Result<Vec<_>>>()?;
+            let names: Vec<String> = files.iter().map(|f| f.display().to_string()).collect();
+            let documents: Vec<(&str, &str)> = names
+                .iter()
+                .zip(&contents)
+                .map(|(name, content)| (name.as_str(), content.as_str()))
+                .collect();
+
+            let title = match title {
+                Some(title) => Some(title),
+                None if shift > 0 => title_from_file_name(&files[0]),
+                None => None,
+            };
+            let options = JoinOptions { title, shift };
+            let mut joined = join_documents(&documents, &options);
+            if toc {
+                let toc_options = TocOptions {
+                    min_level: if options.title.is_some() { 2 } else { 1 },
+                    ..Default::default()
+                };
+                joined = insert_or_update_toc(&joined, &toc_options);
+            }
+            print!("{}", joined);
+        }
+
+        Commands::Edit {
+            file,
+            section,
+            line,
+            from,
+            dry_run,
+        } => {
+            if file.as_os_str() == "-" {
+                anyhow::bail!("edit needs a file, not stdin");
+            }
+            let content = read_input(&file)?;
+            let headings = parse_input(&file, &content);
+            let heading = select_heading(&headings, section.as_deref(), line)?;
+            let new_body = read_input(&from)?;
+
+            let updated = replace_section(&content, &headings, heading, &new_body);
+            write_document(&file, &updated, dry_run)?;
+        }
+
+        Commands::Insert {
+            file,
+            title,
+            after,
+            before,
+            child_of,
+            first_child_of,
+            level,
+            body,
+            dry_run,
+        } => {
+            if file.as_os_str() == "-" {
+                anyhow::bail!("insert needs a file, not stdin");
+            }
+            let level = parse_level(&level)?;
+            let content = read_input(&file)?;
+            let headings = parse_input(&file, &content);
+            let body = match body {
+                Some(path) => read_input(&path)?,
+                None => String::new(),
+            };
+
+            let position = insert_position(
+                &headings,
+                after.as_deref(),
+                before.as_deref(),
+                child_of.as_deref(),
+                first_child_of.as_deref(),
+            )?;
+
+            let updated = insert_section(&content, &headings, position, level, &title, &body)?;
+            write_document(&file, &updated, dry_run)?;
+        }
+
+        Commands::Delete {
+            file,
+            section,
+            line,
+            keep_children,
+            dry_run,
+        } => {
+            if file.as_os_str() == "-" {
+                anyhow::bail!("delete needs a file, not stdin");
+            }
+            let content = read_input(&file)?;
+            let headings = parse_input(&file, &content);
+            let updated =
+                delete_from(&content, &headings, section.as_deref(), line, keep_children)?;
+            write_document(&file, &updated, dry_run)?;
+        }
+
+        Commands::Move {
+            file,
+            section,
+            line,
+            after,
+            before,
+            into,
+            dry_run,
+        } => {
+            if file.as_os_str() == "-" {
+                anyhow::bail!("move needs a file, not stdin");
+            }
+            let content = read_input(&file)?;
+            let headings = parse_input(&file, &content);
+            let heading = select_heading(&headings, section.as_deref(), line)?;
+
+            let target = move_target(
+                &headings,
+                after.as_deref(),
+                before.as_deref(),
+                into.as_deref(),
+            )?;
+
+            let updated = move_section(&content, &headings, heading, target)?;
+            write_document(&file, &updated, dry_run)?;
+        }
+
+        Commands::Diff { old, new } => {
+            let diff = diff_outlines(&read_headings(&old)?, &read_headings(&new)?);
+            if format == OutputFormat::Text {
+                print_outline_diff(&diff);
+            } else {
+                print_structured(format, &diff)?;
+            }
+        }
+
+        Commands::Lint { file, no_ignore } => {
+            let files = if file.is_dir() {
+                collect_markdown_files(&file, respect_ignore(no_ignore))
+                    .with_context(|| format!("Failed to walk directory: {:?}", file))?
+            } else {
+                vec![file]
+            };
+
+            let mut entries = Vec::new();
+            for path in &files {
+                let content = read_input(path)?;
+                let headings = parse_input(path, &content);
+                for finding in lint_document(&content, &headings) {
+                    entries.push(LintEntry {
+                        file: path.display().to_string(),
+                        line_number: finding.line_number,
+                        message: finding.kind.to_string(),
+                        kind: finding.kind,
+                    });
+                }
+            }
+
+            if format == OutputFormat::Text {
+                for entry in &entries {
+                    println!("{}:{}: {}", entry.file, entry.line_number, entry.message);
+                }
+            } else {
+                print_structured(format, &entries)?;
+            }
+            if !entries.is_empty() {
+                anyhow::bail!("{} problem(s) found", entries.len());
+            }
+        }
+
+        #[cfg(feature = "regex")]
+        Commands::Grep {
+            file,
+            pattern,
+            section,
+            ignore_case,
+            files_with_matches,
+            count,
+            skip_code,
+            no_ignore,
+        } => {
+            let pattern = regex::RegexBuilder::new(&pattern)
+                .case_insensitive(ignore_case)
+                .build()
+                .with_context(|| format!("Invalid regular expression: {}", pattern))?;
+
+            let (files, multiple) = if file.is_dir() {
+                let files = collect_markdown_files(&file, respect_ignore(no_ignore))
+                    .with_context(|| format!("Failed to walk directory: {:?}", file))?;
+                (files, true)
+            } else {
+                (vec![file], false)
+            };
+
+            let mut matched_files = Vec::new();
+            let mut counts = Vec::new();
+            let mut hit_lists = Vec::new();
+            for path in &files {
+                let content = read_input(path)?;
+                let headings = parse_input(path, &content);
+                let range = match &section {
+                    // Files without the section have nothing to search
+                    Some(query) => match find_heading(&headings, query) {
+                        Ok(heading) => Some(get_section_range(&headings, heading)),
+                        Err(err) if !multiple => return Err(err),
+                        Err(_) => continue,
+                    },
+                    None => None,
+                };
+
+                let hits: Vec<_> = grep_regex(&content, &headings, &pattern, skip_code)
+                    .into_iter()
+                    .filter(|hit| {
+                        range.is_none_or(|(start, end)| {
+                            hit.line_number >= start && end.is_none_or(|e| hit.line_number < e)
+                        })
+                    })
+                    .collect();
+
+                if format != OutputFormat::Text {
+                    let file = path.display().to_string();
+                    if files_with_matches {
+                        if !hits.is_empty() {
+                            matched_files.push(file);
+                        }
+                    } else if count {
+                        counts.push(FileEntry {
+                            file,
+                            value: HitCount { count: hits.len() },
+                        });
+                    } else {
+                        hit_lists.push(FileEntry {
+                            file,
+                            value: HitList { hits },
+                        });
+                    }
+                    continue;
+                }
+
+                let prefix = if multiple {
+                    format!("{}:", path.display())
+                } else {
+                    String::new()
+                };
+                if files_with_matches {
+                    if !hits.is_empty() {
+                        println!("{}", path.display());
+                    }
+                } else if count {
+                    println!("{}{}", prefix, hits.len());
+                } else {
+                    for hit in hits {
+                        println!(
+                            "{}{}: {}: {}",
+                            prefix,
+                            hit.line_number,
+                            format_breadcrumb(&hit.heading_path),
+                            hit.line
+                        );
+                    }
+                }
+            }
+
+            if format != OutputFormat::Text {
+                if files_with_matches {
+                    print_structured(format, &matched_files)?;
+                } else if count {
+                    print_structured(format, &counts)?;
+                } else {
+                    print_structured(format, &hit_lists)?;
+                }
+            }
+        }
+
+        Commands::Watch {
+            file,
+            command,
+            section,
+            depth,
+            interval,
+        } => {
+            if file.as_os_str() == "-" {
+                anyhow::bail!("watch needs a file, not stdin");
+            }
+            if !file.is_file() {
+                anyhow::bail!("File not found: {:?}", file);
+            }
+
+            watch::watch(&file, std::time::Duration::from_millis(interval), || {
+                let content = read_input(&file)?;
+                let headings = parse_input(&file, &content);
+                match command {
+                    WatchView::Outline => {
+                        let heading_refs: Vec<_> = headings.iter().collect();
+                        print_outline(&heading_refs, depth);
+                    }
+                    WatchView::Read => {
+                        let heading =
+                            find_heading(&headings, section.as_deref().unwrap_or_default())?;
+                        let (start, end) = get_section_range(&headings, heading);
+                        let section_content = extract_section(&content, start, end);
+                        print!("{}", section_content);
+                        if !section_content.ends_with('\n') {
+                            println!();
+                        }
+                    }
+                }
+                Ok(())
+            })?;
+        }
+
+        #[cfg(feature = "serve")]
+        Commands::Serve { dir, port, bind } => {
+            if !dir.is_dir() {
+                anyhow::bail!("Not a directory: {:?}", dir);
+            }
+            serve::serve(&dir, &format!("{}:{}", bind, port))?;
+        }
+
+        #[cfg(feature = "lsp")]
+        Commands::Lsp => lsp::run()?,
+
+        #[cfg(feature = "mcp")]
+        Commands::Mcp => mcp::run()?,
+
+        #[cfg(feature = "pick")]
+        Commands::Pick {
+            file,
+            outline,
+            depth,
+        } => {
+            let content = read_input(&file)?;
+            let headings = parse_input(&file, &content);
+
+            // Without a terminal there is nothing to interact with, so fall back to the outline
+            if !io::IsTerminal::is_terminal(&io::stdout()) {
+                let heading_refs: Vec<_> = headings.iter().collect();
+                print_outline(&heading_refs, depth);
+                return Ok(());
+            }
+
+            let Some(heading) = pick::pick_heading(&headings)? else {
+                return Ok(());
+            };
+
+            let (start, end) = get_section_range(&headings, &heading);
+            if outline {
+                let subsections = get_subsections(&headings, start, end, depth);
+                print_outline(&subsections, depth);
+            } else {
+                let section_content = extract_section(&content, start, end);
+                print!("{}", section_content);
+                if !section_content.ends_with('\n') {
+                    println!();
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use markdown_inspector::parse_headings;
+
+    #[test]
+    fn test_insert_position() {
+        let content = "# Guide\n\n## Install\n\n## Usage\n";
+        let headings = parse_headings(content);
+        let position = |after, before, child_of, first_child_of| {
+            insert_position(&headings, after, before, child_of, first_child_of)
+        };
+
+        assert_eq!(
+            position(None, None, None, None).unwrap(),
+            InsertPosition::EndOfDocument
+        );
+        assert_eq!(
+            position(Some("Install"), None, None, None).unwrap(),
+            InsertPosition::After(&headings[1])
+        );
+        assert_eq!(
+            position(None, Some("Usage"), None, None).unwrap(),
+            InsertPosition::Before(&headings[2])
+        );
+        assert_eq!(
+            position(None, None, Some("Guide"), None).unwrap(),
+            InsertPosition::LastChildOf(&headings[0])
+        );
+        assert_eq!(
+            position(None, None, None, Some("Guide/Usage")).unwrap(),
+            InsertPosition::FirstChildOf(&headings[2])
+        );
+        assert!(position(Some("install"), None, None, None).is_err());
+        assert!(position(Some("Install"), Some("Usage"), None, None).is_err());
+    }
+
+    #[test]
+    fn test_move_target() {
+        let content =
+            "# Guide\n\n## Install\n\n## Usage\n\n### Examples\n\n## FAQ\n\n### Examples\n";
+        let headings = parse_headings(content);
+
+        let after = move_target(&headings, Some("Usage"), None, None).unwrap();
+        assert_eq!(after, MoveTarget::After(&headings[2]));
+        let before = move_target(&headings, None, Some("Guide/Install"), None).unwrap();
+        assert_eq!(before, MoveTarget::Before(&headings[1]));
+        let into = move_target(&headings, None, None, Some("FAQ/Examples")).unwrap();
+        assert_eq!(into, MoveTarget::Into(&headings[5]));
+
+        // Targets need an exact, unambiguous heading
+        assert!(move_target(&headings, Some("usage"), None, None).is_err());
+        assert!(move_target(&headings, None, None, Some("Examples")).is_err());
+        assert!(move_target(&headings, None, None, None).is_err());
+        assert!(move_target(&headings, Some("Usage"), Some("FAQ"), None).is_err());
+
+        let moved = move_section(content, &headings, &headings[1], after).unwrap();
+        let texts: Vec<_> = parse_headings(&moved).into_iter().map(|h| h.text).collect();
+        assert_eq!(
+            texts,
+            ["Guide", "Usage", "Examples", "Install", "FAQ", "Examples"]
+        );
+    }
+
+    #[test]
+    fn test_move_requires_one_target() {
+        let parse =
+            |args: &[&str]| Cli::try_parse_from([&["mdi", "move", "doc.md"], args].concat());
+
+        assert!(parse(&["Install", "--after", "Usage"]).is_ok());
+        assert!(parse(&["--line", "3", "--into", "Usage"]).is_ok());
+        assert!(parse(&["Install"]).is_err());
+        assert!(parse(&["Install", "--after", "Usage", "--before", "FAQ"]).is_err());
+        assert!(parse(&["--after", "Usage"]).is_err());
+    }
+
+    #[test]
+    fn test_broken_links_into_markdown_extension() {
+        let dir = std::env::temp_dir().join(format!("mdi-links-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("guide.markdown"), "# Guide\n\n## Setup\n").unwrap();
+
+        let file = dir.join("index.md");
+        let content = "# Index\n\n[ok](guide.markdown#setup) [bad](guide.markdown#missing)\n";
+        let headings = parse_headings(content);
+        let links = parse_links(content);
+        let broken = find_broken_local_links(&file, content, &headings, &links).unwrap();
+
+        let destinations: Vec<_> = broken
+            .iter()
+            .map(|(link, _)| link.destination.as_str())
+            .collect();
+        assert_eq!(destinations, ["guide.markdown#missing"]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_write_atomically() {
+        let dir = std::env::temp_dir().join(format!("mdi-write-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let file = dir.join("new.md");
+        write_atomically(&file, "# New\n").unwrap();
+        assert_eq!(fs::read_to_string(&file).unwrap(), "# New\n");
+        write_atomically(&file, "# Replaced\n").unwrap();
+        assert_eq!(fs::read_to_string(&file).unwrap(), "# Replaced\n");
+
+        #[cfg(unix)]
+        {
+            let link = dir.join("link.md");
+            std::os::unix::fs::symlink(&file, &link).unwrap();
+            write_atomically(&link, "# Through link\n").unwrap();
+            assert!(
+                fs::symlink_metadata(&link)
+                    .unwrap()
+                    .file_type()
+                    .is_symlink()
+            );
+            assert_eq!(fs::read_to_string(&file).unwrap(), "# Through link\n");
+        }
+
+        let names: Vec<_> = fs::read_dir(&dir).unwrap().collect();
+        assert_eq!(names.len(), if cfg!(unix) { 2 } else { 1 });
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_outermost_sections() {
+        let headings = parse_headings("# A\n## B\n### C\n## D\n# E\n");
+        let pick = |lines: &[usize]| {
+            let selected = lines.iter().map(|&line| &headings[line - 1]).collect();
+            outermost_sections(&headings, selected)
+                .iter()
+                .map(|h| h.line_number)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(pick(&[2, 3]), [2]);
+        assert_eq!(pick(&[2, 3, 4]), [2, 4]);
+        assert_eq!(pick(&[1, 3, 5]), [1, 5]);
+        assert_eq!(pick(&[3, 4]), [3, 4]);
+    }
+
+    #[test]
+    fn test_stats_format_flags() {
+        let cli = Cli::try_parse_from(["mdi", "stats", "-f", "table", "doc.md"]).unwrap();
+        assert!(cli.format == OutputFormat::Text);
+
+        let cli = Cli::try_parse_from(["mdi", "stats", "--format", "json", "doc.md"]).unwrap();
+        assert!(cli.format == OutputFormat::Json);
+    }
+
+    #[test]
+    fn test_delete_requires_unambiguous_section() {
+        let content =
+            "# Guide\n\n## Uninstall\n\nremove it\n\n### Examples\n\n## Usage\n\n### Examples\n";
+        let headings = parse_headings(content);
+        let delete = |section, line| delete_from(content, &headings, section, line, false);
+
+        // No partial or case-insensitive matches
+        assert!(delete(Some("install"), None).is_err());
+        assert!(delete(Some("uninstall"), None).is_err());
+        assert!(delete(Some("3"), None).is_err());
+
+        let ambiguous = delete(Some("Examples"), None).unwrap_err().to_string();
+        assert!(ambiguous.contains("line 7") && ambiguous.contains("line 11"));
+
+        assert_eq!(
+            delete(Some("Usage/Examples"), None).unwrap(),
+            "# Guide\n\n## Uninstall\n\nremove it\n\n### Examples\n\n## Usage\n"
+        );
+        assert_eq!(
+            delete(None, Some(3)).unwrap(),
+            "# Guide\n\n## Usage\n\n### Examples\n"
+        );
+        assert!(delete(None, Some(4)).is_err());
+    }
+}
@@ -0,0 +1,329 @@
+//! Model Context Protocol server for `mdi mcp`
+//!
+//! Speaks newline-delimited JSON-RPC over stdio and offers the outline, section
+//! reading, search and chunking as tools, so agents can load a document one
+//! section at a time.
+
+use crate::{find_heading, format_breadcrumb, parse_input, read_input};
+use anyhow::{Context, Result};
+use markdown_inspector::{
+    ChunkOptions, Heading, chunk_document, extract_section, format_outline_entry,
+    get_section_range, grep_with_context,
+};
+use serde_json::{Value, json};
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+
+/// Protocol revision reported when the client doesn't name one
+const PROTOCOL_VERSION: &str = "2025-06-18";
+
+/// JSON-RPC's "parse error" code
+const PARSE_ERROR: i64 = -32700;
+
+/// JSON-RPC's "method not found" error code
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// JSON-RPC's "invalid params" error code
+const INVALID_PARAMS: i64 = -32602;
+
+fn tools() -> Value {
+    let path = json!({ "type": "string", "description": "Path to the markdown file" });
+    json!([
+        {
+            "name": "outline",
+            "description": "List the headings of a markdown file with their line numbers, indented by level. Use it first to find the sections worth reading.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "path": path,
+                    "max_depth": { "type": "integer", "description": "Deepest heading level to list (1-6)", "minimum": 1, "maximum": 6 },
+                },
+                "required": ["path"],
+            },
+        },
+        {
+            "name": "read_section",
+            "description": "Read one section of a markdown file, including its subsections.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "path": path,
+                    "section": { "type": "string", "description": "Line number, heading text (partial match) or heading path like Guide/Install" },
+                },
+                "required": ["path", "section"],
+            },
+        },
+        {
+            "name": "search",
+            "description": "Find lines containing a case-insensitive substring, grouped under the headings they belong to.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "path": path,
+                    "pattern": { "type": "string", "description": "Text to search for" },
+                    "skip_code": { "type": "boolean", "description": "Ignore lines inside code blocks" },
+                },
+                "required": ["path", "pattern"],
+            },
+        },
+        {
+            "name": "chunk",
+            "description": "Split a markdown file into size-limited chunks along section boundaries, each with its heading breadcrumb and line range.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "path": path,
+                    "max_chars": { "type": "integer", "description": "Soft size limit per chunk in characters (default 2000)" },
+                    "split_on_level": { "type": "integer", "description": "Headings at this level or shallower always start a chunk (default 2)" },
+                },
+                "required": ["path"],
+            },
+        },
+    ])
+}
+
+fn string_arg<'a>(args: &'a Value, name: &str) -> Result<&'a str> {
+    args[name]
+        .as_str()
+        .with_context(|| format!("Missing argument: {}", name))
+}
+
+/// Read the document named by the `path` argument and parse its headings
+fn load_document(args: &Value) -> Result<(String, Vec<Heading>)> {
+    let path = PathBuf::from(string_arg(args, "path")?);
+    // Stdin carries the protocol, so it can't be a document
+    if path.as_os_str() == "-" {
+        anyhow::bail!("path must be a file, not stdin");
+    }
+    let content = read_input(&path)?;
+    let headings = parse_input(&path, &content);
+    Ok((content, headings))
+}
+
+/// Run a tool, returning the text to show the model
+fn call_tool(name: &str, args: &Value) -> Result<String> {
+    match name {
+        "outline" => {
+            let (_, headings) = load_document(args)?;
+            let max_depth = args["max_depth"].as_u64().unwrap_or(6);
+            Ok(headings
+                .iter()
+                .filter(|h| u64::from(h.level) <= max_depth)
+                .map(|h| format_outline_entry(h) + "\n")
+                .collect())
+        }
+        "read_section" => {
+            let (content, headings) = load_document(args)?;
+            let heading = find_heading(&headings, string_arg(args, "section")?)?;
+            let (start, end) = get_section_range(&headings, heading);
+            Ok(extract_section(&content, start, end))
+        }
+        "search" => {
+            let (content, headings) = load_document(args)?;
+            let pattern = string_arg(args, "pattern")?;
+            let skip_code = args["skip_code"].as_bool().unwrap_or(false);
+            let hits = grep_with_context(&content, &headings, pattern, skip_code);
+            if hits.is_empty() {
+                return Ok("No matches".to_string());
+            }
+            Ok(hits
+                .iter()
+                .map(|hit| {
+                    format!(
+                        "line {} ({}): {}\n",
+                        hit.line_number,
+                        format_breadcrumb(&hit.heading_path),
+                        hit.line.trim()
+                    )
+                })
+                .collect())
+        }
+        "chunk" => {
+            let (content, _) = load_document(args)?;
+            let defaults = ChunkOptions::default();
+            let options = ChunkOptions {
+                max_chars: args["max_chars"]
+                    .as_u64()
+                    .map_or(defaults.max_chars, |n| n as usize),
+                split_on_level: args["split_on_level"]
+                    .as_u64()
+                    .map_or(defaults.split_on_level, |n| n.min(6) as u8),
+            };
+            Ok(serde_json::to_string_pretty(&chunk_document(
+                &content, &options,
+            ))?)
+        }
+        _ => anyhow::bail!("Unknown tool: {}", name),
+    }
+}
+
+fn request(method: &str, params: &Value) -> Result<Value, (i64, String)> {
+    match method {
+        "initialize" => {
+            let version = params["protocolVersion"]
+                .as_str()
+                .unwrap_or(PROTOCOL_VERSION);
+            Ok(json!({
+                "protocolVersion": version,
+                "capabilities": { "tools": {} },
+                "serverInfo": { "name": "mdi", "version": env!("CARGO_PKG_VERSION") },
+            }))
+        }
+        "ping" => Ok(json!({})),
+        "tools/list" => Ok(json!({ "tools": tools() })),
+        "tools/call" => {
+            let name = params["name"]
+                .as_str()
+                .ok_or((INVALID_PARAMS, "Missing tool name".to_string()))?;
+            // Tool failures are reported to the model rather than as protocol errors
+            Ok(match call_tool(name, &params["arguments"]) {
+                Ok(text) => json!({ "content": [{ "type": "text", "text": text }] }),
+                Err(err) => json!({
+                    "content": [{ "type": "text", "text": format!("{:#}", err) }],
+                    "isError": true,
+                }),
+            })
+        }
+        _ => Err((METHOD_NOT_FOUND, format!("Unsupported method: {}", method))),
+    }
+}
+
+/// Serve MCP requests on stdin/stdout until stdin closes
+pub fn run() -> Result<()> {
+    let mut output = io::stdout().lock();
+
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let message: Value = match serde_json::from_str(&line) {
+            Ok(message) => message,
+            Err(err) => {
+                let error = json!({
+                    "jsonrpc": "2.0",
+                    "id": null,
+                    "error": { "code": PARSE_ERROR, "message": err.to_string() },
+                });
+                writeln!(output, "{}", error)?;
+                output.flush()?;
+                continue;
+            }
+        };
+
+        // Notifications (no id) and responses (no method) need no reply
+        let (Some(id), Some(method)) = (message.get("id"), message["method"].as_str()) else {
+            continue;
+        };
+        let response = match request(method, &message["params"]) {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, text)) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": code, "message": text },
+            }),
+        };
+        writeln!(output, "{}", response)?;
+        output.flush()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_file(name: &str, content: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("mdi-{}-{}.md", name, std::process::id()));
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    /// Call a tool through `tools/call`, returning its text and whether it failed
+    fn call(name: &str, arguments: Value) -> (String, bool) {
+        let result = request(
+            "tools/call",
+            &json!({ "name": name, "arguments": arguments }),
+        )
+        .unwrap();
+        let text = result["content"][0]["text"].as_str().unwrap().to_string();
+        (text, result["isError"] == true)
+    }
+
+    #[test]
+    fn test_tools_list() {
+        let result = request("tools/list", &Value::Null).unwrap();
+        let names: Vec<_> = result["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|tool| tool["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, ["outline", "read_section", "search", "chunk"]);
+    }
+
+    #[test]
+    fn test_tools() {
+        let path = scratch_file(
+            "mcp",
+            "# Guide\n\nIntro\n\n## Install\n\nRun setup\n\n### Linux\n\napt\n\n## Usage\n\nRun it\n",
+        );
+        let path = path.to_string_lossy();
+
+        let (outline, failed) = call("outline", json!({ "path": path, "max_depth": 2 }));
+        assert!(!failed);
+        assert_eq!(outline, "   1:Guide\n   5:  Install\n  13:  Usage\n");
+
+        let (section, failed) = call(
+            "read_section",
+            json!({ "path": path, "section": "Install" }),
+        );
+        assert!(!failed);
+        assert_eq!(section, "## Install\n\nRun setup\n\n### Linux\n\napt\n\n");
+
+        let (hits, failed) = call("search", json!({ "path": path, "pattern": "run" }));
+        assert!(!failed);
+        assert_eq!(
+            hits,
+            "line 7 (Guide > Install): Run setup\nline 15 (Guide > Usage): Run it\n"
+        );
+        let (hits, _) = call("search", json!({ "path": path, "pattern": "nothing" }));
+        assert_eq!(hits, "No matches");
+
+        let (chunks, failed) = call("chunk", json!({ "path": path, "max_chars": 20 }));
+        assert!(!failed);
+        let chunks: Value = serde_json::from_str(&chunks).unwrap();
+        assert!(chunks.as_array().unwrap().len() > 1);
+
+        std::fs::remove_file(path.as_ref()).unwrap();
+    }
+
+    #[test]
+    fn test_tool_errors() {
+        let (text, failed) = call("read_section", json!({ "path": "README.md" }));
+        assert!(failed);
+        assert_eq!(text, "Missing argument: section");
+
+        let (text, failed) = call("outline", json!({}));
+        assert!(failed);
+        assert_eq!(text, "Missing argument: path");
+
+        let (text, failed) = call("outline", json!({ "path": "-" }));
+        assert!(failed);
+        assert_eq!(text, "path must be a file, not stdin");
+
+        let (text, failed) = call("delete", json!({ "path": "README.md" }));
+        assert!(failed);
+        assert_eq!(text, "Unknown tool: delete");
+    }
+
+    #[test]
+    fn test_unknown_method() {
+        let (code, message) = request("resources/list", &Value::Null).unwrap_err();
+        assert_eq!(code, METHOD_NOT_FOUND);
+        assert_eq!(message, "Unsupported method: resources/list");
+
+        let (code, _) = request("tools/call", &json!({})).unwrap_err();
+        assert_eq!(code, INVALID_PARAMS);
+    }
+}
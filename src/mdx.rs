@@ -0,0 +1,124 @@
+//! MDX syntax that isn't markdown structure
+
+use crate::scan::{CodeBlockTracker, strip_line_ending};
+
+/// What a run of masked lines belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Block {
+    /// `import`/`export` statements, ending at a blank line
+    Esm,
+    /// A JSX element with this many tags still open
+    Jsx(isize),
+    /// A `{/* ... */}` comment
+    Comment,
+}
+
+/// Net number of JSX tags a line opens (negative when it closes more)
+fn jsx_depth_change(line: &str) -> isize {
+    let bytes = line.as_bytes();
+    let mut depth = 0;
+    for (idx, &b) in bytes.iter().enumerate() {
+        match (b, bytes.get(idx + 1)) {
+            (b'<', Some(b'/')) => depth -= 1,
+            (b'<', Some(next)) if next.is_ascii_alphabetic() || *next == b'>' => depth += 1,
+            (b'/', Some(b'>')) => depth -= 1,
+            _ => {}
+        }
+    }
+    depth
+}
+
+/// Whether a line starts a JSX element (`<Tabs>`, `<div ...`, `<>`)
+fn starts_jsx(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    line.len() - trimmed.len() < 4
+        && trimmed
+            .strip_prefix('<')
+            .and_then(|rest| rest.bytes().next())
+            .is_some_and(|b| b.is_ascii_alphabetic() || b == b'>')
+}
+
+/// Copy of `content` with MDX-only syntax blanked out
+///
+/// ESM `import`/`export` statements (up to the next blank line), JSX blocks
+/// (from a line starting with a tag to the line closing it) and `{/* */}`
+/// comments are replaced with spaces, keeping byte offsets and line breaks, so
+/// the lines read as blank and no headings are found inside them.
+pub(crate) fn mask_mdx(content: &str) -> String {
+    let mut output = String::with_capacity(content.len());
+    let mut code = CodeBlockTracker::default();
+    let mut block: Option<Block> = None;
+    let mut prev_blank = true;
+
+    for raw in content.split_inclusive('\n') {
+        let line = strip_line_ending(raw);
+        let blank = line.trim().is_empty();
+
+        if block.is_none() && !code.in_fence() {
+            let trimmed = line.trim_start();
+            if prev_blank && (line.starts_with("import ") || line.starts_with("export ")) {
+                block = Some(Block::Esm);
+            } else if trimmed.starts_with("{/*") {
+                block = Some(Block::Comment);
+            } else if starts_jsx(line) {
+                block = Some(Block::Jsx(0));
+            }
+        }
+        if block.is_none() {
+            code.is_code(line);
+        }
+        prev_blank = blank;
+
+        let masked = match block {
+            Some(Block::Esm) if blank => {
+                block = None;
+                false
+            }
+            Some(Block::Esm) => true,
+            Some(Block::Jsx(depth)) => {
+                let depth = depth + jsx_depth_change(line);
+                block = (depth > 0).then_some(Block::Jsx(depth));
+                true
+            }
+            Some(Block::Comment) => {
+                if line.contains("*/}") {
+                    block = None;
+                }
+                true
+            }
+            None => false,
+        };
+
+        if masked {
+            output.extend(std::iter::repeat_n(' ', line.len()));
+            output.push_str(&raw[line.len()..]);
+        } else {
+            output.push_str(raw);
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ParseOptions, parse_headings_with};
+
+    #[test]
+    fn test_mdx_headings() {
+        let content = "import { Tabs } from './tabs'\nexport const meta = {\n# not a heading\n}\n\n# Guide\n\n<Tabs>\n  <Tab label=\"npm\">\n## Inside\n  </Tab>\n</Tabs>\n<Card title=\"x\" />\nIntro\n---\n\n{/*\n## Commented\n*/}\n\n```jsx\n<Tabs>\n```\n\n## Usage\n";
+        let options = ParseOptions {
+            mdx: true,
+            ..Default::default()
+        };
+
+        let headings = parse_headings_with(content, &options);
+        let texts: Vec<_> = headings.iter().map(|h| h.text.as_str()).collect();
+        assert_eq!(texts, ["Guide", "Intro", "Usage"]);
+        assert_eq!(headings[2].line_number, 25);
+        assert_eq!(&content[headings[2].text_range.clone()], "Usage");
+
+        assert_eq!(mask_mdx(content).len(), content.len());
+    }
+}
@@ -0,0 +1,253 @@
+//! Three-way merging of documents at section granularity
+
+use crate::{Heading, parse_headings};
+use std::collections::HashMap;
+
+/// Result of [`merge_sections`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MergeResult {
+    /// The merged document, with conflict markers around conflicting sections
+    pub content: String,
+    /// Heading paths of the conflicting sections (empty path for the preamble)
+    pub conflicts: Vec<Vec<String>>,
+}
+
+impl MergeResult {
+    /// Whether the merge completed without conflicts
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+/// Identifies a section across versions: its heading path plus an occurrence
+/// counter for repeated paths
+type SectionKey = (Vec<String>, usize);
+
+/// A section's text and the whitespace that separated it from the next one
+#[derive(Clone, Copy)]
+struct Block<'a> {
+    text: &'a str,
+    separator: &'a str,
+}
+
+impl<'a> Block<'a> {
+    /// Split trailing whitespace off `content` so that blank lines between
+    /// sections don't count as changes
+    fn new(content: &'a str) -> Self {
+        let text = content.trim_end();
+        Block {
+            text,
+            separator: &content[text.len()..],
+        }
+    }
+}
+
+/// Split a document into keyed blocks of a heading and its own text (up to the
+/// next heading of any level); the text before the first heading has an empty path
+fn split_sections(content: &str) -> Vec<(SectionKey, Block<'_>)> {
+    let headings = parse_headings(content);
+    let mut blocks = Vec::with_capacity(headings.len() + 1);
+    let mut seen: HashMap<Vec<String>, usize> = HashMap::new();
+    let mut path: Vec<&Heading> = Vec::new();
+
+    let first = headings.first().map_or(content.len(), |h| h.byte_offset);
+    if first > 0 {
+        blocks.push(((Vec::new(), 0), Block::new(&content[..first])));
+    }
+
+    for (idx, h) in headings.iter().enumerate() {
+        while path.last().is_some_and(|last| last.level >= h.level) {
+            path.pop();
+        }
+        path.push(h);
+
+        let texts: Vec<String> = path.iter().map(|p| p.text.clone()).collect();
+        let count = seen.entry(texts.clone()).or_default();
+        let key = (texts, *count);
+        *count += 1;
+
+        let end = headings
+            .get(idx + 1)
+            .map_or(content.len(), |next| next.byte_offset);
+        blocks.push((key, Block::new(&content[h.byte_offset..end])));
+    }
+
+    blocks
+}
+
+/// Append a section after the whitespace that followed the previous one in its
+/// document, or a blank line if that section ended its document
+fn push_section(output: &mut String, separator: &str, eol: &str, text: &str) {
+    if !output.is_empty() {
+        if separator.matches('\n').count() >= 2 {
+            output.push_str(separator);
+        } else {
+            output.push_str(eol);
+            output.push_str(eol);
+        }
+    }
+    output.push_str(text);
+}
+
+/// Merge two edited versions of a document section by section
+///
+/// Sections are matched across `base`, `ours` and `theirs` by their heading
+/// path. A section changed on only one side takes that side's version, and
+/// sections added on either side are kept. Only when both sides changed the
+/// same section differently (or one edited what the other deleted) is it
+/// written with `<<<<<<< ours` / `=======` / `>>>>>>> theirs` markers and
+/// reported as a conflict.
+///
+/// The section order follows `ours`, unless only `theirs` reorganized the
+/// document, in which case its order is used. The blank lines after each section
+/// are kept from the version it was taken from, and conflict markers use the
+/// line endings of `ours`.
+pub fn merge_sections(base: &str, ours: &str, theirs: &str) -> MergeResult {
+    let base_blocks = split_sections(base);
+    let our_blocks = split_sections(ours);
+    let their_blocks = split_sections(theirs);
+
+    let base_map: HashMap<&SectionKey, Block> = base_blocks.iter().map(|(k, b)| (k, *b)).collect();
+    let our_map: HashMap<&SectionKey, Block> = our_blocks.iter().map(|(k, b)| (k, *b)).collect();
+    let their_map: HashMap<&SectionKey, Block> =
+        their_blocks.iter().map(|(k, b)| (k, *b)).collect();
+    let eol = if ours.contains("\r\n") { "\r\n" } else { "\n" };
+
+    // Keep whichever side reordered the sections they share with the base
+    let common_order = |blocks: &[(SectionKey, Block)]| -> Vec<SectionKey> {
+        blocks
+            .iter()
+            .filter(|(k, _)| base_map.contains_key(k))
+            .map(|(k, _)| k.clone())
+            .collect()
+    };
+    let base_order: Vec<SectionKey> = base_blocks.iter().map(|(k, _)| k.clone()).collect();
+    let ours_reordered = common_order(&our_blocks)
+        != base_order
+            .iter()
+            .filter(|k| our_map.contains_key(k))
+            .cloned()
+            .collect::<Vec<_>>();
+    let (primary, secondary) = if ours_reordered {
+        (&our_blocks, &their_blocks)
+    } else {
+        (&their_blocks, &our_blocks)
+    };
+
+    // Primary order, with sections only in the secondary side (or deleted by the
+    // primary side) placed after their predecessor there
+    let mut order: Vec<&SectionKey> = primary.iter().map(|(k, _)| k).collect();
+    for (idx, (key, _)) in secondary.iter().enumerate() {
+        if order.contains(&key) {
+            continue;
+        }
+        let position = secondary[..idx]
+            .iter()
+            .rev()
+            .find_map(|(prev, _)| order.iter().position(|k| *k == prev))
+            .map_or(0, |p| p + 1);
+        order.insert(position, key);
+    }
+
+    let mut content = String::with_capacity(ours.len().max(theirs.len()));
+    let mut conflicts = Vec::new();
+    let mut separator = "";
+
+    for key in order {
+        let o = our_map.get(key).copied();
+        let t = their_map.get(key).copied();
+        let [b_text, o_text, t_text] =
+            [base_map.get(key).copied(), o, t].map(|block| block.map(|b| b.text));
+
+        let merged = if o_text == t_text {
+            o
+        } else if o_text == b_text {
+            t
+        } else if t_text == b_text {
+            o
+        } else {
+            conflicts.push(key.0.clone());
+            push_section(
+                &mut content,
+                separator,
+                eol,
+                &format!(
+                    "<<<<<<< ours{eol}{}{eol}======={eol}{}{eol}>>>>>>> theirs",
+                    o_text.unwrap_or_default(),
+                    t_text.unwrap_or_default()
+                ),
+            );
+            separator = o.or(t).map_or("", |block| block.separator);
+            continue;
+        };
+
+        if let Some(block) = merged {
+            push_section(&mut content, separator, eol, block.text);
+            separator = block.separator;
+        }
+    }
+
+    if ours.ends_with('\n') {
+        content.push_str(eol);
+    }
+
+    MergeResult { content, conflicts }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: &str = "# Doc\n\nintro\n\n## A\n\na\n\n## B\n\nb\n\n## C\n\nc\n";
+
+    #[test]
+    fn test_merge_independent_changes() {
+        // Ours edits A and reorders C before B; theirs edits B and adds D
+        let ours = "# Doc\n\nintro\n\n## A\n\na edited\n\n## C\n\nc\n\n## B\n\nb\n";
+        let theirs = "# Doc\n\nintro\n\n## A\n\na\n\n## B\n\nb edited\n\n## C\n\nc\n\n## D\n\nd\n";
+
+        let merged = merge_sections(BASE, ours, theirs);
+        assert!(merged.is_clean());
+        assert_eq!(
+            merged.content,
+            "# Doc\n\nintro\n\n## A\n\na edited\n\n## C\n\nc\n\n## D\n\nd\n\n## B\n\nb edited\n"
+        );
+    }
+
+    #[test]
+    fn test_merge_conflict() {
+        let ours = BASE.replace("\nb\n", "\nb ours\n");
+        let theirs = BASE.replace("\nb\n", "\nb theirs\n");
+
+        let merged = merge_sections(BASE, &ours, &theirs);
+        assert_eq!(merged.conflicts, [vec!["Doc".to_string(), "B".to_string()]]);
+        assert!(
+            merged.content.contains(
+                "<<<<<<< ours\n## B\n\nb ours\n=======\n## B\n\nb theirs\n>>>>>>> theirs\n"
+            )
+        );
+    }
+
+    #[test]
+    fn test_merge_keeps_separators_and_line_endings() {
+        let base = "# Doc\r\n\r\n\r\n## A\r\n\r\na\r\n\r\n## B\r\n\r\nb\r\n";
+        let ours = base.replace("\na\r", "\na ours\r");
+        let theirs = base.replace("\nb\r", "\nb theirs\r");
+
+        let merged = merge_sections(base, &ours, &theirs);
+        assert!(merged.is_clean());
+        assert_eq!(
+            merged.content,
+            "# Doc\r\n\r\n\r\n## A\r\n\r\na ours\r\n\r\n## B\r\n\r\nb theirs\r\n"
+        );
+
+        let theirs = base.replace("\na\r", "\na theirs\r");
+        let merged = merge_sections(base, &ours, &theirs);
+        assert!(
+            merged
+                .content
+                .contains("<<<<<<< ours\r\n## A\r\n\r\na ours\r\n=======\r\n")
+        );
+    }
+}
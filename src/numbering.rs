@@ -0,0 +1,153 @@
+//! Hierarchical section numbering (`1`, `1.2`, `1.2.3`)
+
+use crate::Heading;
+
+/// Compute the section number of every heading
+///
+/// Headings shallower than `min_level` are left unnumbered (`None`), which lets a
+/// document title at level 1 stay out of the numbering with `min_level = 2`. A
+/// skipped level counts as `0`, so an H4 directly below an H2 numbered `1` gets
+/// `1.0.1`. Headings with the Pandoc `.unnumbered` class (or `{-}`) get `None`
+/// and don't advance the numbering.
+pub fn section_numbers(headings: &[Heading], min_level: u8) -> Vec<Option<String>> {
+    let min_level = min_level.clamp(1, 6);
+    let mut counters = [0_usize; 6];
+
+    headings
+        .iter()
+        .map(|h| {
+            if h.level < min_level {
+                // A new parent above the numbered range restarts the numbering
+                counters = [0; 6];
+                return None;
+            }
+            if h.has_class("unnumbered") {
+                return None;
+            }
+
+            let depth = (h.level - min_level) as usize;
+            counters[depth] += 1;
+            counters[depth + 1..].fill(0);
+            let parts: Vec<String> = counters[..=depth].iter().map(|n| n.to_string()).collect();
+            Some(parts.join("."))
+        })
+        .collect()
+}
+
+/// Length of a leading section number in the form [`apply_section_numbers`]
+/// writes (`3. `, `1.2. `) with `parts` numbers, including the spaces after it
+///
+/// Anything else, such as the year in `2024 Roadmap` or a number at another
+/// depth, is part of the heading's text.
+fn number_prefix_len(text: &str, parts: usize) -> usize {
+    let mut len = 0;
+    for _ in 0..parts {
+        let digits = text[len..]
+            .bytes()
+            .take_while(|b| b.is_ascii_digit())
+            .count();
+        if digits == 0 || text.as_bytes().get(len + digits) != Some(&b'.') {
+            return 0;
+        }
+        len += digits + 1;
+    }
+
+    let rest = &text[len..];
+    if !rest.starts_with(' ') {
+        return 0;
+    }
+    len + (rest.len() - rest.trim_start().len())
+}
+
+/// Rewrite heading text to start with its section number
+///
+/// Numbers are written with a trailing dot (`1. Scope`, `1.2. Terms`). A
+/// number of that form and the heading's depth at the start of a heading is
+/// replaced, so running this again after an edit refreshes the numbering, while
+/// titles that merely start with digits are left alone. Headings shallower than `min_level`
+/// are left unchanged, as in [`section_numbers`].
+pub fn apply_section_numbers(content: &str, headings: &[Heading], min_level: u8) -> String {
+    let numbers = section_numbers(headings, min_level);
+    let mut output = String::with_capacity(content.len());
+    let mut pos = 0;
+
+    for (h, number) in headings.iter().zip(numbers) {
+        let Some(number) = number else {
+            continue;
+        };
+
+        // The line may start with list or block quote markers, so number the
+        // text where the parser found it
+        let start = h.text_range.start;
+        let parts = number.split('.').count();
+        let old_number = number_prefix_len(&content[start..h.byte_range().end], parts);
+        output.push_str(&content[pos..start]);
+        output.push_str(&number);
+        output.push_str(". ");
+        pos = start + old_number;
+    }
+
+    output.push_str(&content[pos..]);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_headings;
+
+    #[test]
+    fn test_section_numbers() {
+        let content = "# Spec\n\n## Scope\n\n## Terms\n\n### Words\n\n#### Deep\n\n## Rules\n\n#### Skipped\n";
+        let headings = parse_headings(content);
+
+        let numbers = section_numbers(&headings, 2);
+        assert_eq!(
+            numbers,
+            [
+                None,
+                Some("1".to_string()),
+                Some("2".to_string()),
+                Some("2.1".to_string()),
+                Some("2.1.1".to_string()),
+                Some("3".to_string()),
+                Some("3.0.1".to_string()),
+            ]
+        );
+
+        let headings =
+            parse_headings("## Scope\n\n## Notes {-}\n\n## Rules {.unnumbered}\n\n## Terms\n");
+        let numbers = section_numbers(&headings, 2);
+        assert_eq!(
+            numbers,
+            [Some("1".to_string()), None, None, Some("2".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_apply_section_numbers_refreshes() {
+        let content = "# Spec\n\n## 2. Scope\n\n## Terms\n\nTerms\n-----\n";
+        let headings = parse_headings(content);
+
+        let numbered = apply_section_numbers(content, &headings, 2);
+        assert_eq!(
+            numbered,
+            "# Spec\n\n## 1. Scope\n\n## 2. Terms\n\n3. Terms\n-----\n"
+        );
+
+        let again = apply_section_numbers(&numbered, &parse_headings(&numbered), 2);
+        assert_eq!(again, numbered);
+    }
+
+    #[test]
+    fn test_apply_section_numbers_keeps_numeric_titles() {
+        let content = "## 2024 Roadmap\n\n### 1.1. Goals\n\n### 1. Step one\n\n## 3 Musketeers\n";
+        let headings = parse_headings(content);
+
+        let numbered = apply_section_numbers(content, &headings, 2);
+        assert_eq!(
+            numbered,
+            "## 1. 2024 Roadmap\n\n### 1.1. Goals\n\n### 1.2. 1. Step one\n\n## 2. 3 Musketeers\n"
+        );
+    }
+}
@@ -0,0 +1,106 @@
+//! Nested outline for export to other tools
+
+use crate::Heading;
+use crate::tree::{Section, SectionTree};
+
+/// A heading with the headings nested below it
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OutlineNode {
+    /// Line number of the heading (1-indexed)
+    pub line_number: usize,
+    /// Heading level (1-6)
+    pub level: u8,
+    /// The heading text
+    pub text: String,
+    /// Explicit identifier from a `{#id}` attribute
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub id: Option<String>,
+    /// Headings nested directly below this one
+    pub children: Vec<OutlineNode>,
+}
+
+impl OutlineNode {
+    fn from_section(section: Section<'_>) -> Self {
+        let Heading {
+            line_number,
+            level,
+            text,
+            id,
+            ..
+        } = section.heading().clone();
+        Self {
+            line_number,
+            level,
+            text,
+            id,
+            children: section.children().map(Self::from_section).collect(),
+        }
+    }
+}
+
+/// Nest headings into a tree of [`OutlineNode`]s, one per top-level heading
+///
+/// Headings nest the same way as in [`SectionTree`].
+pub fn nested_outline(headings: &[Heading]) -> Vec<OutlineNode> {
+    SectionTree::from_headings(headings)
+        .roots()
+        .map(OutlineNode::from_section)
+        .collect()
+}
+
+/// Serialize the nested outline as pretty-printed JSON
+#[cfg(feature = "json")]
+pub fn outline_to_json(headings: &[Heading]) -> Result<String, serde_json::Error> {
+    serde_json::to_string_pretty(&nested_outline(headings))
+}
+
+/// Serialize the nested outline as YAML
+#[cfg(feature = "yaml")]
+pub fn outline_to_yaml(headings: &[Heading]) -> Result<String, serde_yaml::Error> {
+    serde_yaml::to_string(&nested_outline(headings))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_headings;
+
+    #[test]
+    fn test_nested_outline() {
+        let headings =
+            parse_headings("# Guide\n\n## Install {#setup}\n\n### Linux\n\n# Appendix\n");
+        let outline = nested_outline(&headings);
+
+        assert_eq!(outline.len(), 2);
+        assert_eq!(outline[0].children[0].id.as_deref(), Some("setup"));
+        assert_eq!(outline[0].children[0].children[0].text, "Linux");
+        assert!(outline[1].children.is_empty());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_outline_to_json() {
+        let headings = parse_headings("# Guide\n\n## Install\n");
+        let json: serde_json::Value =
+            serde_json::from_str(&outline_to_json(&headings).unwrap()).unwrap();
+
+        assert_eq!(json[0]["text"], "Guide");
+        assert_eq!(json[0]["children"][0]["line_number"], 3);
+        assert_eq!(json[0]["children"][0]["level"], 2);
+        assert!(json[0].get("id").is_none());
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn test_outline_to_yaml() {
+        let headings = parse_headings("# Guide\n\n## Install\n");
+        let yaml = outline_to_yaml(&headings).unwrap();
+        let restored: Vec<OutlineNode> = serde_yaml::from_str(&yaml).unwrap();
+
+        assert_eq!(restored, nested_outline(&headings));
+    }
+}
@@ -0,0 +1,141 @@
+//! Structured (JSON/YAML) output for `--format`
+//!
+//! The types here are only read by serde, so builds without JSON or YAML support
+//! see their fields as unused.
+#![cfg_attr(not(any(feature = "json", feature = "yaml")), allow(dead_code))]
+
+use anyhow::Result;
+use clap::ValueEnum;
+#[cfg(feature = "regex")]
+use markdown_inspector::GrepHit;
+use markdown_inspector::{Heading, LintKind, get_section_range};
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable text
+    #[value(alias = "table")]
+    Text,
+    /// Pretty-printed JSON (requires the `json` feature)
+    Json,
+    /// YAML (requires the `yaml` feature)
+    Yaml,
+}
+
+/// A section in structured output
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SectionEntry<'a> {
+    pub line_number: usize,
+    pub level: u8,
+    pub text: &'a str,
+    /// First line after the section, or none if it runs to the end of the document
+    pub end_line: Option<usize>,
+    /// Deeper headings hidden by `outline --depth --counts`
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub hidden: Option<usize>,
+    /// What `read` prints for the section
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub content: Option<String>,
+}
+
+impl<'a> SectionEntry<'a> {
+    pub fn new(headings: &[Heading], heading: &'a Heading) -> Self {
+        Self {
+            line_number: heading.line_number,
+            level: heading.level,
+            text: &heading.text,
+            end_line: get_section_range(headings, heading).1,
+            hidden: None,
+            content: None,
+        }
+    }
+}
+
+/// Structured output for one file of several
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FileEntry<T> {
+    pub file: String,
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub value: T,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SectionList<'a> {
+    pub sections: Vec<SectionEntry<'a>>,
+}
+
+#[cfg(feature = "regex")]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct HitList {
+    pub hits: Vec<GrepHit>,
+}
+
+#[cfg(feature = "regex")]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct HitCount {
+    pub count: usize,
+}
+
+/// A heading's anchor in structured output
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct AnchorEntry<'a> {
+    pub line_number: usize,
+    pub text: &'a str,
+    /// The anchor links must use, after disambiguating duplicates
+    pub anchor: &'a str,
+    /// Whether the anchor comes from a `{#id}` attribute
+    pub explicit: bool,
+    /// First heading with the same base anchor, if this one collides with it
+    pub collides_with: Option<usize>,
+}
+
+/// A broken link in structured output
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct BrokenEntry<'a> {
+    pub line_number: usize,
+    pub destination: &'a str,
+    pub reason: &'a str,
+}
+
+/// A lint finding in structured output
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LintEntry {
+    pub file: String,
+    pub line_number: usize,
+    pub message: String,
+    pub kind: LintKind,
+}
+
+/// Print a value as JSON or YAML
+#[cfg(any(feature = "json", feature = "yaml"))]
+pub fn print_structured<T: serde::Serialize>(format: OutputFormat, value: &T) -> Result<()> {
+    match format {
+        #[cfg(feature = "json")]
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(value)?),
+        #[cfg(not(feature = "json"))]
+        OutputFormat::Json => {
+            anyhow::bail!("--format json requires mdi to be built with the `json` feature")
+        }
+        #[cfg(feature = "yaml")]
+        OutputFormat::Yaml => print!("{}", serde_yaml::to_string(value)?),
+        #[cfg(not(feature = "yaml"))]
+        OutputFormat::Yaml => {
+            anyhow::bail!("--format yaml requires mdi to be built with the `yaml` feature")
+        }
+        OutputFormat::Text => anyhow::bail!("text output has no structured form"),
+    }
+    Ok(())
+}
+
+#[cfg(not(any(feature = "json", feature = "yaml")))]
+pub fn print_structured<T>(format: OutputFormat, _value: &T) -> Result<()> {
+    let feature = if format == OutputFormat::Yaml {
+        "yaml"
+    } else {
+        "json"
+    };
+    anyhow::bail!(
+        "--format {} requires mdi to be built with the `{}` feature",
+        feature,
+        feature
+    )
+}
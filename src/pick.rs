@@ -0,0 +1,114 @@
+//! Interactive fuzzy section picker for `mdi pick`
+
+use anyhow::Result;
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute, queue,
+    style::{Attribute, Print, SetAttribute},
+    terminal::{self, ClearType},
+};
+use markdown_inspector::{Heading, format_outline_entry, rank_sections};
+use std::io::{self, Write};
+
+/// Restores the terminal when the picker exits, including on early returns
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn enter() -> Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), terminal::EnterAlternateScreen)?;
+        Ok(Self)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+fn draw(query: &str, matches: &[&Heading], selected: usize) -> Result<()> {
+    let mut out = io::stdout();
+    let (width, height) = terminal::size()?;
+    let visible = (height as usize).saturating_sub(1);
+
+    // Keep the selection in view
+    let offset = selected.saturating_sub(visible.saturating_sub(1));
+
+    queue!(
+        out,
+        cursor::MoveTo(0, 0),
+        terminal::Clear(ClearType::All),
+        Print(format!("> {}", query))
+    )?;
+
+    for (row, heading) in matches.iter().skip(offset).take(visible).enumerate() {
+        let entry: String = format_outline_entry(heading)
+            .chars()
+            .take(width as usize)
+            .collect();
+        queue!(out, cursor::MoveTo(0, row as u16 + 1))?;
+        if offset + row == selected {
+            queue!(
+                out,
+                SetAttribute(Attribute::Reverse),
+                Print(entry),
+                SetAttribute(Attribute::Reset)
+            )?;
+        } else {
+            queue!(out, Print(entry))?;
+        }
+    }
+
+    queue!(out, cursor::MoveTo(2 + query.chars().count() as u16, 0))?;
+    out.flush()?;
+    Ok(())
+}
+
+/// Let the user filter headings interactively and pick one
+///
+/// Returns `None` when the picker is dismissed with Esc or Ctrl-C.
+pub fn pick_heading(headings: &[Heading]) -> Result<Option<Heading>> {
+    let _guard = TerminalGuard::enter()?;
+    let mut query = String::new();
+    let mut selected = 0;
+
+    loop {
+        let matches = rank_sections(headings, &query);
+        selected = selected.min(matches.len().saturating_sub(1));
+        draw(&query, &matches, selected)?;
+
+        let Event::Key(KeyEvent {
+            code,
+            modifiers,
+            kind: KeyEventKind::Press,
+            ..
+        }) = event::read()?
+        else {
+            continue;
+        };
+
+        match code {
+            KeyCode::Esc => return Ok(None),
+            KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => return Ok(None),
+            KeyCode::Enter => return Ok(matches.get(selected).map(|h| (*h).clone())),
+            KeyCode::Up => selected = selected.saturating_sub(1),
+            KeyCode::Char('p') if modifiers.contains(KeyModifiers::CONTROL) => {
+                selected = selected.saturating_sub(1)
+            }
+            KeyCode::Down => selected += 1,
+            KeyCode::Char('n') if modifiers.contains(KeyModifiers::CONTROL) => selected += 1,
+            KeyCode::Backspace => {
+                query.pop();
+                selected = 0;
+            }
+            KeyCode::Char(c) => {
+                query.push(c);
+                selected = 0;
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,86 @@
+//! Plain-text rendering of markdown content
+
+use crate::front_matter::parse_front_matter;
+use crate::inline::strip_inline_markdown;
+use crate::scan::{CodeBlockTracker, indent_width, parse_atx, split_heading_attributes};
+use crate::tasks::{parse_checkbox, split_list_marker};
+
+/// Strip any number of leading `>` block quote markers
+fn strip_quote_markers(line: &str) -> &str {
+    let mut rest = line;
+    while indent_width(rest) <= 3
+        && let Some(inner) = rest.trim_start().strip_prefix('>')
+    {
+        rest = inner.strip_prefix(' ').unwrap_or(inner);
+    }
+    rest
+}
+
+/// Whether a line is a thematic break (`---`, `* * *`) or setext underline (`===`)
+pub(crate) fn is_rule(line: &str) -> bool {
+    if indent_width(line) > 3 {
+        return false;
+    }
+
+    let marks: Vec<char> = line.chars().filter(|c| !c.is_whitespace()).collect();
+    match marks.first() {
+        Some('=') => marks.iter().all(|&c| c == '='),
+        Some(&m @ ('-' | '*' | '_')) => marks.len() >= 3 && marks.iter().all(|&c| c == m),
+        _ => false,
+    }
+}
+
+/// Render markdown as plain text, keeping only what a reader would see
+///
+/// Heading marks and attribute blocks, list markers and task checkboxes, block
+/// quote markers, thematic breaks, setext underlines, code fence lines and front
+/// matter are removed; inline syntax is stripped as in
+/// [`strip_inline_markdown`]. Code block contents are kept verbatim. Lines are
+/// joined with `\n`, and blank lines are kept so paragraphs stay separated.
+pub fn to_plain_text(content: &str) -> String {
+    let skip = parse_front_matter(content).map_or(0, |fm| fm.end_line);
+    let mut code = CodeBlockTracker::default();
+    let mut lines = Vec::new();
+
+    for line in content.lines().skip(skip) {
+        let line = strip_quote_markers(line);
+        let was_fenced = code.in_fence();
+        if code.is_code(line) {
+            // Fence lines open or close a block; everything else is code
+            if was_fenced == code.in_fence() {
+                lines.push(line.to_string());
+            }
+            continue;
+        }
+
+        if is_rule(line) {
+            continue;
+        }
+
+        let text = if let Some((_, text)) = parse_atx(line) {
+            split_heading_attributes(text).0
+        } else if let Some((_, item)) = split_list_marker(line) {
+            parse_checkbox(item).map_or(item, |(_, text)| text)
+        } else {
+            line.trim()
+        };
+        lines.push(strip_inline_markdown(text.trim()));
+    }
+
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_plain_text() {
+        let content = "---\ntitle: Guide\n---\n# Guide {#top}\n\nSome **bold** and [a link](x.md).\n\n- [x] Done\n1. First\n\n> Quoted _text_\n\n```rust\nlet x = *y;\n```\n\nSetext\n------\n\n***\n";
+
+        assert_eq!(
+            to_plain_text(content),
+            "Guide\n\nSome bold and a link.\n\nDone\nFirst\n\nQuoted text\n\nlet x = *y;\n\nSetext\n"
+        );
+    }
+}
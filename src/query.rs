@@ -0,0 +1,314 @@
+//! A small selector language for headings
+//!
+//! ```text
+//! h2                          all level-2 headings
+//! *[text~="install"]          any heading whose text contains "install" (case-insensitive)
+//! h1 > h2:first               the first H2 directly below each H1
+//! h1[text="Config"] h3        every H3 anywhere below the H1 titled "Config"
+//! ```
+
+use crate::Heading;
+use anyhow::{Result, bail};
+
+/// Attribute comparison inside `[...]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum AttrOp {
+    /// `=`: exact match
+    Equals,
+    /// `~=`: case-insensitive substring
+    Contains,
+    /// `^=`: prefix
+    StartsWith,
+    /// `$=`: suffix
+    EndsWith,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Attr {
+    Text,
+    Id,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct AttrFilter {
+    attr: Attr,
+    op: AttrOp,
+    value: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pseudo {
+    First,
+    Last,
+}
+
+/// A single selector like `h2[text~="x"]:first`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Compound {
+    level: Option<u8>,
+    filters: Vec<AttrFilter>,
+    pseudo: Option<Pseudo>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Combinator {
+    Descendant,
+    Child,
+}
+
+impl AttrFilter {
+    fn matches(&self, heading: &Heading) -> bool {
+        let value = match self.attr {
+            Attr::Text => heading.text.as_str(),
+            Attr::Id => match &heading.id {
+                Some(id) => id.as_str(),
+                None => return false,
+            },
+        };
+
+        match self.op {
+            AttrOp::Equals => value == self.value,
+            AttrOp::Contains => value.to_lowercase().contains(&self.value.to_lowercase()),
+            AttrOp::StartsWith => value.starts_with(&self.value),
+            AttrOp::EndsWith => value.ends_with(&self.value),
+        }
+    }
+}
+
+impl Compound {
+    fn matches(&self, heading: &Heading) -> bool {
+        self.level.is_none_or(|level| heading.level == level)
+            && self.filters.iter().all(|f| f.matches(heading))
+    }
+}
+
+/// Parse a quoted or bare attribute value, returning it and the rest of the input
+fn parse_value(input: &str) -> Result<(String, &str)> {
+    if let Some(rest) = input.strip_prefix(['"', '\'']) {
+        let quote = input.chars().next().unwrap_or('"');
+        let Some(end) = rest.find(quote) else {
+            bail!("Unterminated string in selector");
+        };
+        return Ok((rest[..end].to_string(), &rest[end + 1..]));
+    }
+
+    let end = input.find(']').unwrap_or(input.len());
+    Ok((input[..end].trim().to_string(), &input[end..]))
+}
+
+/// Parse one compound selector from the start of `input`
+fn parse_compound(input: &str) -> Result<(Compound, &str)> {
+    let mut compound = Compound::default();
+    let mut rest = input;
+
+    if let Some(r) = rest.strip_prefix('*') {
+        rest = r;
+    } else if let Some(r) = rest.strip_prefix(['h', 'H']) {
+        let digit = r.chars().next().filter(|c| ('1'..='6').contains(c));
+        let Some(digit) = digit else {
+            bail!("Expected a heading level h1-h6 in selector: {}", input);
+        };
+        compound.level = Some(digit as u8 - b'0');
+        rest = &r[1..];
+    } else if !rest.starts_with(['[', ':']) {
+        bail!("Unexpected selector: {}", input);
+    }
+
+    loop {
+        if let Some(r) = rest.strip_prefix('[') {
+            let name_end = r
+                .find(|c: char| !c.is_ascii_alphabetic())
+                .unwrap_or(r.len());
+            let attr = match &r[..name_end] {
+                "text" => Attr::Text,
+                "id" => Attr::Id,
+                other => bail!("Unknown attribute in selector: {}", other),
+            };
+            let r = &r[name_end..];
+            let (op, r) = if let Some(r) = r.strip_prefix("~=") {
+                (AttrOp::Contains, r)
+            } else if let Some(r) = r.strip_prefix("^=") {
+                (AttrOp::StartsWith, r)
+            } else if let Some(r) = r.strip_prefix("$=") {
+                (AttrOp::EndsWith, r)
+            } else if let Some(r) = r.strip_prefix('=') {
+                (AttrOp::Equals, r)
+            } else {
+                bail!("Expected =, ~=, ^= or $= after attribute name");
+            };
+            let (value, r) = parse_value(r)?;
+            let Some(r) = r.strip_prefix(']') else {
+                bail!("Expected ] in selector");
+            };
+            compound.filters.push(AttrFilter { attr, op, value });
+            rest = r;
+        } else if let Some(r) = rest.strip_prefix(':') {
+            let name_end = r
+                .find(|c: char| !c.is_ascii_alphabetic())
+                .unwrap_or(r.len());
+            compound.pseudo = Some(match &r[..name_end] {
+                "first" => Pseudo::First,
+                "last" => Pseudo::Last,
+                other => bail!("Unknown pseudo-class in selector: :{}", other),
+            });
+            rest = &r[name_end..];
+        } else {
+            return Ok((compound, rest));
+        }
+    }
+}
+
+/// Parse a full selector into compounds joined by combinators
+fn parse_selector(expr: &str) -> Result<Vec<(Combinator, Compound)>> {
+    let mut steps = Vec::new();
+    let mut rest = expr.trim();
+    let mut combinator = Combinator::Descendant;
+
+    while !rest.is_empty() {
+        let (compound, r) = parse_compound(rest)?;
+        steps.push((combinator, compound));
+
+        let trimmed = r.trim_start();
+        combinator = match trimmed.strip_prefix('>') {
+            Some(after) => {
+                rest = after.trim_start();
+                if rest.is_empty() {
+                    bail!("Selector ends with a combinator");
+                }
+                Combinator::Child
+            }
+            None => {
+                rest = trimmed;
+                Combinator::Descendant
+            }
+        };
+    }
+
+    if steps.is_empty() {
+        bail!("Empty selector");
+    }
+    Ok(steps)
+}
+
+/// Select headings with a CSS-like selector expression
+///
+/// Supported syntax:
+/// - `h1`-`h6` match a level, `*` matches any heading
+/// - `[text="..."]` exact text, `[text~="..."]` case-insensitive substring,
+///   `[text^="..."]` prefix, `[text$="..."]` suffix; `[id=...]` tests the
+///   explicit `{#id}`
+/// - `:first` / `:last` keep only the first or last match below each parent
+/// - `a b` selects `b` anywhere below `a`, `a > b` only direct children
+///
+/// Returns matches in document order, or an error for a malformed expression.
+pub fn query<'a>(headings: &'a [Heading], expr: &str) -> Result<Vec<&'a Heading>> {
+    let steps = parse_selector(expr)?;
+
+    // Parent index of every heading
+    let mut parents: Vec<Option<usize>> = Vec::with_capacity(headings.len());
+    let mut stack: Vec<usize> = Vec::new();
+    for (idx, h) in headings.iter().enumerate() {
+        while stack.last().is_some_and(|&p| headings[p].level >= h.level) {
+            stack.pop();
+        }
+        parents.push(stack.last().copied());
+        stack.push(idx);
+    }
+
+    let mut selected: Option<Vec<bool>> = None;
+    for (combinator, compound) in &steps {
+        let related = |idx: usize| match &selected {
+            None => true,
+            Some(prev) => match combinator {
+                Combinator::Child => parents[idx].is_some_and(|p| prev[p]),
+                Combinator::Descendant => {
+                    let mut cursor = parents[idx];
+                    while let Some(p) = cursor {
+                        if prev[p] {
+                            return true;
+                        }
+                        cursor = parents[p];
+                    }
+                    false
+                }
+            },
+        };
+
+        let mut matched: Vec<bool> = (0..headings.len())
+            .map(|idx| compound.matches(&headings[idx]) && related(idx))
+            .collect();
+
+        if let Some(pseudo) = compound.pseudo {
+            // Keep one match per parent
+            let mut kept: Vec<(Option<usize>, usize)> = Vec::new();
+            for idx in (0..headings.len()).filter(|&i| matched[i]) {
+                match kept.iter_mut().find(|(p, _)| *p == parents[idx]) {
+                    Some(entry) if pseudo == Pseudo::Last => entry.1 = idx,
+                    Some(_) => {}
+                    None => kept.push((parents[idx], idx)),
+                }
+            }
+            matched = vec![false; headings.len()];
+            for (_, idx) in kept {
+                matched[idx] = true;
+            }
+        }
+
+        selected = Some(matched);
+    }
+
+    let selected = selected.unwrap_or_default();
+    Ok(headings
+        .iter()
+        .zip(selected)
+        .filter(|(_, keep)| *keep)
+        .map(|(h, _)| h)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_headings;
+
+    fn texts(headings: &[&Heading]) -> Vec<String> {
+        headings.iter().map(|h| h.text.clone()).collect()
+    }
+
+    #[test]
+    fn test_query_selectors() {
+        let headings = parse_headings(
+            "# Guide\n## Install\n### Linux\n## Configuration\n### Files\n### Env {#env}\n# Reference\n## Install notes\n",
+        );
+
+        assert_eq!(
+            texts(&query(&headings, "h2").unwrap()),
+            ["Install", "Configuration", "Install notes"]
+        );
+        assert_eq!(
+            texts(&query(&headings, "*[text~=\"INSTALL\"]").unwrap()),
+            ["Install", "Install notes"]
+        );
+        assert_eq!(
+            texts(&query(&headings, "h1 > h2:first").unwrap()),
+            ["Install", "Install notes"]
+        );
+        assert_eq!(
+            texts(&query(&headings, "h2[text=Configuration] h3:last").unwrap()),
+            ["Env"]
+        );
+        assert_eq!(
+            texts(&query(&headings, "h1[text^='Gui'] [id=env]").unwrap()),
+            ["Env"]
+        );
+    }
+
+    #[test]
+    fn test_query_errors() {
+        let headings = parse_headings("# A\n");
+        assert!(query(&headings, "h7").is_err());
+        assert!(query(&headings, "h1 >").is_err());
+        assert!(query(&headings, "h1[title=x]").is_err());
+        assert!(query(&headings, "h1:nth").is_err());
+    }
+}
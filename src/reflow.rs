@@ -0,0 +1,177 @@
+//! Rewrapping section prose to a fixed width
+
+use crate::Heading;
+use crate::edit::{line_ending_at, section_body_range};
+use crate::plain::is_rule;
+use crate::scan::{CodeBlockTracker, parse_atx, strip_line_ending};
+use crate::tasks::{parse_checkbox, split_list_marker};
+
+/// Whether a line must be kept as written
+fn is_verbatim(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    trimmed.is_empty()
+        || parse_atx(line).is_some()
+        || is_rule(line)
+        || trimmed.starts_with(['>', '<', '|'])
+        || line.contains('|')
+        || (trimmed.starts_with('[') && trimmed.contains("]:"))
+}
+
+/// Whether a wrapped line starting with `word` would be read as block syntax
+/// (a heading, list item, quote, fence or setext underline)
+fn starts_block(word: &str) -> bool {
+    let digits = word.len() - word.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    word.starts_with(['#', '>', '-', '+', '*', '=', '`', '~', '|', '<'])
+        || (digits > 0 && word[digits..].starts_with(['.', ')']))
+}
+
+/// Rewrap a paragraph (or list item) given as its source lines
+fn wrap_paragraph(lines: &[&str], width: usize, eol: &str, out: &mut String) {
+    let Some(first) = lines.first() else {
+        return;
+    };
+
+    // List items wrap with a hanging indent under their text
+    let text = match split_list_marker(first) {
+        Some((_, item)) => parse_checkbox(item).map_or(item, |(_, rest)| rest),
+        None => first.trim_start(),
+    };
+    let first_prefix = &first[..first.len() - text.len()];
+    let rest_prefix = if split_list_marker(first).is_some() {
+        " ".repeat(first_prefix.chars().count())
+    } else {
+        first_prefix.to_string()
+    };
+    let hard_break = lines.last().is_some_and(|l| l.ends_with("  "));
+
+    let words = text
+        .split_whitespace()
+        .chain(lines[1..].iter().flat_map(|l| l.split_whitespace()));
+    let mut line = first_prefix.to_string();
+    let mut len = line.chars().count();
+    let mut empty = true;
+    for word in words {
+        let word_len = word.chars().count();
+        if !empty && len + 1 + word_len > width && !starts_block(word) {
+            out.push_str(&line);
+            out.push_str(eol);
+            line = rest_prefix.clone();
+            len = line.chars().count();
+            empty = true;
+        }
+        if !empty {
+            line.push(' ');
+            len += 1;
+        }
+        line.push_str(word);
+        len += word_len;
+        empty = false;
+    }
+
+    out.push_str(&line);
+    if hard_break {
+        out.push_str("  ");
+    }
+    out.push_str(eol);
+}
+
+/// Rewrap the prose in `text`, leaving everything else as written
+fn reflow_text(text: &str, width: usize, eol: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut code = CodeBlockTracker::default();
+    let mut paragraph: Vec<&str> = Vec::new();
+
+    for raw in text.split_inclusive('\n') {
+        let line = strip_line_ending(raw);
+        let is_setext_underline = !paragraph.is_empty()
+            && !line.trim().is_empty()
+            && (line.trim().chars().all(|c| c == '=') || line.trim().chars().all(|c| c == '-'));
+
+        if code.is_code(line) {
+            wrap_paragraph(&paragraph, width, eol, &mut out);
+            paragraph.clear();
+            out.push_str(raw);
+        } else if is_setext_underline {
+            // The paragraph is a setext heading's text
+            for heading_line in paragraph.drain(..) {
+                out.push_str(heading_line);
+                out.push_str(eol);
+            }
+            out.push_str(raw);
+        } else if is_verbatim(line) {
+            wrap_paragraph(&paragraph, width, eol, &mut out);
+            paragraph.clear();
+            out.push_str(raw);
+        } else {
+            if split_list_marker(line).is_some() {
+                wrap_paragraph(&paragraph, width, eol, &mut out);
+                paragraph.clear();
+            }
+            paragraph.push(line);
+            if line.ends_with("  ") || line.ends_with('\\') {
+                wrap_paragraph(&paragraph, width, eol, &mut out);
+                paragraph.clear();
+            }
+        }
+    }
+    wrap_paragraph(&paragraph, width, eol, &mut out);
+
+    if !text.ends_with('\n') && out.ends_with('\n') {
+        out.truncate(out.len() - eol.len());
+    }
+    out
+}
+
+/// Rewrap the paragraphs of a section to `width` columns
+///
+/// Paragraphs and list items (with a hanging indent) in the section body,
+/// including its subsections, are refilled. Headings, code blocks, tables, block
+/// quotes, HTML, thematic breaks and link reference definitions are left as
+/// written, and hard line breaks are preserved. Words longer than `width` are
+/// not split, and a line is never broken before a word that would turn the next
+/// line into a different block (such as `- ` or `#`).
+pub fn reflow_section(
+    content: &str,
+    headings: &[Heading],
+    heading: &Heading,
+    width: usize,
+) -> String {
+    let eol = line_ending_at(content, heading.byte_offset);
+    let (start, end) = section_body_range(content, headings, heading);
+
+    let mut output = String::with_capacity(content.len());
+    output.push_str(&content[..start]);
+    output.push_str(&reflow_text(&content[start..end], width, eol));
+    output.push_str(&content[end..]);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_headings;
+
+    #[test]
+    fn test_reflow_section() {
+        let content = "# Intro\n\nThis line stays as it is even though it is long.\n\n## Usage\n\nRun the tool with\nthe file and a section name to print it.\n\n    code that should never be wrapped at all\n\n- [ ] An item that is long enough to wrap\n\n| a | b |\n|---|---|\n\nSetext title that is long\n---\n";
+        let headings = parse_headings(content);
+
+        let reflowed = reflow_section(content, &headings, &headings[1], 20);
+        assert_eq!(
+            reflowed,
+            "# Intro\n\nThis line stays as it is even though it is long.\n\n## Usage\n\nRun the tool with\nthe file and a\nsection name to\nprint it.\n\n    code that should never be wrapped at all\n\n- [ ] An item that\n      is long enough\n      to wrap\n\n| a | b |\n|---|---|\n\nSetext title that is long\n---\n"
+        );
+        assert_eq!(parse_headings(&reflowed).len(), headings.len());
+    }
+
+    #[test]
+    fn test_reflow_keeps_block_syntax_at_line_starts() {
+        let content = "# A\r\n\r\nSee item\r\n- 1 here  \r\nnext line";
+        let headings = parse_headings(content);
+
+        assert_eq!(
+            reflow_section(content, &headings, &headings[0], 8),
+            "# A\r\n\r\nSee item\r\n- 1 here  \r\nnext\r\nline"
+        );
+    }
+}
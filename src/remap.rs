@@ -0,0 +1,113 @@
+//! Carrying line numbers across edits without reparsing
+
+use crate::Heading;
+
+/// A line-based edit: `removed` lines starting at `line` replaced by `inserted` lines
+///
+/// A pure insertion has `removed == 0` and places the new lines before `line`; a
+/// pure deletion has `inserted == 0`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LineEdit {
+    /// First affected line (1-indexed)
+    pub line: usize,
+    /// Number of lines removed
+    pub removed: usize,
+    /// Number of lines inserted in their place
+    pub inserted: usize,
+}
+
+impl LineEdit {
+    /// Insert `count` lines before `line`
+    pub fn insert(line: usize, count: usize) -> Self {
+        Self {
+            line,
+            removed: 0,
+            inserted: count,
+        }
+    }
+
+    /// Delete `count` lines starting at `line`
+    pub fn delete(line: usize, count: usize) -> Self {
+        Self {
+            line,
+            removed: count,
+            inserted: 0,
+        }
+    }
+}
+
+/// Map a line number through a sequence of edits
+///
+/// Edits are applied in order, each in the line numbers produced by the ones
+/// before it. Returns `None` if the line was removed or replaced.
+pub fn remap_line(line: usize, edits: &[LineEdit]) -> Option<usize> {
+    edits.iter().try_fold(line, |line, edit| {
+        if line < edit.line {
+            Some(line)
+        } else if line < edit.line.saturating_add(edit.removed) {
+            None
+        } else {
+            // `line` is past the removed lines, so it's at least `edit.removed`
+            Some((line - edit.removed).saturating_add(edit.inserted))
+        }
+    })
+}
+
+/// Update headings parsed before a sequence of edits to the edited document
+///
+/// Line numbers are shifted as in [`remap_line`] and headings on removed lines are
+/// dropped. Headings added by the edits are not discovered, and byte offsets are
+/// left as they were, so reparse when those matter.
+pub fn remap_headings(headings: &[Heading], edits: &[LineEdit]) -> Vec<Heading> {
+    headings
+        .iter()
+        .filter_map(|h| {
+            remap_line(h.line_number, edits).map(|line_number| Heading {
+                line_number,
+                ..h.clone()
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_headings;
+
+    #[test]
+    fn test_remap_headings() {
+        let headings = parse_headings("# A\n\n## B\n\ntext\n\n## C\n\n## D\n");
+        let edits = [
+            LineEdit::insert(2, 3),
+            LineEdit::delete(9, 2),
+            LineEdit {
+                line: 1,
+                removed: 0,
+                inserted: 1,
+            },
+        ];
+
+        let lines: Vec<_> = remap_headings(&headings, &edits)
+            .iter()
+            .map(|h| (h.text.clone(), h.line_number))
+            .collect();
+        assert_eq!(lines, [("A".into(), 2), ("B".into(), 7), ("D".into(), 11)]);
+        assert_eq!(remap_line(3, &[LineEdit::delete(1, 1)]), Some(2));
+        assert_eq!(remap_line(3, &[]), Some(3));
+    }
+
+    #[test]
+    fn test_remap_line_saturates() {
+        assert_eq!(remap_line(5, &[LineEdit::delete(3, usize::MAX)]), None);
+        assert_eq!(
+            remap_line(usize::MAX, &[LineEdit::insert(1, 2)]),
+            Some(usize::MAX)
+        );
+        assert_eq!(
+            remap_line(usize::MAX, &[LineEdit::delete(1, 2)]),
+            Some(usize::MAX - 2)
+        );
+    }
+}
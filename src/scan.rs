@@ -0,0 +1,869 @@
+//! Line-by-line heading scanner shared by the in-memory and streaming parsers
+
+use crate::Heading;
+use crate::front_matter::FrontMatterFormat;
+use std::collections::VecDeque;
+use std::io::{self, BufRead};
+
+/// Strip a trailing `\n` or `\r\n` from a raw line
+pub(crate) fn strip_line_ending(raw: &str) -> &str {
+    let line = raw.strip_suffix('\n').unwrap_or(raw);
+    line.strip_suffix('\r').unwrap_or(line)
+}
+
+/// The UTF-8 byte order mark some Windows editors put at the start of a file
+pub(crate) const BOM: char = '\u{feff}';
+
+/// An opening code fence: its character and run length
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Fence {
+    pub(crate) marker: char,
+    pub(crate) len: usize,
+}
+
+/// Parse a code fence line, returning the fence and its info string
+pub(crate) fn parse_fence(line: &str) -> Option<(Fence, &str)> {
+    if indent_width(line) > 3 {
+        return None;
+    }
+
+    let trimmed = line.trim_start();
+    let marker = trimmed.chars().next().filter(|c| matches!(c, '`' | '~'))?;
+    let len = trimmed.chars().take_while(|&c| c == marker).count();
+    if len < 3 {
+        return None;
+    }
+
+    // Backtick fences can't have backticks in their info string
+    let info = trimmed[len..].trim();
+    if marker == '`' && info.contains('`') {
+        return None;
+    }
+
+    Some((Fence { marker, len }, info))
+}
+
+/// Whether a line starts a bullet or ordered list item
+fn is_list_item(line: &str) -> bool {
+    if indent_width(line) > 3 {
+        return false;
+    }
+
+    let trimmed = line.trim_start();
+    let digits = trimmed.len()
+        - trimmed
+            .trim_start_matches(|c: char| c.is_ascii_digit())
+            .len();
+    let ordered = digits > 0
+        && trimmed[digits..]
+            .strip_prefix(['.', ')'])
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(' '));
+
+    ordered || ["- ", "* ", "+ "].iter().any(|m| trimmed.starts_with(m))
+}
+
+/// Tracks code blocks while walking a document line by line
+///
+/// Fenced blocks follow CommonMark: a block opened by a run of backticks or
+/// tildes is only closed by a run of the same character that is at least as
+/// long and has no info string. Indented blocks are lines indented by four or
+/// more columns that don't continue a paragraph or a list item.
+#[derive(Debug, Default)]
+pub(crate) struct CodeBlockTracker {
+    fence: Option<Fence>,
+    in_indented: bool,
+    paragraph_open: bool,
+    in_list: bool,
+    prev_blank: bool,
+}
+
+impl CodeBlockTracker {
+    /// Whether the tracker is currently inside a fenced code block
+    pub(crate) fn in_fence(&self) -> bool {
+        self.fence.is_some()
+    }
+
+    /// Feed the next line, returning true if it is a fence marker or inside a code block
+    pub(crate) fn is_code(&mut self, line: &str) -> bool {
+        if let Some(open) = self.fence {
+            if let Some((fence, info)) = parse_fence(line)
+                && info.is_empty()
+                && fence.marker == open.marker
+                && fence.len >= open.len
+            {
+                self.fence = None;
+            }
+            return true;
+        }
+
+        if line.trim().is_empty() {
+            // Blank lines don't end an indented block; the next unindented line does
+            self.paragraph_open = false;
+            self.prev_blank = true;
+            return false;
+        }
+
+        let indent = indent_width(line);
+        if indent >= 4 && (self.in_indented || (!self.paragraph_open && !self.in_list)) {
+            self.in_indented = true;
+            self.prev_blank = false;
+            return true;
+        }
+        self.in_indented = false;
+
+        if let Some((fence, _)) = parse_fence(line) {
+            self.fence = Some(fence);
+            self.paragraph_open = false;
+            self.prev_blank = false;
+            return true;
+        }
+
+        if is_list_item(line) {
+            self.in_list = true;
+        } else if indent == 0 && (self.prev_blank || parse_atx(line).is_some()) {
+            // An unindented heading also ends a list
+            self.in_list = false;
+        }
+
+        // Headings can't be continued, so an indented line after one is code
+        self.paragraph_open = parse_atx(line).is_none();
+        self.prev_blank = false;
+        false
+    }
+}
+
+/// Parse an ATX heading line into its level and text
+pub(crate) fn parse_atx(line: &str) -> Option<(u8, &str)> {
+    if indent_width(line) > 3 {
+        return None;
+    }
+
+    let rest = line.trim_start().strip_prefix('#')?;
+    let mut level = 1_u8;
+    let mut remaining = rest;
+
+    while let Some(r) = remaining.strip_prefix('#') {
+        level += 1;
+        remaining = r;
+        if level >= 6 {
+            break;
+        }
+    }
+
+    // Must have space after #'s
+    let text = remaining.strip_prefix(' ')?.trim();
+    Some((level, strip_closing_hashes(text)))
+}
+
+/// Attributes from a trailing Pandoc-style `{#id .class key=value}` block
+#[derive(Debug, Default)]
+pub(crate) struct HeadingAttributes {
+    pub(crate) id: Option<String>,
+    pub(crate) classes: Vec<String>,
+    pub(crate) attributes: Vec<(String, String)>,
+}
+
+/// Split an attribute block into whitespace-separated words, keeping quoted
+/// values together
+fn attribute_words(block: &str) -> Option<Vec<&str>> {
+    let mut words = Vec::new();
+    let mut rest = block.trim_start();
+    while !rest.is_empty() {
+        let mut in_quotes = false;
+        let end = rest
+            .char_indices()
+            .find(|&(_, c)| {
+                if c == '"' {
+                    in_quotes = !in_quotes;
+                }
+                c.is_whitespace() && !in_quotes
+            })
+            .map_or(rest.len(), |(idx, _)| idx);
+        if in_quotes {
+            return None;
+        }
+        words.push(&rest[..end]);
+        rest = rest[end..].trim_start();
+    }
+    Some(words)
+}
+
+/// Parse the inside of an attribute block; any unrecognized word means the
+/// braces are part of the heading text
+pub(crate) fn parse_attribute_block(block: &str) -> Option<HeadingAttributes> {
+    let mut attrs = HeadingAttributes::default();
+    let words = attribute_words(block)?;
+    if words.is_empty() {
+        return None;
+    }
+
+    for word in words {
+        if let Some(id) = word.strip_prefix('#').filter(|id| !id.is_empty()) {
+            attrs.id = Some(id.to_string());
+        } else if let Some(class) = word.strip_prefix('.').filter(|c| !c.is_empty()) {
+            attrs.classes.push(class.to_string());
+        } else if word == "-" {
+            // Pandoc shorthand for `.unnumbered`
+            attrs.classes.push("unnumbered".to_string());
+        } else if let Some((key, value)) = word.split_once('=')
+            && !key.is_empty()
+            && !key.contains('"')
+        {
+            let value = value
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .unwrap_or(value);
+            attrs.attributes.push((key.to_string(), value.to_string()));
+        } else {
+            return None;
+        }
+    }
+    Some(attrs)
+}
+
+/// Split a trailing attribute block (`{#id .class key=value}`) off heading text
+///
+/// This is the attribute syntax used by Pandoc, and its `{#id}` form by kramdown
+/// and many static site generators. The block must be separated from the text by
+/// a space.
+pub(crate) fn split_heading_attributes(text: &str) -> (&str, HeadingAttributes) {
+    let parsed = text.strip_suffix('}').and_then(|body| {
+        let open = body.rfind('{')?;
+        let before = &body[..open];
+        if !(before.is_empty() || before.ends_with(' ')) {
+            return None;
+        }
+        Some((before.trim_end(), parse_attribute_block(&body[open + 1..])?))
+    });
+    parsed.unwrap_or((text, HeadingAttributes::default()))
+}
+
+/// Remove an optional closing `#` sequence, which must be preceded by a space
+fn strip_closing_hashes(text: &str) -> &str {
+    let without = text.trim_end_matches('#');
+    if without.is_empty() {
+        without
+    } else if without.len() < text.len() && without.ends_with([' ', '\t']) {
+        without.trim_end()
+    } else {
+        text
+    }
+}
+
+/// Byte offset of `part` within `whole`, which it must be a subslice of
+fn offset_in(whole: &str, part: &str) -> usize {
+    part.as_ptr() as usize - whole.as_ptr() as usize
+}
+
+/// Count the columns of leading whitespace, expanding tabs to the next multiple of 4
+pub(crate) fn indent_width(line: &str) -> usize {
+    let mut width = 0;
+    for c in line.chars() {
+        match c {
+            ' ' => width += 1,
+            '\t' => width += 4 - width % 4,
+            _ => break,
+        }
+    }
+    width
+}
+
+/// Parse a setext underline (`===` or `---`), returning the heading level it denotes
+fn parse_setext_underline(line: &str) -> Option<u8> {
+    if indent_width(line) > 3 {
+        return None;
+    }
+
+    let marker = line.trim();
+    let first = marker.chars().next()?;
+    let level = match first {
+        '=' => 1,
+        '-' => 2,
+        _ => return None,
+    };
+
+    marker.chars().all(|c| c == first).then_some(level)
+}
+
+/// Whether a line starts a block that can't be the text of a setext heading
+fn starts_other_block(line: &str) -> bool {
+    line.trim_start().starts_with('>') || is_list_item(line)
+}
+
+/// Paragraph text that a following setext underline would turn into a heading
+#[derive(Debug)]
+struct PendingParagraph {
+    line_number: usize,
+    byte_offset: usize,
+    text: String,
+    column: usize,
+    /// Document byte offsets of the start and end of the trimmed text
+    text_start: usize,
+    text_end: usize,
+}
+
+/// A possible front matter block whose closing delimiter hasn't been seen yet
+///
+/// The lines are scanned as regular markdown on the side, so the headings can
+/// still be reported if the block turns out not to be front matter.
+#[derive(Debug)]
+struct FrontMatterProbe {
+    format: FrontMatterFormat,
+    shadow: HeadingScanner,
+    pending: Vec<Heading>,
+    /// Lines seen after the opening delimiter
+    lines: usize,
+}
+
+/// Incremental heading parser fed one line at a time
+#[derive(Debug, Default)]
+pub(crate) struct HeadingScanner {
+    code: CodeBlockTracker,
+    paragraph: Option<PendingParagraph>,
+    front_matter: Option<Box<FrontMatterProbe>>,
+    front_matter_checked: bool,
+    /// Headings found while probing a block that turned out not to be front
+    /// matter, waiting for [`take_released`](Self::take_released)
+    released: Vec<Heading>,
+    line_number: usize,
+    byte_offset: usize,
+    /// Scanner for the content of the block quote the previous line was in
+    quote: Option<Box<HeadingScanner>>,
+    /// Report headings inside block quotes
+    blockquotes: bool,
+    /// Bytes and characters of `>` markers before each line, for scanners of
+    /// block quote content
+    prefix_len: usize,
+    prefix_chars: usize,
+}
+
+/// Split the `>` marker (and one following space) off a block quote line
+fn strip_quote_marker(line: &str) -> Option<&str> {
+    let trimmed = line.trim_start_matches(' ');
+    if line.len() - trimmed.len() > 3 {
+        return None;
+    }
+    let rest = trimmed.strip_prefix('>')?;
+    Some(rest.strip_prefix([' ', '\t']).unwrap_or(rest))
+}
+
+impl HeadingScanner {
+    /// A scanner picking up mid-document at a line known to start outside code
+    /// blocks, paragraphs and front matter, such as an ATX heading
+    ///
+    /// `line_number` is the number of lines before it.
+    pub(crate) fn resume_at(line_number: usize, byte_offset: usize) -> Self {
+        Self {
+            front_matter_checked: true,
+            line_number,
+            byte_offset,
+            ..Default::default()
+        }
+    }
+
+    /// Feed the next line (without its terminator) and its raw length in bytes
+    ///
+    /// Returns the heading that the line completes, if any. For setext headings
+    /// this is the underline, but the heading reports the line its text starts on.
+    pub(crate) fn push_line(&mut self, line: &str, raw_len: usize) -> Option<Heading> {
+        // Skip a byte order mark, keeping byte offsets relative to the document
+        let (line, raw_len) = match line.strip_prefix(BOM) {
+            Some(rest) if self.line_number == 0 => {
+                self.byte_offset += BOM.len_utf8();
+                (rest, raw_len - BOM.len_utf8())
+            }
+            _ => (line, raw_len),
+        };
+
+        self.line_number += 1;
+        let byte_offset = self.byte_offset;
+        self.byte_offset += raw_len;
+
+        if let Some(probe) = &mut self.front_matter {
+            if probe.format.is_closing(line) {
+                self.front_matter = None;
+                return None;
+            }
+            if let Some(heading) = probe.shadow.push_line(line, raw_len) {
+                probe.pending.push(heading);
+            }
+            probe.lines += 1;
+            if !probe.format.may_continue(line, probe.lines) {
+                let probe = self.front_matter.take().unwrap();
+                *self = probe.shadow;
+                self.released = probe.pending;
+            }
+            return None;
+        }
+
+        if !self.front_matter_checked {
+            self.front_matter_checked = true;
+            if let Some(format) = FrontMatterFormat::from_opening(line) {
+                let mut shadow = HeadingScanner {
+                    front_matter_checked: true,
+                    blockquotes: self.blockquotes,
+                    line_number: self.line_number - 1,
+                    byte_offset,
+                    ..Default::default()
+                };
+                shadow.push_line(line, raw_len);
+                self.front_matter = Some(Box::new(FrontMatterProbe {
+                    format,
+                    shadow,
+                    pending: Vec::new(),
+                    lines: 0,
+                }));
+                return None;
+            }
+        }
+
+        // Skip fence markers and lines inside code blocks
+        if self.code.is_code(line) {
+            self.paragraph = None;
+            self.quote = None;
+            return None;
+        }
+
+        // Scan block quote content as a document of its own
+        let Some(quoted) = strip_quote_marker(line) else {
+            self.quote = None;
+            return self.push_content_line(line, byte_offset);
+        };
+        self.paragraph = None;
+        let marker = line.len() - quoted.len();
+        let quote = self.quote.get_or_insert_with(|| {
+            Box::new(HeadingScanner {
+                front_matter_checked: true,
+                blockquotes: true,
+                prefix_len: self.prefix_len + marker,
+                prefix_chars: self.prefix_chars + line[..marker].chars().count(),
+                ..Default::default()
+            })
+        });
+        quote.line_number = self.line_number - 1;
+        quote.byte_offset = byte_offset + marker;
+        let heading = quote.push_line(quoted, raw_len - marker)?;
+        self.blockquotes.then_some(Heading {
+            in_blockquote: true,
+            ..heading
+        })
+    }
+
+    /// Handle a line outside code blocks and block quotes, starting at `byte_offset`
+    fn push_content_line(&mut self, line: &str, byte_offset: usize) -> Option<Heading> {
+        // Headings report the start of their line, before any quote markers
+        let line_start = byte_offset - self.prefix_len;
+
+        if line.trim().is_empty() {
+            self.paragraph = None;
+            return None;
+        }
+
+        if let Some((level, text)) = parse_atx(line) {
+            self.paragraph = None;
+            // The attribute may also follow a closing sequence (`## Title ## {#id}`)
+            let (text, attrs) = split_heading_attributes(text);
+            let text = strip_closing_hashes(text);
+            let start = offset_in(line, text);
+            return Some(Heading {
+                line_number: self.line_number,
+                level,
+                text: text.to_string(),
+                id: attrs.id,
+                classes: attrs.classes,
+                attributes: attrs.attributes,
+                byte_offset: line_start,
+                byte_len: byte_offset + line.len() - line_start,
+                column: self.prefix_chars + line[..start].chars().count() + 1,
+                text_range: byte_offset + start..byte_offset + start + text.len(),
+                in_blockquote: false,
+            });
+        }
+
+        if let Some(level) = parse_setext_underline(line) {
+            // Without paragraph text above, `---` is a thematic break
+            let paragraph = self.paragraph.take()?;
+            let (text, attrs) = split_heading_attributes(&paragraph.text);
+            let removed = paragraph.text.len() - text.len();
+            return Some(Heading {
+                line_number: paragraph.line_number,
+                level,
+                text: text.to_string(),
+                id: attrs.id,
+                classes: attrs.classes,
+                attributes: attrs.attributes,
+                byte_offset: paragraph.byte_offset,
+                byte_len: byte_offset + line.len() - paragraph.byte_offset,
+                column: paragraph.column,
+                text_range: paragraph.text_start..paragraph.text_end - removed,
+                in_blockquote: false,
+            });
+        }
+
+        let trimmed = line.trim();
+        let text_start = byte_offset + offset_in(line, trimmed);
+        if starts_other_block(line) {
+            self.paragraph = None;
+        } else if let Some(paragraph) = &mut self.paragraph {
+            paragraph.text.push(' ');
+            paragraph.text.push_str(trimmed);
+            paragraph.text_end = text_start + trimmed.len();
+        } else {
+            self.paragraph = Some(PendingParagraph {
+                line_number: self.line_number,
+                byte_offset: line_start,
+                text: trimmed.to_string(),
+                column: self.prefix_chars + line[..offset_in(line, trimmed)].chars().count() + 1,
+                text_start,
+                text_end: text_start + trimmed.len(),
+            });
+        }
+
+        None
+    }
+
+    /// Signal the end of input, returning any headings still held back
+    ///
+    /// Headings are only held back while a leading `---` might open front matter;
+    /// if it is never closed, they were regular content after all.
+    pub(crate) fn finish(&mut self) -> Vec<Heading> {
+        let mut headings = self.take_released();
+        if let Some(probe) = self.front_matter.take() {
+            headings.extend(probe.pending);
+        }
+        headings
+    }
+
+    /// Headings held back while probing a leading delimiter that turned out not
+    /// to open front matter
+    ///
+    /// They precede any heading returned by later calls to `push_line`, so
+    /// callers collect them after every line.
+    pub(crate) fn take_released(&mut self) -> Vec<Heading> {
+        std::mem::take(&mut self.released)
+    }
+}
+
+/// Iterator returned by [`parse_headings_from_reader`]
+struct ReaderHeadings<R> {
+    reader: R,
+    scanner: HeadingScanner,
+    buf: String,
+    pending: VecDeque<Heading>,
+    done: bool,
+}
+
+impl<R: BufRead> Iterator for ReaderHeadings<R> {
+    type Item = io::Result<Heading>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pending.is_empty() && !self.done {
+            self.buf.clear();
+            match self.reader.read_line(&mut self.buf) {
+                Ok(0) => {
+                    self.done = true;
+                    self.pending.extend(self.scanner.finish());
+                }
+                Ok(raw_len) => {
+                    let line = strip_line_ending(&self.buf);
+                    let heading = self.scanner.push_line(line, raw_len);
+                    self.pending.extend(self.scanner.take_released());
+                    self.pending.extend(heading);
+                }
+                Err(e) => {
+                    // A read error ends the stream; line numbers would be wrong after it
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+        self.pending.pop_front().map(Ok)
+    }
+}
+
+/// Parse headings from a reader without loading the whole document into memory
+///
+/// Yields the same headings as the line scanner behind
+/// [`parse_headings`](crate::parse_headings) would for the full content, one line
+/// at a time (headings inside a leading `---` block are held back until its
+/// closing delimiter or the end of input). I/O errors (including invalid UTF-8)
+/// are yielded once and end the iteration.
+///
+/// This always uses the line scanner, even with the `commonmark` feature, since
+/// pulldown-cmark needs the whole document. Headings nested in list items are
+/// therefore not found.
+pub fn parse_headings_from_reader<R: BufRead>(
+    reader: R,
+) -> impl Iterator<Item = io::Result<Heading>> {
+    ReaderHeadings {
+        reader,
+        scanner: HeadingScanner::default(),
+        buf: String::new(),
+        pending: VecDeque::new(),
+        done: false,
+    }
+}
+
+/// Parse headings from in-memory content with the line scanner
+#[cfg(any(not(feature = "commonmark"), test))]
+pub(crate) fn scan_headings(content: &str, blockquotes: bool) -> Vec<Heading> {
+    let mut scanner = HeadingScanner {
+        blockquotes,
+        ..Default::default()
+    };
+    let mut headings = Vec::new();
+    for raw in content.split_inclusive('\n') {
+        let heading = scanner.push_line(strip_line_ending(raw), raw.len());
+        headings.extend(scanner.take_released());
+        headings.extend(heading);
+    }
+    headings.extend(scanner.finish());
+    headings
+}
+
+/// Parse headings from raw bytes, such as a memory-mapped file
+///
+/// Valid UTF-8 is parsed like [`parse_headings`](crate::parse_headings), with
+/// the `commonmark` backend when that feature is enabled. Unlike
+/// [`parse_headings_from_reader`], invalid UTF-8 doesn't stop the scan: such
+/// content always goes through the line scanner, which decodes those lines
+/// lossily, so byte offsets stay relative to `bytes` but a heading on such a line
+/// reports `column` and `text_range` within the decoded text.
+pub fn parse_headings_bytes(bytes: &[u8]) -> Vec<Heading> {
+    #[cfg(feature = "commonmark")]
+    if let Ok(content) = std::str::from_utf8(bytes) {
+        return crate::parse_headings(content);
+    }
+
+    let mut scanner = HeadingScanner::default();
+    let mut headings = Vec::new();
+    for raw in bytes.split_inclusive(|&b| b == b'\n') {
+        let line = String::from_utf8_lossy(raw);
+        let heading = scanner.push_line(strip_line_ending(&line), raw.len());
+        headings.extend(scanner.take_released());
+        headings.extend(heading);
+    }
+    headings.extend(scanner.finish());
+    headings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::front_matter::MAX_FRONT_MATTER_LINES;
+    use crate::{ParseOptions, parse_headings_with};
+
+    /// Test the scanner itself, whichever backend `crate::parse_headings` uses
+    fn parse_headings(content: &str) -> Vec<Heading> {
+        scan_headings(content, false)
+    }
+
+    #[test]
+    fn test_reader_matches_parse_headings() {
+        let content = "# Title\r\n\r\n```\r\n# not a heading\r\n```\r\n## Section\nText\n### Deep";
+        let streamed: Vec<Heading> = parse_headings_from_reader(content.as_bytes())
+            .collect::<io::Result<_>>()
+            .unwrap();
+
+        assert_eq!(streamed, parse_headings(content));
+        assert_eq!(streamed.len(), 3);
+        assert_eq!(&content[streamed[2].byte_range()], "### Deep");
+    }
+
+    #[test]
+    fn test_fence_matching() {
+        let content = "# Title\n\n````markdown\n```\n# Inside\n```\n````\n\n~~~\n```\n## Still inside\n~~~~\n\n## After\n";
+        let headings = parse_headings(content);
+        let texts: Vec<_> = headings.iter().map(|h| h.text.as_str()).collect();
+        assert_eq!(texts, ["Title", "After"]);
+
+        // A closing fence can't carry an info string
+        let headings = parse_headings("```\n```rust\n# Inside\n```\n# Outside\n");
+        assert_eq!(headings.len(), 1);
+        assert_eq!(headings[0].text, "Outside");
+    }
+
+    #[test]
+    fn test_indented_code_blocks() {
+        let content = "# Title\n\n    # shell comment\n    make\n\n    # another\n\nText\n    # continuation\n\n- item\n\n    # list content\n\n## Real\n";
+        let headings = parse_headings(content);
+        let texts: Vec<_> = headings.iter().map(|h| h.text.as_str()).collect();
+        assert_eq!(texts, ["Title", "Real"]);
+
+        // An indented code line can't become setext heading text
+        assert!(parse_headings("    code\n---\n").is_empty());
+
+        // A heading ends a list, so the indented line after it is code again
+        assert_eq!(parse_headings("- item\n# Title\n    code\n---\n").len(), 1);
+    }
+
+    #[test]
+    fn test_explicit_heading_ids() {
+        let content = "# Install {#setup}\n## Closing ## {#closed}\n## Not{#attr}\n## Spaced {#a b}\nSetext {#st}\n---\n";
+        let headings = parse_headings(content);
+
+        assert_eq!(headings[0].text, "Install");
+        assert_eq!(headings[0].id.as_deref(), Some("setup"));
+        assert_eq!(headings[1].text, "Closing");
+        assert_eq!(headings[1].id.as_deref(), Some("closed"));
+        assert_eq!(headings[2].text, "Not{#attr}");
+        assert_eq!(headings[2].id, None);
+        assert_eq!(headings[3].id, None);
+        assert_eq!(headings[4].text, "Setext");
+        assert_eq!(headings[4].id.as_deref(), Some("st"));
+    }
+
+    #[test]
+    fn test_blockquote_headings() {
+        let content = "# Top\n\n> ## Quoted\n> text\n>\n> > Nested\n> > ---\n\nAfter\n---\n";
+        let texts = |headings: Vec<Heading>| -> Vec<String> {
+            headings.into_iter().map(|h| h.text).collect()
+        };
+        assert_eq!(texts(parse_headings(content)), ["Top", "After"]);
+
+        let options = ParseOptions {
+            blockquotes: true,
+            ..Default::default()
+        };
+        let headings = parse_headings_with(content, &options);
+        assert_eq!(headings.len(), 4);
+        assert!(headings[1].in_blockquote && !headings[3].in_blockquote);
+        assert_eq!(headings[1].byte_range(), 7..18);
+        assert_eq!(&content[headings[2].text_range.clone()], "Nested");
+        assert_eq!(headings[2].column, 5);
+        assert_eq!(headings[2].byte_offset, 28);
+        assert_eq!(headings[2].byte_len, 18);
+    }
+
+    #[test]
+    fn test_pandoc_attributes() {
+        let content = "# Intro {#start .lead -}
+## Data {data-source=\"raw csv\" .wide}
+## Code {x} ##
+## Math {a=1}
+";
+        let headings = parse_headings(content);
+
+        assert_eq!(headings[0].text, "Intro");
+        assert_eq!(headings[0].id.as_deref(), Some("start"));
+        assert_eq!(headings[0].classes, ["lead", "unnumbered"]);
+        assert_eq!(headings[1].text, "Data");
+        assert_eq!(headings[1].classes, ["wide"]);
+        assert_eq!(
+            headings[1].attributes,
+            [("data-source".to_string(), "raw csv".to_string())]
+        );
+        assert_eq!(&content[headings[1].text_range.clone()], "Data");
+        // A bare word isn't an attribute, so the braces are text
+        assert_eq!(headings[2].text, "Code {x}");
+        assert_eq!(headings[3].attributes, [("a".to_string(), "1".to_string())]);
+    }
+
+    #[test]
+    fn test_closing_hashes() {
+        let headings =
+            parse_headings("## Title ##\n### Trailing ###   \n# C#\n## Issue #42 #\n### ###\n");
+        let texts: Vec<_> = headings.iter().map(|h| h.text.as_str()).collect();
+        assert_eq!(texts, ["Title", "Trailing", "C#", "Issue #42", ""]);
+    }
+
+    #[test]
+    fn test_setext_headings() {
+        let content =
+            "Title\n=====\n\nIntro text\n\nMulti-line\nsection name\n---\n\n---\n\n- item\n---\n";
+        let headings = parse_headings(content);
+
+        assert_eq!(headings.len(), 2);
+        assert_eq!(headings[0].line_number, 1);
+        assert_eq!(headings[0].level, 1);
+        assert_eq!(headings[0].text, "Title");
+        assert_eq!(&content[headings[0].byte_range()], "Title\n=====");
+
+        assert_eq!(headings[1].line_number, 6);
+        assert_eq!(headings[1].level, 2);
+        assert_eq!(headings[1].text, "Multi-line section name");
+    }
+
+    #[test]
+    fn test_reader_invalid_utf8() {
+        let bytes: &[u8] = b"# Title\n\xff\xfe\n## Later\n";
+        let results: Vec<_> = parse_headings_from_reader(bytes).collect();
+
+        assert_eq!(results.len(), 2);
+        assert!(results[0].is_ok());
+        assert!(results[1].is_err());
+    }
+
+    #[test]
+    fn test_reader_releases_headings_of_unclosed_front_matter() {
+        let lines = std::iter::once("---\ntitle: x\n".to_string())
+            .chain((0..MAX_FRONT_MATTER_LINES).map(|n| format!("# Heading {}\n", n)));
+        let content: String = lines.collect();
+
+        // Headings come out once the block is too long to be front matter,
+        // without waiting for the end of input,
+        // which never comes here
+        let mut reader = parse_headings_from_reader(io::BufReader::new(io::Read::chain(
+            content.as_bytes(),
+            io::repeat(b'\n'),
+        )));
+        let first = reader.next().unwrap().unwrap();
+        assert_eq!((first.line_number, first.text.as_str()), (3, "Heading 0"));
+
+        let headings = parse_headings(&content);
+        assert_eq!(headings.len(), MAX_FRONT_MATTER_LINES);
+        assert_eq!(&content[headings[0].byte_range()], "# Heading 0");
+    }
+
+    #[test]
+    fn test_parse_headings_bytes() {
+        let content = "---\ntitle: x\n---\n# Title\r\n\nText\n===\n```\n# no\n```\n## Last";
+        assert_eq!(
+            parse_headings_bytes(content.as_bytes()),
+            parse_headings(content)
+        );
+
+        // Valid UTF-8 goes through the same backend as `crate::parse_headings`
+        let list = "- # In a list\n\n# Top\n";
+        assert_eq!(
+            parse_headings_bytes(list.as_bytes()),
+            crate::parse_headings(list)
+        );
+
+        let bytes: &[u8] = b"# Title\n\xff\xfe\n## Later\n";
+        let headings = parse_headings_bytes(bytes);
+        assert_eq!(headings.len(), 2);
+        assert_eq!(headings[1].byte_offset, 11);
+    }
+
+    #[test]
+    fn test_heading_text_columns() {
+        let content = "  ## Café ## {#cafe}\n\n\u{e9}t\u{e9}\n  Summer {#s}\n===\n";
+        let headings = parse_headings(content);
+
+        assert_eq!(headings[0].column, 6);
+        assert_eq!(&content[headings[0].text_range.clone()], "Café");
+
+        assert_eq!(headings[1].column, 1);
+        assert_eq!(
+            &content[headings[1].text_range.clone()],
+            "\u{e9}t\u{e9}\n  Summer"
+        );
+    }
+
+    #[test]
+    fn test_byte_order_mark() {
+        let content = "\u{feff}# Title\r\n\r\n## Next\r\n";
+        let headings = parse_headings(content);
+
+        assert_eq!(headings.len(), 2);
+        assert_eq!(headings[0].text, "Title");
+        assert_eq!(&content[headings[0].byte_range()], "# Title");
+        assert_eq!(&content[headings[1].byte_range()], "## Next");
+
+        let streamed: Vec<_> = parse_headings_from_reader(content.as_bytes())
+            .collect::<io::Result<_>>()
+            .unwrap();
+        assert_eq!(streamed, headings);
+    }
+}
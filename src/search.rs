@@ -0,0 +1,171 @@
+//! Text search across a document, reporting where in the outline each hit lives
+
+use crate::Heading;
+use crate::scan::CodeBlockTracker;
+
+/// A line matching a search, together with the headings that contain it
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GrepHit {
+    /// Texts of the containing headings, outermost first (empty before the first heading)
+    pub heading_path: Vec<String>,
+    /// Line number of the match (1-indexed)
+    pub line_number: usize,
+    /// The full matching line
+    pub line: String,
+}
+
+/// Get the chain of headings containing `line_number`, outermost first
+pub(crate) fn ancestry_at(headings: &[Heading], line_number: usize) -> Vec<&Heading> {
+    let mut path: Vec<&Heading> = Vec::new();
+
+    for h in headings.iter().take_while(|h| h.line_number <= line_number) {
+        while path.last().is_some_and(|last| last.level >= h.level) {
+            path.pop();
+        }
+        path.push(h);
+    }
+
+    path
+}
+
+/// Find all lines containing `pattern` along with their heading breadcrumb
+///
+/// Matching is a case-insensitive substring search over every line, including
+/// heading lines. With `skip_code` set, fence markers and lines inside fenced or
+/// indented code blocks are ignored.
+pub fn grep_with_context(
+    content: &str,
+    headings: &[Heading],
+    pattern: &str,
+    skip_code: bool,
+) -> Vec<GrepHit> {
+    let pattern_lower = pattern.to_lowercase();
+    grep_lines(content, headings, skip_code, |line| {
+        line.to_lowercase().contains(&pattern_lower)
+    })
+}
+
+/// Find all lines matching a regular expression along with their heading breadcrumb
+///
+/// Same as [`grep_with_context`], but lines are matched with `pattern` as is, so
+/// case sensitivity is up to the pattern (`(?i)`).
+#[cfg(feature = "regex")]
+pub fn grep_regex(
+    content: &str,
+    headings: &[Heading],
+    pattern: &regex::Regex,
+    skip_code: bool,
+) -> Vec<GrepHit> {
+    grep_lines(content, headings, skip_code, |line| pattern.is_match(line))
+}
+
+/// Collect the lines accepted by `matches`, optionally skipping code blocks
+fn grep_lines(
+    content: &str,
+    headings: &[Heading],
+    skip_code: bool,
+    matches: impl Fn(&str) -> bool,
+) -> Vec<GrepHit> {
+    let mut code = CodeBlockTracker::default();
+    let mut hits = Vec::new();
+
+    for (idx, line) in content.lines().enumerate() {
+        let in_code = code.is_code(line);
+        if (skip_code && in_code) || !matches(line) {
+            continue;
+        }
+
+        let line_number = idx + 1;
+        hits.push(GrepHit {
+            heading_path: ancestry_at(headings, line_number)
+                .iter()
+                .map(|h| h.text.clone())
+                .collect(),
+            line_number,
+            line: line.to_string(),
+        });
+    }
+
+    hits
+}
+
+/// Find all headings whose text matches a regular expression
+///
+/// The pattern is matched against the heading text as parsed (without `#` marks
+/// or `{#id}` attributes), e.g. `^v\d+\.\d+\.\d+` finds release entries in a
+/// changelog.
+#[cfg(feature = "regex")]
+pub fn find_sections_matching<'a>(
+    headings: &'a [Heading],
+    pattern: &regex::Regex,
+) -> Vec<&'a Heading> {
+    headings
+        .iter()
+        .filter(|h| pattern.is_match(&h.text))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_headings;
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn test_grep_regex() {
+        let content = "# Guide\n\nVersion 1.2.3\n\n## Usage\n\nversion 2\n";
+        let headings = parse_headings(content);
+        let pattern = regex::Regex::new(r"Version \d+\.\d+").unwrap();
+
+        let hits = grep_regex(content, &headings, &pattern, false);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].line_number, 3);
+        assert_eq!(hits[0].heading_path, ["Guide"]);
+    }
+
+    #[test]
+    fn test_grep_nested_heading() {
+        let content = "# Guide\n\n## Install\n\n### Linux\n\nRun apt install.\n\n## Usage\n\nNothing to install here.\n";
+        let headings = parse_headings(content);
+
+        let hits = grep_with_context(content, &headings, "apt", false);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].heading_path, ["Guide", "Install", "Linux"]);
+        assert_eq!(hits[0].line_number, 7);
+        assert_eq!(hits[0].line, "Run apt install.");
+
+        // Siblings are popped off the path
+        let hits = grep_with_context(content, &headings, "nothing", false);
+        assert_eq!(hits[0].heading_path, ["Guide", "Usage"]);
+    }
+
+    #[test]
+    fn test_grep_skip_code() {
+        let content = "# Setup\n\nConfigure it.\n\n```\nconfigure --fast\n```\n";
+        let headings = parse_headings(content);
+
+        assert_eq!(
+            grep_with_context(content, &headings, "configure", false).len(),
+            2
+        );
+
+        let hits = grep_with_context(content, &headings, "configure", true);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].line_number, 3);
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn test_find_sections_matching() {
+        let content = "# Changelog\n\n## v1.2.0\n\n### Fixed\n\n## v1.1.10\n\n## Unreleased v2\n";
+        let headings = parse_headings(content);
+
+        let pattern = regex::Regex::new(r"^v\d+\.\d+\.\d+").unwrap();
+        let releases: Vec<_> = find_sections_matching(&headings, &pattern)
+            .iter()
+            .map(|h| h.text.as_str())
+            .collect();
+        assert_eq!(releases, ["v1.2.0", "v1.1.10"]);
+    }
+}
@@ -0,0 +1,321 @@
+//! Minimal HTTP interface for `mdi serve`
+//!
+//! Serves GET requests only, one connection per thread, and closes each
+//! connection after the response.
+
+use crate::output::SectionEntry;
+use crate::{find_heading, parse_input};
+use anyhow::{Context, Result};
+use markdown_inspector::{
+    collect_markdown_files, extract_section, get_section_range, is_markdown_file, percent_decode,
+    render_heading_html,
+};
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+/// How long a client may take to send its request
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Upper bound on the request line and headers together
+const MAX_REQUEST_BYTES: u64 = 16 * 1024;
+
+/// Connections handled at once; more are turned away with 503
+const MAX_CONNECTIONS: usize = 64;
+
+#[derive(Debug)]
+struct Response {
+    status: u16,
+    content_type: &'static str,
+    body: String,
+}
+
+impl Response {
+    fn json(value: &impl serde::Serialize) -> Self {
+        match serde_json::to_string_pretty(value) {
+            Ok(body) => Self {
+                status: 200,
+                content_type: "application/json",
+                body,
+            },
+            Err(err) => Self::error(500, &err.to_string()),
+        }
+    }
+
+    fn html(body: String) -> Self {
+        Self {
+            status: 200,
+            content_type: "text/html; charset=utf-8",
+            body,
+        }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body: serde_json::json!({ "error": message }).to_string(),
+        }
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        431 => "Request Header Fields Too Large",
+        503 => "Service Unavailable",
+        _ => "Internal Server Error",
+    }
+}
+
+/// Look up a query parameter
+fn param(query: &str, name: &str) -> Option<String> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| percent_decode(&key.replace('+', " ")) == name)
+        .map(|(_, value)| percent_decode(&value.replace('+', " ")))
+}
+
+/// Resolve a `file` parameter below the served directory, which `root` names
+/// in canonical form
+///
+/// Only the files `/files` lists can be read: markdown files outside hidden
+/// directories. Absolute paths and `..` components are rejected, and the path is
+/// canonicalized so that symlinks can't lead outside `root` either.
+fn resolve(root: &Path, file: &str) -> Result<PathBuf, Response> {
+    let relative = Path::new(file);
+    let visible = relative.components().all(|c| match c {
+        Component::Normal(name) => !name.to_string_lossy().starts_with('.'),
+        Component::CurDir => true,
+        _ => false,
+    });
+    if !visible || !is_markdown_file(relative) {
+        return Err(Response::error(
+            400,
+            "file must be a relative path to a markdown file below the served directory",
+        ));
+    }
+
+    let not_found = || Response::error(404, &format!("File not found: {}", file));
+    let path = fs::canonicalize(root.join(relative)).map_err(|_| not_found())?;
+    if path.starts_with(root) && is_markdown_file(&path) && path.is_file() {
+        Ok(path)
+    } else {
+        Err(not_found())
+    }
+}
+
+fn read_file(root: &Path, query: &str) -> Result<(PathBuf, String), Response> {
+    let file =
+        param(query, "file").ok_or_else(|| Response::error(400, "missing file parameter"))?;
+    let path = resolve(root, &file)?;
+    let content =
+        fs::read_to_string(&path).map_err(|err| Response::error(500, &err.to_string()))?;
+    Ok((path, content))
+}
+
+fn route(root: &Path, path: &str, query: &str) -> Result<Response, Response> {
+    match path {
+        "/" | "/files" => {
+            let files = collect_markdown_files(root, true)
+                .map_err(|err| Response::error(500, &err.to_string()))?;
+            let names: Vec<_> = files
+                .iter()
+                .filter_map(|f| f.strip_prefix(root).ok())
+                .map(|f| f.display().to_string())
+                .collect();
+            Ok(Response::json(&names))
+        }
+        "/outline" => {
+            let (path, content) = read_file(root, query)?;
+            let headings = parse_input(&path, &content);
+            let entries: Vec<_> = headings
+                .iter()
+                .map(|h| SectionEntry::new(&headings, h))
+                .collect();
+            Ok(Response::json(&entries))
+        }
+        "/section" => {
+            let (path, content) = read_file(root, query)?;
+            let headings = parse_input(&path, &content);
+            let q = param(query, "q").ok_or_else(|| Response::error(400, "missing q parameter"))?;
+            let heading = find_heading(&headings, &q)
+                .map_err(|err| Response::error(404, &err.to_string()))?;
+
+            if param(query, "format").as_deref() == Some("html") {
+                return Ok(Response::html(render_heading_html(
+                    &content, &headings, heading,
+                )));
+            }
+            let (start, end) = get_section_range(&headings, heading);
+            let entry = SectionEntry {
+                content: Some(extract_section(&content, start, end)),
+                ..SectionEntry::new(&headings, heading)
+            };
+            Ok(Response::json(&entry))
+        }
+        _ => Err(Response::error(404, &format!("No such endpoint: {}", path))),
+    }
+}
+
+fn handle(stream: TcpStream, root: &Path) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(&stream).take(MAX_REQUEST_BYTES);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+
+    // Skip the headers; nothing in them changes the response
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let target = parts.next().unwrap_or_default();
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+    let response = if reader.limit() == 0 {
+        Response::error(431, "request headers are too large")
+    } else if method == "GET" {
+        route(root, path, query).unwrap_or_else(|err| err)
+    } else {
+        Response::error(405, "only GET is supported")
+    };
+    write_response(&stream, &response)
+}
+
+fn write_response(mut stream: &TcpStream, response: &Response) -> std::io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        reason(response.status),
+        response.content_type,
+        response.body.len(),
+        response.body
+    )?;
+    stream.flush()
+}
+
+/// Serve the markdown files below `root` on `address` until interrupted
+pub fn serve(root: &Path, address: &str) -> Result<()> {
+    let root = fs::canonicalize(root)
+        .with_context(|| format!("Failed to resolve directory: {:?}", root))?;
+    let listener =
+        TcpListener::bind(address).with_context(|| format!("Failed to listen on {}", address))?;
+    eprintln!("Serving {} on http://{}", root.display(), address);
+
+    let active = Arc::new(AtomicUsize::new(0));
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else { continue };
+        if active.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+            active.fetch_sub(1, Ordering::SeqCst);
+            let _ = write_response(&stream, &Response::error(503, "too many connections"));
+            continue;
+        }
+
+        let root = root.clone();
+        let active = Arc::clone(&active);
+        thread::spawn(move || {
+            let _ = handle(stream, &root);
+            active.fetch_sub(1, Ordering::SeqCst);
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("mdi-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        for (path, content) in files {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        root
+    }
+
+    fn status(result: Result<PathBuf, Response>) -> u16 {
+        result.map_or_else(|response| response.status, |_| 200)
+    }
+
+    #[test]
+    fn test_resolve() {
+        let scratch = scratch_dir(
+            "serve",
+            &[
+                ("root/docs/guide.md", "# Guide\n"),
+                ("root/.private/notes.md", "# Notes\n"),
+                ("root/.secret.md", "# Secret\n"),
+                ("root/config.toml", "key = 1\n"),
+                ("outside.md", "# Outside\n"),
+            ],
+        );
+        let root = fs::canonicalize(scratch.join("root")).unwrap();
+
+        assert_eq!(
+            resolve(&root, "docs/guide.md").unwrap(),
+            root.join("docs/guide.md")
+        );
+        assert_eq!(status(resolve(&root, "./docs/guide.md")), 200);
+        assert_eq!(status(resolve(&root, "docs/missing.md")), 404);
+
+        // Parent directories and absolute paths
+        assert_eq!(status(resolve(&root, "../outside.md")), 400);
+        assert_eq!(status(resolve(&root, "docs/../../outside.md")), 400);
+        let absolute = scratch.join("outside.md");
+        assert_eq!(status(resolve(&root, &absolute.to_string_lossy())), 400);
+
+        // Hidden entries and files `/files` doesn't list
+        assert_eq!(status(resolve(&root, ".private/notes.md")), 400);
+        assert_eq!(status(resolve(&root, ".secret.md")), 400);
+        assert_eq!(status(resolve(&root, "config.toml")), 400);
+
+        fs::remove_dir_all(&scratch).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_rejects_escaping_symlinks() {
+        let scratch = scratch_dir(
+            "serve-symlink",
+            &[
+                ("root/docs/guide.md", "# Guide\n"),
+                ("outside/secret.md", "# Secret\n"),
+                ("outside/secret.txt", "secret\n"),
+            ],
+        );
+        let root = fs::canonicalize(scratch.join("root")).unwrap();
+        let link = |target: &str, name: &str| {
+            std::os::unix::fs::symlink(scratch.join(target), root.join(name)).unwrap();
+        };
+        link("outside/secret.md", "escape.md");
+        link("outside", "linked");
+        link("outside/secret.txt", "disguised.md");
+        link("root/docs/guide.md", "alias.md");
+
+        assert_eq!(status(resolve(&root, "escape.md")), 404);
+        assert_eq!(status(resolve(&root, "linked/secret.md")), 404);
+        assert_eq!(status(resolve(&root, "disguised.md")), 404);
+        assert_eq!(
+            resolve(&root, "alias.md").unwrap(),
+            root.join("docs/guide.md")
+        );
+
+        fs::remove_dir_all(&scratch).unwrap();
+    }
+}
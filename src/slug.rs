@@ -0,0 +1,190 @@
+//! GitHub-compatible anchor generation for headings
+
+use crate::Heading;
+use std::collections::HashMap;
+
+/// Convert heading text to a GitHub-style anchor
+///
+/// Lowercases the text, drops punctuation (everything except letters, digits,
+/// `-`, `_` and spaces) and turns each space into a `-`. This does not handle
+/// duplicates; use [`Slugger`] or [`heading_anchors`] for that.
+pub fn heading_anchor(text: &str) -> String {
+    text.to_lowercase()
+        .chars()
+        .filter(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | ' '))
+        .map(|c| if c == ' ' { '-' } else { c })
+        .collect()
+}
+
+/// Generates unique anchors the way GitHub does for a single document
+///
+/// The first occurrence of a slug is used as-is; later duplicates get `-1`, `-2`,
+/// ... appended.
+#[derive(Debug, Clone, Default)]
+pub struct Slugger {
+    occurrences: HashMap<String, usize>,
+}
+
+impl Slugger {
+    /// Create a slugger with no anchors seen yet
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the next unique anchor for `text`
+    pub fn slug(&mut self, text: &str) -> String {
+        let base = heading_anchor(text);
+        let mut slug = base.clone();
+
+        // Like github-slugger, the counter is kept on the base slug
+        while self.occurrences.contains_key(&slug) {
+            let count = self.occurrences.entry(base.clone()).or_default();
+            *count += 1;
+            slug = format!("{}-{}", base, count);
+        }
+
+        self.occurrences.insert(slug.clone(), 0);
+        slug
+    }
+}
+
+/// Get the unique anchor of every heading, in document order
+///
+/// Like GitHub, anchors are derived from the rendered text, so inline markdown is
+/// stripped first. Explicit `{#id}` attributes are used as-is and reserve their
+/// anchor, so later generated slugs avoid them.
+pub fn heading_anchors(headings: &[Heading]) -> Vec<String> {
+    let mut slugger = Slugger::new();
+    headings
+        .iter()
+        .map(|h| match &h.id {
+            Some(id) => {
+                slugger.occurrences.entry(id.clone()).or_insert(0);
+                id.clone()
+            }
+            None => slugger.slug(&h.plain_text()),
+        })
+        .collect()
+}
+
+impl Heading {
+    /// Anchor for this heading: its explicit `{#id}` if present, otherwise the
+    /// GitHub-style slug (ignoring duplicates elsewhere in the document)
+    pub fn slug(&self) -> String {
+        match &self.id {
+            Some(id) => id.clone(),
+            None => heading_anchor(&self.plain_text()),
+        }
+    }
+}
+
+/// Why the headings in a [`DuplicateHeadings`] group collide
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DuplicateKind {
+    /// The headings have identical text
+    Text,
+    /// The texts differ but produce the same anchor (e.g. `C++` and `C`)
+    Anchor,
+}
+
+/// A set of headings that share an anchor
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DuplicateHeadings {
+    /// Whether the texts or only the anchors collide
+    pub kind: DuplicateKind,
+    /// The shared anchor, before any `-1`, `-2` disambiguation
+    pub anchor: String,
+    /// Line numbers of all involved headings
+    pub line_numbers: Vec<usize>,
+}
+
+/// Find headings whose anchors collide within a document
+///
+/// Anchors are compared before duplicates are disambiguated, so every group
+/// reports headings where a `#anchor` link can only ever reach the first one.
+/// Explicit `{#id}` attributes take part in the comparison. Groups are ordered by
+/// their first heading.
+pub fn find_duplicate_headings(headings: &[Heading]) -> Vec<DuplicateHeadings> {
+    let mut groups: Vec<(String, Vec<&Heading>)> = Vec::new();
+
+    for h in headings {
+        let anchor = h.slug();
+        match groups.iter_mut().find(|(a, _)| *a == anchor) {
+            Some((_, members)) => members.push(h),
+            None => groups.push((anchor, vec![h])),
+        }
+    }
+
+    groups
+        .into_iter()
+        .filter(|(_, members)| members.len() > 1)
+        .map(|(anchor, members)| {
+            let same_text = members.iter().all(|h| h.text == members[0].text);
+            DuplicateHeadings {
+                kind: if same_text {
+                    DuplicateKind::Text
+                } else {
+                    DuplicateKind::Anchor
+                },
+                anchor,
+                line_numbers: members.iter().map(|h| h.line_number).collect(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_headings;
+
+    #[test]
+    fn test_heading_anchor() {
+        assert_eq!(heading_anchor("Getting Started"), "getting-started");
+        assert_eq!(heading_anchor("What's new in v1.2?"), "whats-new-in-v12");
+        assert_eq!(
+            heading_anchor("snake_case & kebab-case"),
+            "snake_case--kebab-case"
+        );
+        assert_eq!(heading_anchor("Über Ärger"), "über-ärger");
+    }
+
+    #[test]
+    fn test_duplicate_anchors() {
+        let headings = parse_headings("# Examples\n## Examples\n## Examples 1\n### Examples\n");
+        assert_eq!(
+            heading_anchors(&headings),
+            ["examples", "examples-1", "examples-1-1", "examples-2"]
+        );
+        assert_eq!(headings[1].slug(), "examples");
+
+        let headings = parse_headings("## The `parse` [API](api.md)\n");
+        assert_eq!(headings[0].slug(), "the-parse-api");
+    }
+
+    #[test]
+    fn test_explicit_ids_reserve_anchors() {
+        let headings = parse_headings("# Setup {#install}\n## Install\n");
+        assert_eq!(heading_anchors(&headings), ["install", "install-1"]);
+        assert_eq!(headings[0].slug(), "install");
+    }
+
+    #[test]
+    fn test_find_duplicate_headings() {
+        let headings =
+            parse_headings("# Usage\n## C++\n## Usage\n## C\n## Setup {#usage}\n## Other\n");
+        let duplicates = find_duplicate_headings(&headings);
+
+        assert_eq!(duplicates.len(), 2);
+        assert_eq!(duplicates[0].anchor, "usage");
+        assert_eq!(duplicates[0].kind, DuplicateKind::Anchor);
+        assert_eq!(duplicates[0].line_numbers, [1, 3, 5]);
+        assert_eq!(duplicates[1].anchor, "c");
+        assert_eq!(duplicates[1].line_numbers, [2, 4]);
+
+        let same = find_duplicate_headings(&parse_headings("## FAQ\n## FAQ\n"));
+        assert_eq!(same[0].kind, DuplicateKind::Text);
+    }
+}
@@ -0,0 +1,119 @@
+//! Splitting a document into one file per section
+
+use crate::inline::strip_inline_markdown;
+use crate::toc::escape_link_text;
+use crate::{Heading, Slugger};
+
+/// A section split out of a document by [`split_document`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SplitSection {
+    /// The section's heading
+    pub heading: Heading,
+    /// Unique file name derived from the heading, e.g. `getting-started.md`
+    pub file_name: String,
+    /// The section's content, including subsections, ending in a single newline
+    pub content: String,
+}
+
+/// Split out every section at `level`, each with its subsections
+///
+/// File names are the headings' anchors (see [`Slugger`]) with a `.md`
+/// extension, falling back to `section` for headings without any usable
+/// characters. Each file runs up to the next selected heading, so content
+/// between sections that belongs to neither (such as a shallower heading and its
+/// text) goes with the section before it, and nothing after the first selected
+/// heading is lost. See [`split_index`] for the part before it.
+pub fn split_document(content: &str, headings: &[Heading], level: u8) -> Vec<SplitSection> {
+    let selected: Vec<&Heading> = headings.iter().filter(|h| h.level == level).collect();
+    let mut slugger = Slugger::new();
+
+    selected
+        .iter()
+        .enumerate()
+        .map(|(idx, heading)| {
+            let end = selected
+                .get(idx + 1)
+                .map_or(content.len(), |next| next.byte_offset);
+            let section = &content[heading.byte_offset..end];
+            let text = strip_inline_markdown(&heading.text);
+            let slug = match slugger.slug(&text) {
+                slug if slug.trim_matches('-').is_empty() => slugger.slug("section"),
+                slug => slug,
+            };
+            SplitSection {
+                heading: (*heading).clone(),
+                file_name: format!("{}.md", slug),
+                content: format!("{}\n", section.trim_end()),
+            }
+        })
+        .collect()
+}
+
+/// Build an index page linking to the split-out sections
+///
+/// The index starts with the document's content before the first split section
+/// (typically the title and introduction), followed by a bullet list with one
+/// link per file.
+pub fn split_index(content: &str, sections: &[SplitSection]) -> String {
+    let preamble_lines = sections
+        .first()
+        .map_or(usize::MAX, |s| s.heading.line_number - 1);
+    let preamble: Vec<&str> = content.lines().take(preamble_lines).collect();
+    let preamble = preamble.join("\n");
+
+    let mut index = String::new();
+    if !preamble.trim().is_empty() {
+        index.push_str(preamble.trim_end());
+        index.push_str("\n\n");
+    }
+    for section in sections {
+        index.push_str(&format!(
+            "- [{}]({})\n",
+            escape_link_text(&section.heading.text),
+            section.file_name
+        ));
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_headings;
+
+    #[test]
+    fn test_split_document() {
+        let content = "# Design\n\nIntro.\n\n## Getting *Started*\n\nText.\n\n### Details\n\nMore.\n\n## FAQ\n\nQ.\n\n## FAQ\n\n## ???\n";
+        let headings = parse_headings(content);
+        let sections = split_document(content, &headings, 2);
+
+        let names: Vec<_> = sections.iter().map(|s| s.file_name.as_str()).collect();
+        assert_eq!(
+            names,
+            ["getting-started.md", "faq.md", "faq-1.md", "section.md"]
+        );
+        assert_eq!(
+            sections[0].content,
+            "## Getting *Started*\n\nText.\n\n### Details\n\nMore.\n"
+        );
+        assert_eq!(sections[3].content, "## ???\n");
+
+        // Content between the selected sections stays with the one before it
+        let parts = "## A\n\na\n\n# Part [2]\n\nlost?\n\n## B\n\nb\n";
+        let parted = split_document(parts, &parse_headings(parts), 2);
+        assert_eq!(parted[0].content, "## A\n\na\n\n# Part [2]\n\nlost?\n");
+        assert_eq!(parted[1].content, "## B\n\nb\n");
+
+        let parted = split_document(parts, &parse_headings(parts), 1);
+        assert_eq!(
+            split_index(parts, &parted),
+            "## A\n\na\n\n- [Part \\[2\\]](part-2.md)\n"
+        );
+
+        assert_eq!(
+            split_index(content, &sections[..2]),
+            "# Design\n\nIntro.\n\n- [Getting *Started*](getting-started.md)\n- [FAQ](faq.md)\n"
+        );
+    }
+}
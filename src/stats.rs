@@ -0,0 +1,108 @@
+//! Per-section size statistics
+
+use crate::scan::CodeBlockTracker;
+use crate::{Heading, get_section_range, parse_code_blocks, parse_links};
+
+/// Size statistics for one section
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SectionStats {
+    /// Line number of the section's heading (1-indexed)
+    pub line_number: usize,
+    /// Heading level (1-6)
+    pub level: u8,
+    /// Heading text
+    pub text: String,
+    /// Number of lines, including the heading and trailing blank lines
+    pub lines: usize,
+    /// Number of whitespace-separated words outside code blocks, including heading text
+    pub words: usize,
+    /// Number of fenced code blocks
+    pub code_blocks: usize,
+    /// Number of links
+    pub links: usize,
+}
+
+/// Compute size statistics for every section
+///
+/// Each section spans the range reported by [`get_section_range`], so the counts
+/// of a section include its subsections. Code blocks are attributed to the
+/// section containing their opening fence.
+pub fn section_stats(content: &str, headings: &[Heading]) -> Vec<SectionStats> {
+    let mut code = CodeBlockTracker::default();
+    let mut words_per_line: Vec<usize> = content
+        .lines()
+        .map(|line| {
+            if code.is_code(line) {
+                0
+            } else {
+                line.split_whitespace().count()
+            }
+        })
+        .collect();
+
+    // Count heading text rather than `#` marks or setext underlines
+    for h in headings {
+        let source_lines = content[h.byte_range()].lines().count().max(1);
+        let first = h.line_number - 1;
+        for words in words_per_line.iter_mut().skip(first).take(source_lines) {
+            *words = 0;
+        }
+        if let Some(words) = words_per_line.get_mut(first) {
+            *words = h.text.split_whitespace().count();
+        }
+    }
+
+    let code_starts: Vec<usize> = parse_code_blocks(content)
+        .iter()
+        .map(|b| b.start_line)
+        .collect();
+    let link_lines: Vec<usize> = parse_links(content).iter().map(|l| l.line_number).collect();
+    let total_lines = words_per_line.len();
+
+    headings
+        .iter()
+        .map(|h| {
+            let (start, end) = get_section_range(headings, h);
+            let end = end.unwrap_or(total_lines + 1);
+            let in_section = |line: &&usize| (start..end).contains(*line);
+
+            SectionStats {
+                line_number: h.line_number,
+                level: h.level,
+                text: h.text.clone(),
+                lines: end - start,
+                words: words_per_line[start - 1..(end - 1).min(total_lines)]
+                    .iter()
+                    .sum(),
+                code_blocks: code_starts.iter().filter(in_section).count(),
+                links: link_lines.iter().filter(in_section).count(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_headings;
+
+    #[test]
+    fn test_section_stats() {
+        let content = "# Guide\n\nSee [docs](docs.md).\n\n## Build\n\nRun this command:\n\n```sh\ncargo build --release\n```\n";
+        let headings = parse_headings(content);
+        let stats = section_stats(content, &headings);
+
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].lines, 11);
+        assert_eq!(stats[0].words, 1 + 2 + 1 + 3);
+        assert_eq!(stats[0].code_blocks, 1);
+        assert_eq!(stats[0].links, 1);
+
+        assert_eq!(stats[1].text, "Build");
+        assert_eq!(stats[1].lines, 7);
+        assert_eq!(stats[1].words, 1 + 3);
+        assert_eq!(stats[1].code_blocks, 1);
+        assert_eq!(stats[1].links, 0);
+    }
+}
@@ -0,0 +1,171 @@
+//! Pipe table extraction
+
+use crate::scan::CodeBlockTracker;
+
+/// Column alignment from a table's delimiter row
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Alignment {
+    /// `---`
+    #[default]
+    None,
+    /// `:---`
+    Left,
+    /// `:---:`
+    Center,
+    /// `---:`
+    Right,
+}
+
+/// A pipe table found in a document
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Table {
+    /// Line number of the header row (1-indexed)
+    pub start_line: usize,
+    /// Line number of the last row
+    pub end_line: usize,
+    /// Header cells
+    pub headers: Vec<String>,
+    /// Alignment of each column
+    pub alignments: Vec<Alignment>,
+    /// Body rows, each padded or truncated to the number of header cells
+    pub rows: Vec<Vec<String>>,
+}
+
+/// Split a table row into trimmed cells, honoring escaped pipes and code spans
+fn split_row(line: &str) -> Vec<String> {
+    let trimmed = line.trim();
+    let trimmed = trimmed.strip_prefix('|').unwrap_or(trimmed);
+    let trimmed = match trimmed.strip_suffix('|') {
+        Some(rest) if !rest.ends_with('\\') => rest,
+        _ => trimmed,
+    };
+
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut in_code = false;
+    let mut chars = trimmed.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'|') => {
+                cell.push('|');
+                chars.next();
+            }
+            '`' => {
+                in_code = !in_code;
+                cell.push(c);
+            }
+            '|' if !in_code => cells.push(std::mem::take(&mut cell).trim().to_string()),
+            _ => cell.push(c),
+        }
+    }
+    cells.push(cell.trim().to_string());
+    cells
+}
+
+/// Parse a delimiter row (`| --- | :-: |`) into column alignments
+fn parse_delimiter_row(line: &str) -> Option<Vec<Alignment>> {
+    if !line.contains(['|', '-']) {
+        return None;
+    }
+
+    split_row(line)
+        .iter()
+        .map(|cell| {
+            let left = cell.starts_with(':');
+            let right = cell.ends_with(':');
+            let dashes = cell.trim_start_matches(':').trim_end_matches(':');
+            if dashes.is_empty() || !dashes.chars().all(|c| c == '-') {
+                return None;
+            }
+            Some(match (left, right) {
+                (true, true) => Alignment::Center,
+                (true, false) => Alignment::Left,
+                (false, true) => Alignment::Right,
+                (false, false) => Alignment::None,
+            })
+        })
+        .collect()
+}
+
+/// Extract all pipe tables (GitHub Flavored Markdown style) from markdown content
+///
+/// A table starts with a header row followed by a delimiter row with the same
+/// number of cells, and ends at the first blank line or line without a pipe.
+/// Tables inside code blocks are ignored.
+pub fn parse_tables(content: &str) -> Vec<Table> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut code = CodeBlockTracker::default();
+    let mut tables = Vec::new();
+    let mut idx = 0;
+
+    while idx < lines.len() {
+        let line = lines[idx];
+        if code.is_code(line) || !line.contains('|') {
+            idx += 1;
+            continue;
+        }
+
+        let headers = split_row(line);
+        let Some(alignments) = lines
+            .get(idx + 1)
+            .and_then(|next| parse_delimiter_row(next))
+            .filter(|a| a.len() == headers.len())
+        else {
+            idx += 1;
+            continue;
+        };
+
+        let start = idx;
+        let mut rows = Vec::new();
+        idx += 2;
+        while let Some(row) = lines.get(idx)
+            && !row.trim().is_empty()
+            && row.contains('|')
+        {
+            let mut cells = split_row(row);
+            cells.resize(headers.len(), String::new());
+            rows.push(cells);
+            idx += 1;
+        }
+
+        // Keep the code block tracker in sync with the lines we consumed
+        for row in &lines[start + 1..idx] {
+            code.is_code(row);
+        }
+
+        tables.push(Table {
+            start_line: start + 1,
+            end_line: idx,
+            headers,
+            alignments,
+            rows,
+        });
+    }
+
+    tables
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tables() {
+        let content = "# Matrix\n\n| Target | Tier | Notes |\n|:-------|:----:|------:|\n| linux | 1 | `a|b` |\n| wasm \\| web | 2 |\n\nAfter | the table\n\n```\n| a | b |\n|---|---|\n```\n";
+        let tables = parse_tables(content);
+
+        assert_eq!(tables.len(), 1);
+        let table = &tables[0];
+        assert_eq!((table.start_line, table.end_line), (3, 6));
+        assert_eq!(table.headers, ["Target", "Tier", "Notes"]);
+        assert_eq!(
+            table.alignments,
+            [Alignment::Left, Alignment::Center, Alignment::Right]
+        );
+        assert_eq!(table.rows[0], ["linux", "1", "`a|b`"]);
+        assert_eq!(table.rows[1], ["wasm | web", "2", ""]);
+    }
+}
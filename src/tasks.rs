@@ -0,0 +1,128 @@
+//! Task list item extraction
+
+use crate::parse_headings;
+use crate::scan::{CodeBlockTracker, indent_width};
+use crate::search::ancestry_at;
+
+/// A task list item (`- [ ] todo` / `- [x] done`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TaskItem {
+    /// Line number of the item (1-indexed)
+    pub line_number: usize,
+    /// Whether the box is ticked
+    pub checked: bool,
+    /// Item text after the checkbox
+    pub text: String,
+    /// Nesting depth within its list (0 for top-level items)
+    pub depth: usize,
+    /// Texts of the containing headings, outermost first (empty before the first heading)
+    pub heading_path: Vec<String>,
+}
+
+/// Split a list item line into its marker indent and the text after the marker
+pub(crate) fn split_list_marker(line: &str) -> Option<(usize, &str)> {
+    let indent = indent_width(line);
+    let trimmed = line.trim_start();
+
+    let digits = trimmed.chars().take_while(|c| c.is_ascii_digit()).count();
+    let rest = if digits > 0 {
+        trimmed[digits..].strip_prefix(['.', ')'])?
+    } else {
+        trimmed.strip_prefix(['-', '*', '+'])?
+    };
+
+    if rest.is_empty() {
+        Some((indent, rest))
+    } else {
+        rest.strip_prefix([' ', '\t']).map(|text| (indent, text))
+    }
+}
+
+/// Parse a `[ ]`/`[x]` checkbox at the start of list item text
+pub(crate) fn parse_checkbox(text: &str) -> Option<(bool, &str)> {
+    let checked = match text.get(..3)? {
+        "[ ]" => false,
+        "[x]" | "[X]" => true,
+        _ => return None,
+    };
+
+    let rest = &text[3..];
+    if rest.is_empty() {
+        Some((checked, rest))
+    } else {
+        rest.strip_prefix([' ', '\t']).map(|t| (checked, t.trim()))
+    }
+}
+
+/// Extract all task list items from markdown content
+///
+/// Depth counts enclosing list items of any kind, so a task nested under a plain
+/// bullet has depth 1. Items inside code blocks are ignored.
+pub fn parse_tasks(content: &str) -> Vec<TaskItem> {
+    let headings = parse_headings(content);
+    let mut code = CodeBlockTracker::default();
+    let mut indents: Vec<usize> = Vec::new();
+    let mut tasks = Vec::new();
+
+    for (idx, line) in content.lines().enumerate() {
+        if code.is_code(line) {
+            continue;
+        }
+
+        let Some((indent, text)) = split_list_marker(line) else {
+            // An unindented paragraph ends the list
+            if !line.trim().is_empty() && indent_width(line) == 0 {
+                indents.clear();
+            }
+            continue;
+        };
+
+        while indents.last().is_some_and(|&last| last >= indent) {
+            indents.pop();
+        }
+        let depth = indents.len();
+        indents.push(indent);
+
+        if let Some((checked, text)) = parse_checkbox(text) {
+            let line_number = idx + 1;
+            tasks.push(TaskItem {
+                line_number,
+                checked,
+                text: text.to_string(),
+                depth,
+                heading_path: ancestry_at(&headings, line_number)
+                    .iter()
+                    .map(|h| h.text.clone())
+                    .collect(),
+            });
+        }
+    }
+
+    tasks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tasks() {
+        let content = "# Notes\n\n## Action items\n\n- [ ] Write docs\n  - [x] Outline\n- Release\n  1. [X] Tag\n- [link](x.md)\n\n```\n- [ ] not a task\n```\n";
+        let tasks = parse_tasks(content);
+
+        assert_eq!(tasks.len(), 3);
+        assert_eq!(tasks[0].text, "Write docs");
+        assert!(!tasks[0].checked);
+        assert_eq!(tasks[0].depth, 0);
+        assert_eq!(tasks[0].heading_path, ["Notes", "Action items"]);
+
+        assert_eq!(tasks[1].text, "Outline");
+        assert!(tasks[1].checked);
+        assert_eq!(tasks[1].depth, 1);
+
+        assert_eq!(tasks[2].text, "Tag");
+        assert_eq!(tasks[2].depth, 1);
+        assert_eq!(tasks[2].line_number, 8);
+    }
+}
@@ -0,0 +1,268 @@
+//! Table of contents generation
+
+use crate::edit::{line_ending_at, next_line_start};
+use crate::scan::CodeBlockTracker;
+use crate::{Heading, heading_anchors, parse_front_matter, parse_headings};
+
+/// Marker comment opening a maintained TOC
+pub const TOC_START_MARKER: &str = "<!-- toc -->";
+/// Marker comment closing a maintained TOC
+pub const TOC_END_MARKER: &str = "<!-- tocstop -->";
+
+/// Formatting options for [`generate_toc`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TocOptions {
+    /// List marker for each entry (`-`, `*` or `+`)
+    pub bullet: char,
+    /// Spaces of indentation per nesting level
+    pub indent: usize,
+    /// Shallowest heading level to include (e.g. 2 to leave out the H1 title)
+    pub min_level: u8,
+    /// Deepest heading level to include
+    pub max_level: u8,
+    /// Number the entries (`1.`, `2.`, ...) instead of using `bullet`
+    pub numbered: bool,
+}
+
+impl Default for TocOptions {
+    fn default() -> Self {
+        Self {
+            bullet: '-',
+            indent: 2,
+            min_level: 1,
+            max_level: 6,
+            numbered: false,
+        }
+    }
+}
+
+/// Escape characters that would end the link text early
+pub(crate) fn escape_link_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('[', "\\[")
+        .replace(']', "\\]")
+}
+
+/// Generate a nested markdown list linking to every heading
+///
+/// Entries are indented by their level relative to the shallowest heading, and
+/// link to the anchors from [`heading_anchors`], so duplicate headings and
+/// explicit `{#id}` attributes resolve the same way GitHub renders them.
+/// Only headings between `min_level` and `max_level` are listed, and headings
+/// with the Pandoc `.unlisted` class are left out. Numbered entries count up
+/// within each list and are indented by at least three spaces per level, so
+/// nested lists stay nested. The result ends with a newline unless there are no
+/// entries.
+pub fn generate_toc(headings: &[Heading], opts: &TocOptions) -> String {
+    let anchors = heading_anchors(headings);
+    let listed = |h: &Heading| {
+        (opts.min_level..=opts.max_level).contains(&h.level) && !h.has_class("unlisted")
+    };
+    let base = headings
+        .iter()
+        .filter(|h| listed(h))
+        .map(|h| h.level)
+        .min()
+        .unwrap_or(1);
+
+    let indent = if opts.numbered {
+        opts.indent.max(3)
+    } else {
+        opts.indent
+    };
+    let mut counters = [0_usize; 6];
+
+    headings
+        .iter()
+        .zip(&anchors)
+        .filter(|(h, _)| listed(h))
+        .map(|(h, anchor)| {
+            let depth = (h.level - base) as usize;
+            let marker = if opts.numbered {
+                counters[depth] += 1;
+                counters[depth + 1..].fill(0);
+                format!("{}.", counters[depth])
+            } else {
+                opts.bullet.to_string()
+            };
+            format!(
+                "{}{} [{}](#{})\n",
+                " ".repeat(indent * depth),
+                marker,
+                escape_link_text(&h.plain_text()),
+                anchor
+            )
+        })
+        .collect()
+}
+
+/// Regenerate the TOC between `<!-- toc -->` and `<!-- tocstop -->` markers
+///
+/// Everything between the first pair of marker lines outside code blocks is
+/// replaced by [`generate_toc`] over the document's current headings, separated
+/// from the markers by blank lines. Returns `None` if the document has no
+/// complete marker pair.
+pub fn update_toc(content: &str, opts: &TocOptions) -> Option<String> {
+    let mut code = CodeBlockTracker::default();
+    let mut start = None;
+    let mut end = None;
+    let mut offset = 0;
+
+    for raw in content.split_inclusive('\n') {
+        let line = raw.trim();
+        if !code.is_code(raw.trim_end_matches(['\r', '\n'])) {
+            if start.is_none() && line == TOC_START_MARKER {
+                start = Some(offset + raw.len());
+            } else if start.is_some() && line == TOC_END_MARKER {
+                end = Some(offset);
+                break;
+            }
+        }
+        offset += raw.len();
+    }
+
+    let (start, end) = (start?, end?);
+    let eol = if content[..start].ends_with("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    let toc = generate_toc(&parse_headings(content), opts).replace('\n', eol);
+
+    let mut output = String::with_capacity(content.len() + toc.len());
+    output.push_str(&content[..start]);
+    if !content[..start].ends_with('\n') {
+        output.push_str(eol);
+    }
+    output.push_str(eol);
+    output.push_str(&toc);
+    output.push_str(eol);
+    output.push_str(&content[end..]);
+    Some(output)
+}
+
+/// Update the TOC between markers, adding a marked TOC if the document has none
+///
+/// A new TOC goes after the first H1 heading, or at the top of the document
+/// (after any front matter) when there is no H1.
+pub fn insert_or_update_toc(content: &str, opts: &TocOptions) -> String {
+    if let Some(updated) = update_toc(content, opts) {
+        return updated;
+    }
+
+    let headings = parse_headings(content);
+    let at = match headings.iter().find(|h| h.level == 1) {
+        Some(title) => next_line_start(content, title.byte_range().end),
+        None => parse_front_matter(content).map_or(0, |fm| {
+            (1..=fm.end_line).fold(0, |offset, _| next_line_start(content, offset))
+        }),
+    };
+    let eol = line_ending_at(content, at.min(content.len().saturating_sub(1)));
+    let (before, after) = content.split_at(at);
+
+    let mut output = String::with_capacity(content.len() + 64);
+    output.push_str(before);
+    if !before.is_empty() {
+        if !before.ends_with('\n') {
+            output.push_str(eol);
+        }
+        output.push_str(eol);
+    }
+    output.push_str(TOC_START_MARKER);
+    output.push_str(eol);
+    output.push_str(TOC_END_MARKER);
+    output.push_str(eol);
+    if !after.is_empty() && !after.starts_with(['\r', '\n']) {
+        output.push_str(eol);
+    }
+    output.push_str(after);
+
+    update_toc(&output, opts).unwrap_or(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_headings;
+
+    #[test]
+    fn test_generate_toc() {
+        let content = "## Intro\n\n### `mdi` \\[beta\\]\n\n## Intro\n\n## Setup {#install}\n\n## Changelog {.unlisted}\n";
+        let headings = parse_headings(content);
+
+        assert_eq!(
+            generate_toc(&headings, &TocOptions::default()),
+            "- [Intro](#intro)\n  - [mdi \\[beta\\]](#mdi-beta)\n- [Intro](#intro-1)\n- [Setup](#install)\n"
+        );
+
+        let opts = TocOptions {
+            bullet: '*',
+            indent: 4,
+            ..Default::default()
+        };
+        assert!(generate_toc(&headings, &opts).contains("    * [mdi"));
+    }
+
+    #[test]
+    fn test_generate_toc_level_range() {
+        let headings =
+            parse_headings("# Title\n\n## Install\n\n### Linux\n\n#### Details\n\n## Usage\n");
+        let opts = TocOptions {
+            min_level: 2,
+            max_level: 3,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            generate_toc(&headings, &opts),
+            "- [Install](#install)\n  - [Linux](#linux)\n- [Usage](#usage)\n"
+        );
+
+        let numbered = TocOptions {
+            numbered: true,
+            ..opts
+        };
+        assert_eq!(
+            generate_toc(&headings, &numbered),
+            "1. [Install](#install)\n   1. [Linux](#linux)\n2. [Usage](#usage)\n"
+        );
+    }
+
+    #[test]
+    fn test_update_toc_between_markers() {
+        let content = "# Project\n\n<!-- toc -->\n- [Old](#old)\n<!-- tocstop -->\n\n## Usage\n\n```\n<!-- tocstop -->\n```\n";
+        let updated = update_toc(content, &TocOptions::default()).unwrap();
+        assert_eq!(
+            updated,
+            "# Project\n\n<!-- toc -->\n\n- [Project](#project)\n  - [Usage](#usage)\n\n<!-- tocstop -->\n\n## Usage\n\n```\n<!-- tocstop -->\n```\n"
+        );
+
+        // Updating again is a no-op
+        assert_eq!(
+            update_toc(&updated, &TocOptions::default()).unwrap(),
+            updated
+        );
+        assert!(update_toc("# No markers\n", &TocOptions::default()).is_none());
+    }
+
+    #[test]
+    fn test_insert_or_update_toc() {
+        let opts = TocOptions {
+            min_level: 2,
+            ..Default::default()
+        };
+        let content = "# Project\n\nIntro.\n\n## Usage\n";
+        let inserted = insert_or_update_toc(content, &opts);
+        assert_eq!(
+            inserted,
+            "# Project\n\n<!-- toc -->\n\n- [Usage](#usage)\n\n<!-- tocstop -->\n\nIntro.\n\n## Usage\n"
+        );
+        assert_eq!(insert_or_update_toc(&inserted, &opts), inserted);
+
+        // Without an H1 the TOC goes to the top, below front matter
+        assert_eq!(
+            insert_or_update_toc("---\nx: 1\n---\n## Usage", &opts),
+            "---\nx: 1\n---\n\n<!-- toc -->\n\n- [Usage](#usage)\n\n<!-- tocstop -->\n\n## Usage"
+        );
+    }
+}
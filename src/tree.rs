@@ -0,0 +1,284 @@
+//! Hierarchical view of a document's headings
+
+use crate::{Heading, parse_headings};
+
+/// A heading and its position in the tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Node {
+    heading: Heading,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    /// Index one past the last descendant (nodes are stored in document order)
+    subtree_end: usize,
+}
+
+/// Tree of nested sections built from a flat heading list
+///
+/// Each heading becomes a node whose children are the headings nested directly
+/// below it. Nodes are stored in document order, which is also the depth-first
+/// (pre-order) traversal order.
+///
+/// Deserializing rebuilds the tree from the serialized headings and rejects
+/// input whose parent and child indices don't match them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "SerializedTree"))]
+pub struct SectionTree {
+    nodes: Vec<Node>,
+    roots: Vec<usize>,
+}
+
+/// A [`SectionTree`] as read by serde, before its indices are checked
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct SerializedTree {
+    nodes: Vec<Node>,
+    roots: Vec<usize>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<SerializedTree> for SectionTree {
+    type Error = &'static str;
+
+    fn try_from(serialized: SerializedTree) -> Result<Self, Self::Error> {
+        let headings: Vec<Heading> = serialized
+            .nodes
+            .iter()
+            .map(|node| node.heading.clone())
+            .collect();
+        let tree = Self::from_headings(&headings);
+        if tree.nodes != serialized.nodes || tree.roots != serialized.roots {
+            return Err("section tree indices don't match its headings");
+        }
+        Ok(tree)
+    }
+}
+
+/// A borrowed handle to one section in a [`SectionTree`]
+#[derive(Debug, Clone, Copy)]
+pub struct Section<'a> {
+    tree: &'a SectionTree,
+    index: usize,
+}
+
+/// Parse markdown content and build its section tree
+pub fn build_section_tree(content: &str) -> SectionTree {
+    SectionTree::from_headings(&parse_headings(content))
+}
+
+impl SectionTree {
+    /// Build a tree from headings in document order
+    ///
+    /// A heading's parent is the closest preceding heading with a lower level, so
+    /// skipped levels (`#` followed by `###`) still nest under the `#`.
+    pub fn from_headings(headings: &[Heading]) -> Self {
+        let mut nodes: Vec<Node> = Vec::with_capacity(headings.len());
+        let mut roots = Vec::new();
+        let mut stack: Vec<usize> = Vec::new();
+
+        for (index, heading) in headings.iter().enumerate() {
+            while let Some(&top) = stack.last() {
+                if nodes[top].heading.level < heading.level {
+                    break;
+                }
+                nodes[top].subtree_end = index;
+                stack.pop();
+            }
+
+            let parent = stack.last().copied();
+            match parent {
+                Some(p) => nodes[p].children.push(index),
+                None => roots.push(index),
+            }
+
+            nodes.push(Node {
+                heading: heading.clone(),
+                parent,
+                children: Vec::new(),
+                subtree_end: index + 1,
+            });
+            stack.push(index);
+        }
+
+        for index in stack {
+            nodes[index].subtree_end = nodes.len();
+        }
+
+        Self { nodes, roots }
+    }
+
+    /// Number of sections in the tree
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Whether the document has no headings
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Get a section by its index in document order
+    pub fn get(&self, index: usize) -> Option<Section<'_>> {
+        (index < self.nodes.len()).then_some(Section { tree: self, index })
+    }
+
+    /// Top-level sections (those without a parent)
+    pub fn roots(&self) -> impl Iterator<Item = Section<'_>> {
+        self.roots
+            .iter()
+            .map(move |&index| Section { tree: self, index })
+    }
+
+    /// All sections in depth-first (document) order
+    pub fn iter(&self) -> impl Iterator<Item = Section<'_>> {
+        (0..self.nodes.len()).map(move |index| Section { tree: self, index })
+    }
+}
+
+impl<'a> Section<'a> {
+    fn node(&self) -> &'a Node {
+        &self.tree.nodes[self.index]
+    }
+
+    /// Index of this section in document order
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The heading that starts this section
+    pub fn heading(&self) -> &'a Heading {
+        &self.node().heading
+    }
+
+    /// The enclosing section, if any
+    pub fn parent(&self) -> Option<Section<'a>> {
+        self.node().parent.map(|index| Section {
+            tree: self.tree,
+            index,
+        })
+    }
+
+    /// Sections nested directly below this one
+    pub fn children(&self) -> impl Iterator<Item = Section<'a>> + use<'a> {
+        let tree = self.tree;
+        self.node()
+            .children
+            .iter()
+            .map(move |&index| Section { tree, index })
+    }
+
+    /// Sections sharing this one's parent (the roots for top-level sections)
+    fn siblings(&self) -> &'a [usize] {
+        match self.node().parent {
+            Some(parent) => &self.tree.nodes[parent].children,
+            None => &self.tree.roots,
+        }
+    }
+
+    /// The following section with the same parent
+    pub fn next_sibling(&self) -> Option<Section<'a>> {
+        let siblings = self.siblings();
+        let pos = siblings.iter().position(|&i| i == self.index)?;
+        siblings.get(pos + 1).map(|&index| Section {
+            tree: self.tree,
+            index,
+        })
+    }
+
+    /// The preceding section with the same parent
+    pub fn prev_sibling(&self) -> Option<Section<'a>> {
+        let siblings = self.siblings();
+        let pos = siblings.iter().position(|&i| i == self.index)?;
+        let index = *siblings.get(pos.checked_sub(1)?)?;
+        Some(Section {
+            tree: self.tree,
+            index,
+        })
+    }
+
+    /// All sections nested below this one, in depth-first order
+    pub fn descendants(&self) -> impl Iterator<Item = Section<'a>> + use<'a> {
+        let tree = self.tree;
+        (self.index + 1..self.node().subtree_end).map(move |index| Section { tree, index })
+    }
+
+    /// Nesting depth in the tree (0 for top-level sections)
+    pub fn depth(&self) -> usize {
+        std::iter::successors(self.parent(), |s| s.parent()).count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts<'a>(sections: impl Iterator<Item = Section<'a>>) -> Vec<&'a str> {
+        sections.map(|s| s.heading().text.as_str()).collect()
+    }
+
+    #[test]
+    fn test_build_section_tree() {
+        let content = "# Guide\n\n## Install\n\n### Linux\n\n### macOS\n\n## Usage\n\n# Appendix\n";
+        let tree = build_section_tree(content);
+
+        assert_eq!(tree.len(), 6);
+        assert_eq!(texts(tree.roots()), ["Guide", "Appendix"]);
+
+        let guide = tree.get(0).unwrap();
+        assert_eq!(texts(guide.children()), ["Install", "Usage"]);
+        assert_eq!(
+            texts(guide.descendants()),
+            ["Install", "Linux", "macOS", "Usage"]
+        );
+
+        let macos = tree.get(3).unwrap();
+        assert_eq!(macos.parent().unwrap().heading().text, "Install");
+        assert_eq!(macos.depth(), 2);
+
+        assert_eq!(
+            texts(tree.iter()),
+            ["Guide", "Install", "Linux", "macOS", "Usage", "Appendix"]
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let tree = build_section_tree("# Guide\n\n## Install\n");
+
+        let json = serde_json::to_string(&tree).unwrap();
+        let restored: SectionTree = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, tree);
+        assert_eq!(restored.get(1).unwrap().heading().text, "Install");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_rejects_bad_indices() {
+        let tree = build_section_tree("# Guide\n\n## Install\n");
+        let mut json: serde_json::Value = serde_json::to_value(&tree).unwrap();
+
+        json["nodes"][0]["children"][0] = 7.into();
+        assert!(serde_json::from_value::<SectionTree>(json.clone()).is_err());
+
+        json["nodes"][0]["children"][0] = 1.into();
+        json["roots"][0] = 5.into();
+        assert!(serde_json::from_value::<SectionTree>(json).is_err());
+    }
+
+    #[test]
+    fn test_skipped_levels_nest_under_closest_ancestor() {
+        let tree = build_section_tree("# Title\n\n### Deep\n\n## Shallow\n");
+
+        let title = tree.get(0).unwrap();
+        assert_eq!(texts(title.children()), ["Deep", "Shallow"]);
+        assert_eq!(tree.get(2).unwrap().parent().unwrap().index(), 0);
+
+        let deep = tree.get(1).unwrap();
+        assert_eq!(deep.next_sibling().unwrap().heading().text, "Shallow");
+        assert!(deep.prev_sibling().is_none());
+        assert_eq!(tree.get(2).unwrap().prev_sibling().unwrap().index(), 1);
+        assert!(title.next_sibling().is_none());
+    }
+}
@@ -0,0 +1,162 @@
+//! Discover markdown files below a directory
+
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Whether a path has a markdown file extension (`.md`, `.markdown`, `.mdx`)
+pub fn is_markdown_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            ["md", "markdown", "mdx"]
+                .iter()
+                .any(|md| ext.eq_ignore_ascii_case(md))
+        })
+}
+
+/// Whether a path's file name starts with a dot
+fn is_hidden(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with('.'))
+}
+
+/// Recursively collect markdown files, skipping hidden entries
+///
+/// Symlinked directories are followed, but each directory is only visited once
+/// (by its canonical path), so symlink cycles end.
+fn walk_plain(
+    dir: &Path,
+    files: &mut Vec<PathBuf>,
+    visited: &mut HashSet<PathBuf>,
+) -> io::Result<()> {
+    if !visited.insert(fs::canonicalize(dir)?) {
+        return Ok(());
+    }
+
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if is_hidden(&path) {
+            continue;
+        }
+
+        if path.is_dir() {
+            walk_plain(&path, files, visited)?;
+        } else if is_markdown_file(&path) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "ignore")]
+fn walk_ignoring(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    let walker = ignore::WalkBuilder::new(dir)
+        .require_git(false)
+        .add_custom_ignore_filename(".mdignore")
+        .build();
+
+    for entry in walker {
+        let entry = entry.map_err(io::Error::other)?;
+        if entry.file_type().is_some_and(|t| t.is_file()) && is_markdown_file(entry.path()) {
+            files.push(entry.into_path());
+        }
+    }
+    Ok(())
+}
+
+/// Collect all markdown files (`.md`, `.markdown`, `.mdx`) below `dir`, sorted by
+/// path
+///
+/// Hidden files and directories are always skipped. When built with the `ignore`
+/// feature and `respect_ignore` is set, `.gitignore` rules and `.mdignore` files
+/// (gitignore-style glob patterns) are honored as well.
+pub fn collect_markdown_files(dir: &Path, respect_ignore: bool) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+
+    #[cfg(feature = "ignore")]
+    if respect_ignore {
+        walk_ignoring(dir, &mut files)?;
+        files.sort();
+        return Ok(files);
+    }
+
+    #[cfg(not(feature = "ignore"))]
+    let _ = respect_ignore;
+
+    walk_plain(dir, &mut files, &mut HashSet::new())?;
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create a scratch directory tree for a test, removing any leftovers first
+    fn scratch_dir(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("mdi-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        for (path, content) in files {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        root
+    }
+
+    #[test]
+    fn test_collect_markdown_files() {
+        let root = scratch_dir(
+            "walk",
+            &[
+                ("b.md", "# B\n"),
+                ("a/z.markdown", "# Z\n"),
+                ("notes.txt", "# Not markdown\n"),
+                (".hidden/h.md", "# Hidden\n"),
+            ],
+        );
+
+        let files = collect_markdown_files(&root, false).unwrap();
+        assert_eq!(files, [root.join("a/z.markdown"), root.join("b.md")]);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_cycle() {
+        let root = scratch_dir("cycle", &[("docs/a.md", "# A\n")]);
+        std::os::unix::fs::symlink(&root, root.join("docs/loop")).unwrap();
+
+        let files = collect_markdown_files(&root, false).unwrap();
+        assert_eq!(files, [root.join("docs/a.md")]);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(feature = "ignore")]
+    #[test]
+    fn test_mdignore_excludes_files() {
+        let root = scratch_dir(
+            "mdignore",
+            &[
+                (".mdignore", "vendor/\n*.draft.md\n"),
+                ("guide.md", "# Guide\n"),
+                ("wip.draft.md", "# WIP\n"),
+                ("vendor/lib.md", "# Vendored\n"),
+            ],
+        );
+
+        let files = collect_markdown_files(&root, true).unwrap();
+        assert_eq!(files, [root.join("guide.md")]);
+
+        // Opting out of ignore handling finds everything again
+        let files = collect_markdown_files(&root, false).unwrap();
+        assert_eq!(files.len(), 3);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
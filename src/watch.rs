@@ -0,0 +1,40 @@
+//! Polling file watcher for `mdi watch`
+
+use anyhow::Result;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// Clear the terminal and move the cursor to the top left
+const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+
+fn modified(file: &Path) -> Option<SystemTime> {
+    fs::metadata(file).and_then(|m| m.modified()).ok()
+}
+
+/// Call `render` now and again whenever `file` changes, until interrupted
+///
+/// The file's modification time is checked every `interval`. While the file is
+/// missing (e.g. an editor replacing it) the last view stays up. Errors from
+/// `render` are shown in place of the view rather than ending the watch.
+pub fn watch(
+    file: &Path,
+    interval: Duration,
+    mut render: impl FnMut() -> Result<()>,
+) -> Result<()> {
+    let mut last = None;
+    loop {
+        let current = modified(file);
+        if current.is_some() && current != last {
+            last = current;
+            print!("{}", CLEAR_SCREEN);
+            if let Err(err) = render() {
+                println!("Error: {:#}", err);
+            }
+            io::stdout().flush()?;
+        }
+        thread::sleep(interval);
+    }
+}
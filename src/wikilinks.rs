@@ -0,0 +1,122 @@
+//! Wiki-style `[[Note]]` links as used by Obsidian and other Zettelkasten tools
+
+use crate::links::skip_code_span;
+use crate::scan::CodeBlockTracker;
+
+/// A `[[Note Name#Heading|alias]]` link
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WikiLink {
+    /// Line number of the link (1-indexed)
+    pub line_number: usize,
+    /// Name of the linked note; empty for links within the same note (`[[#Heading]]`)
+    pub target: String,
+    /// Heading fragment after `#`, e.g. `Setup` or `Setup#Linux` for nested
+    /// headings, or a `^block` reference
+    pub heading: Option<String>,
+    /// Display text after `|`
+    pub alias: Option<String>,
+    /// Whether the link embeds the note (`![[Note]]`)
+    pub embed: bool,
+}
+
+/// Parse the inside of `[[...]]`
+fn parse_inner(inner: &str, line_number: usize, embed: bool) -> Option<WikiLink> {
+    // In tables the alias separator is escaped so it doesn't split the cell
+    let inner = inner.replace("\\|", "|");
+    let (link, alias) = match inner.split_once('|') {
+        Some((link, alias)) => (link, Some(alias.trim().to_string())),
+        None => (inner.as_str(), None),
+    };
+    let (target, heading) = match link.split_once('#') {
+        Some((target, heading)) => (target, Some(heading.trim().to_string())),
+        None => (link, None),
+    };
+
+    let target = target.trim();
+    if target.is_empty() && heading.as_deref().is_none_or(str::is_empty) {
+        return None;
+    }
+    Some(WikiLink {
+        line_number,
+        target: target.to_string(),
+        heading,
+        alias,
+        embed,
+    })
+}
+
+/// Extract all wiki links from markdown content
+///
+/// Finds `[[Note]]`, `[[Note#Heading]]`, `[[Note|alias]]` and their combinations,
+/// plus `![[Note]]` embeds, in document order. Links inside code blocks and code
+/// spans are ignored.
+pub fn parse_wikilinks(content: &str) -> Vec<WikiLink> {
+    let mut code = CodeBlockTracker::default();
+    let mut links = Vec::new();
+
+    for (idx, line) in content.lines().enumerate() {
+        if code.is_code(line) {
+            continue;
+        }
+
+        let chars: Vec<char> = line.chars().collect();
+        let mut i = 0;
+        while i < chars.len() {
+            match chars[i] {
+                '\\' => i += 2,
+                '`' => i = skip_code_span(&chars, i),
+                '[' if chars.get(i + 1) == Some(&'[') => {
+                    let close = (i + 2..chars.len().saturating_sub(1))
+                        .find(|&j| chars[j] == ']' && chars[j + 1] == ']');
+                    let Some(close) = close else {
+                        i += 2;
+                        continue;
+                    };
+                    let inner: String = chars[i + 2..close].iter().collect();
+                    let embed = i > 0 && chars[i - 1] == '!';
+                    links.extend(parse_inner(&inner, idx + 1, embed));
+                    i = close + 2;
+                }
+                _ => i += 1,
+            }
+        }
+    }
+
+    links
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_wikilinks() {
+        let content = "See [[Setup Guide#Install|installing]] and [[Glossary]].\n\n![[diagram.png]] [[#Local]]\n\n| a | [[Note\\|alias]] |\n\n`[[not a link]]`\n\n```\n[[code]]\n```\n\n[[]] [[Deep#Setup#Linux]]\n";
+        let links = parse_wikilinks(content);
+
+        let summary: Vec<_> = links
+            .iter()
+            .map(|l| {
+                (
+                    l.line_number,
+                    l.target.as_str(),
+                    l.heading.as_deref(),
+                    l.alias.as_deref(),
+                    l.embed,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (1, "Setup Guide", Some("Install"), Some("installing"), false),
+                (1, "Glossary", None, None, false),
+                (3, "diagram.png", None, None, true),
+                (3, "", Some("Local"), None, false),
+                (5, "Note", None, Some("alias"), false),
+                (13, "Deep", Some("Setup#Linux"), None, false),
+            ]
+        );
+    }
+}
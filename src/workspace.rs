@@ -0,0 +1,421 @@
+//! Multi-file workspaces for cross-document queries
+
+use crate::links::is_external;
+#[cfg(feature = "rayon")]
+use crate::{GrepHit, grep_with_context};
+use crate::{
+    Heading, Link, find_section, find_section_by_path, heading_anchors, parse_headings, parse_links,
+};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+/// A parsed markdown file in a [`Workspace`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkspaceFile {
+    /// Path of the file, lexically normalized
+    pub path: PathBuf,
+    /// Full file content
+    pub content: String,
+    /// Headings parsed from the content
+    pub headings: Vec<Heading>,
+}
+
+/// Where a link between workspace files points to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResolvedLink<'a> {
+    /// The target file
+    pub file: &'a WorkspaceFile,
+    /// The heading named by the link's `#fragment`, if it has one
+    pub heading: Option<&'a Heading>,
+}
+
+/// Why a link in a [`BrokenLink`] doesn't resolve
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BrokenLinkReason {
+    /// The target file is neither in the workspace nor on disk
+    MissingFile,
+    /// The target file exists but has no heading matching the fragment
+    MissingHeading,
+}
+
+/// A relative link between files that doesn't resolve
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BrokenLink {
+    /// File containing the link
+    pub path: PathBuf,
+    /// The link itself
+    pub link: Link,
+    /// What is missing
+    pub reason: BrokenLinkReason,
+}
+
+/// A set of parsed markdown files
+#[derive(Debug, Clone, Default)]
+pub struct Workspace {
+    files: Vec<WorkspaceFile>,
+}
+
+/// Normalize `.` and `..` components without touching the filesystem
+pub(crate) fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    normalized.push("..");
+                }
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// Decode `%XX` escapes in a link path or URL component, leaving invalid
+/// sequences as they are
+///
+/// If the decoded bytes aren't valid UTF-8, `text` is returned unchanged.
+pub fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+
+    while idx < bytes.len() {
+        let hex = bytes
+            .get(idx + 1..idx + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[idx], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                idx += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                idx += 1;
+            }
+        }
+    }
+
+    String::from_utf8(decoded).unwrap_or_else(|_| text.to_string())
+}
+
+/// Resolve a relative link path against the directory of the linking file
+fn link_target_path(from: &Path, path: &str) -> PathBuf {
+    let base = from.parent().unwrap_or(Path::new(""));
+    normalize_path(&base.join(percent_decode(path)))
+}
+
+/// Parse a file's content into a [`WorkspaceFile`]
+fn parse_file(path: &Path, content: String) -> WorkspaceFile {
+    WorkspaceFile {
+        path: normalize_path(path),
+        headings: parse_headings(&content),
+        content,
+    }
+}
+
+impl Workspace {
+    /// Create an empty workspace
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load and parse the given markdown files
+    ///
+    /// With the `rayon` feature, files are read and parsed in parallel.
+    pub fn load<P: AsRef<Path>>(paths: impl IntoIterator<Item = P>) -> io::Result<Self> {
+        let paths: Vec<PathBuf> = paths.into_iter().map(|p| p.as_ref().into()).collect();
+        let read = |path: &PathBuf| Ok(parse_file(path, fs::read_to_string(path)?));
+
+        #[cfg(feature = "rayon")]
+        let files: io::Result<Vec<WorkspaceFile>> = paths.par_iter().map(read).collect();
+        #[cfg(not(feature = "rayon"))]
+        let files: io::Result<Vec<WorkspaceFile>> = paths.iter().map(read).collect();
+
+        let mut workspace = Self::new();
+        for file in files? {
+            workspace.insert_file(file);
+        }
+        Ok(workspace)
+    }
+
+    /// Load every markdown file below a directory
+    ///
+    /// See [`collect_markdown_files`](crate::collect_markdown_files) for which
+    /// files are picked up.
+    pub fn from_dir(dir: &Path, respect_ignore: bool) -> io::Result<Self> {
+        Self::load(crate::collect_markdown_files(dir, respect_ignore)?)
+    }
+
+    /// Load every file matching a glob pattern such as `docs/**/*.md`
+    #[cfg(feature = "glob")]
+    pub fn from_glob(pattern: &str) -> anyhow::Result<Self> {
+        let mut paths = glob::glob(pattern)?.collect::<Result<Vec<_>, _>>()?;
+        paths.sort();
+        Ok(Self::load(paths)?)
+    }
+
+    /// Add a file from already loaded content, replacing any file with the same path
+    pub fn add_file(&mut self, path: impl AsRef<Path>, content: String) {
+        self.insert_file(parse_file(path.as_ref(), content));
+    }
+
+    fn insert_file(&mut self, file: WorkspaceFile) {
+        match self.files.iter_mut().find(|f| f.path == file.path) {
+            Some(existing) => *existing = file,
+            None => self.files.push(file),
+        }
+    }
+
+    /// All files in the order they were added
+    pub fn files(&self) -> &[WorkspaceFile] {
+        &self.files
+    }
+
+    /// Look up a file by path
+    pub fn file(&self, path: impl AsRef<Path>) -> Option<&WorkspaceFile> {
+        let path = normalize_path(path.as_ref());
+        self.files.iter().find(|f| f.path == path)
+    }
+
+    /// Every heading in the workspace together with its file
+    pub fn headings(&self) -> impl Iterator<Item = (&WorkspaceFile, &Heading)> {
+        self.files
+            .iter()
+            .flat_map(|file| file.headings.iter().map(move |h| (file, h)))
+    }
+
+    /// Headings up to `max_depth` in every file, collected in parallel
+    #[cfg(feature = "rayon")]
+    pub fn par_outline(&self, max_depth: u8) -> Vec<(&WorkspaceFile, Vec<&Heading>)> {
+        self.files
+            .par_iter()
+            .map(|file| {
+                let headings = file.headings.iter().filter(|h| h.level <= max_depth);
+                (file, headings.collect())
+            })
+            .collect()
+    }
+
+    /// Search every file in parallel, as [`grep_with_context`] does for one
+    ///
+    /// Only files with at least one hit are returned, in workspace order.
+    #[cfg(feature = "rayon")]
+    pub fn par_search(
+        &self,
+        pattern: &str,
+        skip_code: bool,
+    ) -> Vec<(&WorkspaceFile, Vec<GrepHit>)> {
+        self.files
+            .par_iter()
+            .map(|file| {
+                let hits = grep_with_context(&file.content, &file.headings, pattern, skip_code);
+                (file, hits)
+            })
+            .filter(|(_, hits)| !hits.is_empty())
+            .collect()
+    }
+
+    /// Find a section in any file, checking files in order
+    ///
+    /// `section` is interpreted as in [`find_section`], or as a heading path
+    /// (see [`find_section_by_path`]) when it contains `/`.
+    pub fn find_section(&self, section: &str) -> Option<(&WorkspaceFile, &Heading)> {
+        self.files.iter().find_map(|file| {
+            section
+                .contains('/')
+                .then(|| find_section_by_path(&file.headings, section))
+                .flatten()
+                .or_else(|| find_section(&file.headings, section))
+                .map(|h| (file, h))
+        })
+    }
+
+    /// Check every relative link between files
+    ///
+    /// A link is broken when its target is neither a workspace file nor an
+    /// existing file on disk, or when its `#fragment` matches no heading in a
+    /// workspace file. External URLs, site-absolute paths (`/docs/...`) and
+    /// in-page links are skipped; see [`validate_anchors`](crate::validate_anchors)
+    /// for the latter.
+    pub fn validate_links(&self) -> Vec<BrokenLink> {
+        let mut broken = Vec::new();
+
+        for file in &self.files {
+            for link in parse_links(&file.content) {
+                let destination = &link.destination;
+                if is_external(destination)
+                    || destination.starts_with('/')
+                    || destination.starts_with('#')
+                {
+                    continue;
+                }
+
+                let (path, fragment) = match destination.split_once('#') {
+                    Some((path, fragment)) => (path, Some(fragment)),
+                    None => (destination.as_str(), None),
+                };
+                let target = link_target_path(&file.path, path);
+
+                let reason = match self.file(&target) {
+                    Some(target_file) => fragment
+                        .filter(|fragment| {
+                            !heading_anchors(&target_file.headings)
+                                .iter()
+                                .any(|a| a == fragment)
+                        })
+                        .map(|_| BrokenLinkReason::MissingHeading),
+                    None if target.exists() => None,
+                    None => Some(BrokenLinkReason::MissingFile),
+                };
+
+                if let Some(reason) = reason {
+                    broken.push(BrokenLink {
+                        path: file.path.clone(),
+                        link,
+                        reason,
+                    });
+                }
+            }
+        }
+
+        broken
+    }
+
+    /// Resolve a link destination found in `from` to a workspace file and heading
+    ///
+    /// Relative paths are resolved against the directory of `from`, and a bare
+    /// `#fragment` refers to `from` itself. Fragments are matched against
+    /// [`heading_anchors`]. Returns `None` for external URLs, files outside the
+    /// workspace and fragments that match no heading.
+    pub fn resolve_link(&self, from: &Path, destination: &str) -> Option<ResolvedLink<'_>> {
+        if is_external(destination) {
+            return None;
+        }
+
+        let (path, fragment) = match destination.split_once('#') {
+            Some((path, fragment)) => (path, Some(fragment)),
+            None => (destination, None),
+        };
+
+        let file = if path.is_empty() {
+            self.file(from)?
+        } else {
+            self.file(link_target_path(from, path))?
+        };
+
+        let heading = match fragment {
+            Some(fragment) => {
+                let anchors = heading_anchors(&file.headings);
+                let idx = anchors.iter().position(|a| a == fragment)?;
+                Some(&file.headings[idx])
+            }
+            None => None,
+        };
+
+        Some(ResolvedLink { file, heading })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_workspace() -> Workspace {
+        let mut workspace = Workspace::new();
+        workspace.add_file(
+            "docs/index.md",
+            "# Docs\n\nSee [install](guide/install.md#on-linux).\n".to_string(),
+        );
+        workspace.add_file(
+            "docs/guide/install.md",
+            "# Install\n\n## On Linux\n\nSee [home](../index.md).\n".to_string(),
+        );
+        workspace
+    }
+
+    #[test]
+    fn test_find_section_across_files() {
+        let workspace = sample_workspace();
+
+        let (file, heading) = workspace.find_section("linux").unwrap();
+        assert_eq!(file.path, Path::new("docs/guide/install.md"));
+        assert_eq!(heading.text, "On Linux");
+        assert_eq!(workspace.headings().count(), 3);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_parallel_queries() {
+        let workspace = sample_workspace();
+
+        let outline = workspace.par_outline(1);
+        assert_eq!(outline.len(), 2);
+        assert_eq!(outline[1].0.path, Path::new("docs/guide/install.md"));
+        assert_eq!(outline[1].1.len(), 1);
+
+        let results = workspace.par_search("see [home]", false);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0.path, Path::new("docs/guide/install.md"));
+        assert_eq!(results[0].1[0].heading_path, ["Install", "On Linux"]);
+    }
+
+    #[test]
+    fn test_resolve_relative_links() {
+        let workspace = sample_workspace();
+
+        let resolved = workspace
+            .resolve_link(Path::new("docs/index.md"), "guide/install.md#on-linux")
+            .unwrap();
+        assert_eq!(resolved.file.path, Path::new("docs/guide/install.md"));
+        assert_eq!(resolved.heading.unwrap().text, "On Linux");
+
+        let back = workspace
+            .resolve_link(Path::new("./docs/guide/install.md"), "../index.md")
+            .unwrap();
+        assert_eq!(back.file.path, Path::new("docs/index.md"));
+        assert!(back.heading.is_none());
+
+        let missing = workspace.resolve_link(Path::new("docs/index.md"), "guide/install.md#nope");
+        assert!(missing.is_none());
+        assert!(
+            workspace
+                .resolve_link(Path::new("docs/index.md"), "https://example.com")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_validate_links() {
+        let mut workspace = sample_workspace();
+        workspace.add_file(
+            "docs/guide/usage.md",
+            "# Usage\n\n[ok](install.md#on-linux) [gone](setup.md) [bad](../index.md#nope)\n\
+             [site](/docs/index.md) [web](https://example.com) [self](#usage)\n"
+                .to_string(),
+        );
+
+        let broken: Vec<_> = workspace
+            .validate_links()
+            .into_iter()
+            .map(|b| (b.link.destination, b.reason))
+            .collect();
+        assert_eq!(
+            broken,
+            [
+                ("setup.md".to_string(), BrokenLinkReason::MissingFile),
+                (
+                    "../index.md#nope".to_string(),
+                    BrokenLinkReason::MissingHeading
+                ),
+            ]
+        );
+    }
+}
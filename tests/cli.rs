@@ -0,0 +1,350 @@
+//! End-to-end tests that run the `mdi` binary
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+const GUIDE: &str = "---
+title: Guide
+---
+# Guide
+
+Intro with a [link](#usage) and [site](https://example.com).
+
+## Install
+
+Run the installer.
+
+```sh
+make install
+```
+
+### Linux
+
+Use apt. See [missing](#nowhere).
+
+## Usage
+
+```rust
+// run with cargo run
+fn main() {}
+```
+
+Run it.
+";
+
+/// Write `content` to a file of its own in the temp directory
+fn scratch_file(name: &str, content: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("mdi-cli-{}-{}", name, std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(format!("{}.md", name));
+    fs::write(&path, content).unwrap();
+    path
+}
+
+fn mdi(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_mdi"))
+        .args(args)
+        .env_remove("MDI_FORMAT")
+        .env("RUST_BACKTRACE", "0")
+        .output()
+        .unwrap()
+}
+
+/// Run mdi, expecting success, and return what it printed
+fn stdout(args: &[&str]) -> String {
+    let output = mdi(args);
+    assert!(
+        output.status.success(),
+        "mdi {:?} failed: {}",
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+/// Run mdi, expecting failure, and return its error output
+fn stderr(args: &[&str]) -> String {
+    let output = mdi(args);
+    assert!(!output.status.success(), "mdi {:?} succeeded", args);
+    String::from_utf8(output.stderr).unwrap()
+}
+
+#[test]
+fn test_search() {
+    let path = scratch_file("search", GUIDE);
+    let file = path.to_str().unwrap();
+
+    assert_eq!(
+        stdout(&["search", file, "run"]),
+        "Guide > Install:\n  line 10: Run the installer.\n\nGuide > Usage:\n  line 23: // run with cargo run\n  line 27: Run it.\n"
+    );
+    assert_eq!(
+        stdout(&["search", file, "RUN", "--skip-code"]),
+        "Guide > Install:\n  line 10: Run the installer.\n\nGuide > Usage:\n  line 27: Run it.\n"
+    );
+    assert_eq!(stdout(&["search", file, "nothing"]), "");
+    assert!(stderr(&["search", "missing.md", "run"]).contains("missing.md"));
+
+    fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_links() {
+    let path = scratch_file("links", GUIDE);
+    let file = path.to_str().unwrap();
+
+    assert_eq!(
+        stdout(&["links", file]),
+        "   6: link -> #usage\n   6: site -> https://example.com\n  18: missing -> #nowhere\n"
+    );
+    assert_eq!(
+        stdout(&["links", file, "--external"]),
+        "   6: site -> https://example.com\n"
+    );
+    assert_eq!(
+        stdout(&["links", file, "--internal", "-s", "Install"]),
+        "  18: missing -> #nowhere\n"
+    );
+
+    let output = mdi(&["links", file, "--check"]);
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "  18: #nowhere (no matching heading)\n"
+    );
+    assert!(String::from_utf8_lossy(&output.stderr).contains("1 broken link(s)"));
+
+    fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_code() {
+    let path = scratch_file("code", GUIDE);
+    let file = path.to_str().unwrap();
+
+    assert_eq!(
+        stdout(&["code", file]),
+        "make install\n\n// run with cargo run\nfn main() {}\n"
+    );
+    assert_eq!(
+        stdout(&["code", file, "--lang", "rust"]),
+        "// run with cargo run\nfn main() {}\n"
+    );
+    assert_eq!(stdout(&["code", file, "-s", "Install"]), "make install\n");
+    assert_eq!(
+        stdout(&["code", file, "-i", "2"]),
+        "// run with cargo run\nfn main() {}\n"
+    );
+    assert_eq!(stdout(&["code", file, "--lang", "python"]), "");
+    assert!(stderr(&["code", file, "-i", "5"]).contains("No code block 5 (2 matching)"));
+
+    fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_stats() {
+    let path = scratch_file("stats", GUIDE);
+    let file = path.to_str().unwrap();
+
+    let table = " LINE  WORDS  LINES  CODE LINKS  SECTION
+    4     19     24     2     3  Guide
+    8      9     12     1     1    Install
+   16      5      4     0     1      Linux
+   20      3      8     1     0    Usage
+";
+    assert_eq!(stdout(&["stats", file]), table);
+    assert_eq!(stdout(&["stats", file, "-f", "table"]), table);
+
+    #[cfg(feature = "json")]
+    {
+        let json: serde_json::Value =
+            serde_json::from_str(&stdout(&["stats", file, "-f", "json"])).unwrap();
+        assert_eq!(json.as_array().unwrap().len(), 4);
+        assert_eq!(json[1]["text"], "Install");
+        assert_eq!(json[1]["lines"], 12);
+        assert_eq!(json[1]["code_blocks"], 1);
+    }
+    #[cfg(not(feature = "json"))]
+    assert!(stderr(&["stats", file, "-f", "json"]).contains("`json` feature"));
+
+    fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_diff() {
+    let old = scratch_file("diff-old", "# Guide\n\n## Setup\n\n## Usage\n\n## Legacy\n");
+    let new = scratch_file(
+        "diff-new",
+        "# Guide\n\n## Set up\n\n## Usage\n\n### Examples\n",
+    );
+    let (old, new) = (old.to_str().unwrap(), new.to_str().unwrap());
+
+    assert_eq!(
+        stdout(&["diff", old, new]),
+        "removed    line 7 ## Legacy\nadded      line 7 ### Examples\nrenamed    line 3 ## Setup -> line 3 ## Set up\n"
+    );
+    assert_eq!(stdout(&["diff", old, old]), "");
+
+    for file in [old, new] {
+        fs::remove_dir_all(PathBuf::from(file).parent().unwrap()).unwrap();
+    }
+}
+
+#[test]
+fn test_tree() {
+    let path = scratch_file("tree", GUIDE);
+    let file = path.to_str().unwrap();
+
+    assert_eq!(
+        stdout(&["tree", file]),
+        format!(
+            "{}\n└── Guide\n    ├── Install\n    │   └── Linux\n    └── Usage\n",
+            file
+        )
+    );
+    assert_eq!(
+        stdout(&["tree", file, "--lines", "-d", "2"]),
+        format!(
+            "{}\n└── Guide (24)\n    ├── Install (12)\n    └── Usage (8)\n",
+            file
+        )
+    );
+
+    fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_anchors() {
+    let path = scratch_file(
+        "anchors",
+        "# Guide\n\n## Install {#setup}\n\n## FAQ\n\n## FAQ\n",
+    );
+    let file = path.to_str().unwrap();
+
+    assert_eq!(
+        stdout(&["anchors", file]),
+        "   1: #guide  Guide\n   3: #setup  Install  (explicit id)\n   5: #faq    FAQ\n   7: #faq-1  FAQ  (collides with line 5)\n"
+    );
+
+    fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_frontmatter() {
+    let yaml = scratch_file("frontmatter-yaml", GUIDE);
+    let toml = scratch_file(
+        "frontmatter-toml",
+        "+++\ntitle = \"T\"\n[extra]\nn = 2\n+++\n# X\n",
+    );
+    let bare = scratch_file("frontmatter-bare", "# No front matter\n");
+    let (yaml, toml, bare) = (
+        yaml.to_str().unwrap(),
+        toml.to_str().unwrap(),
+        bare.to_str().unwrap(),
+    );
+
+    assert_eq!(stdout(&["frontmatter", yaml]), "title: Guide\n");
+    assert_eq!(
+        stdout(&["frontmatter", toml]),
+        "title = \"T\"\n[extra]\nn = 2\n"
+    );
+    assert!(stderr(&["frontmatter", bare]).contains("No front matter found"));
+
+    #[cfg(feature = "yaml")]
+    {
+        assert_eq!(stdout(&["frontmatter", yaml, "title"]), "Guide\n");
+        assert!(stderr(&["frontmatter", yaml, "nope"]).contains("Key not found: nope"));
+    }
+    #[cfg(feature = "toml")]
+    assert_eq!(stdout(&["frontmatter", toml, "extra.n"]), "2\n");
+
+    for file in [yaml, toml, bare] {
+        fs::remove_dir_all(PathBuf::from(file).parent().unwrap()).unwrap();
+    }
+}
+
+#[test]
+fn test_insert() {
+    let content = "# Guide\n\n## Install\n\n## Usage\n";
+    let path = scratch_file("insert", content);
+    let file = path.to_str().unwrap();
+    let body = scratch_file("insert-body", "Edit it.\n\n# Options\n");
+
+    assert_eq!(
+        stdout(&[
+            "insert",
+            file,
+            "--after",
+            "Install",
+            "-t",
+            "Config",
+            "--dry-run"
+        ]),
+        "# Guide\n\n## Install\n\n## Config\n\n## Usage\n"
+    );
+    assert_eq!(
+        stdout(&[
+            "insert",
+            file,
+            "--child-of",
+            "Usage",
+            "-t",
+            "Examples",
+            "--body",
+            body.to_str().unwrap(),
+            "--dry-run",
+        ]),
+        "# Guide\n\n## Install\n\n## Usage\n\n### Examples\n\nEdit it.\n\n#### Options\n"
+    );
+    assert!(stderr(&["insert", file, "--after", "install", "-t", "X"]).contains("exact heading"));
+    assert!(
+        stderr(&[
+            "insert", file, "--after", "Install", "--before", "Usage", "-t", "X"
+        ])
+        .contains("cannot be used with")
+    );
+    assert_eq!(fs::read_to_string(&path).unwrap(), content);
+
+    // Without --dry-run the file is rewritten in place
+    assert_eq!(stdout(&["insert", file, "-t", "FAQ", "--level", "2"]), "");
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "# Guide\n\n## Install\n\n## Usage\n\n## FAQ\n"
+    );
+
+    for file in [&path, &body] {
+        fs::remove_dir_all(file.parent().unwrap()).unwrap();
+    }
+}
+
+#[test]
+fn test_move() {
+    let content = "# Guide\n\n## Install\n\nsteps\n\n## Usage\n\nrun\n\n## FAQ\n";
+    let path = scratch_file("move", content);
+    let file = path.to_str().unwrap();
+
+    assert_eq!(
+        stdout(&["move", file, "Install", "--after", "Usage", "--dry-run"]),
+        "# Guide\n\n## Usage\n\nrun\n\n## Install\n\nsteps\n\n## FAQ\n"
+    );
+    assert_eq!(
+        stdout(&["move", file, "FAQ", "--before", "Install", "--dry-run"]),
+        "# Guide\n\n## FAQ\n\n## Install\n\nsteps\n\n## Usage\n\nrun\n"
+    );
+    assert_eq!(
+        stdout(&["move", file, "--line", "3", "--into", "Usage", "--dry-run"]),
+        "# Guide\n\n## Usage\n\nrun\n\n### Install\n\nsteps\n\n## FAQ\n"
+    );
+    assert!(stderr(&["move", file, "Install"]).contains("required"));
+    assert!(stderr(&["move", file, "Install", "--into", "Install"]).contains("itself"));
+    assert_eq!(fs::read_to_string(&path).unwrap(), content);
+
+    assert_eq!(stdout(&["move", file, "Install", "--into", "FAQ"]), "");
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "# Guide\n\n## Usage\n\nrun\n\n## FAQ\n\n### Install\n\nsteps\n"
+    );
+
+    fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}